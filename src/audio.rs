@@ -1,25 +1,167 @@
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+use core::cmp::Ordering;
+use std::time::Duration;
+
+use crate::RiffParseError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 #[non_exhaustive]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AudioCodec {
 	Opus,
-	Vorbis
+	Vorbis,
+	/// AAC-LC.
+	Aac
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// A timed-text subtitle format, for use with [`AudioContainer::TimedText`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 #[non_exhaustive]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TimedTextFormat {
+	/// Web Video Text Tracks (`.vtt`).
+	WebVtt,
+	/// SubRip (`.srt`).
+	Srt
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[non_exhaustive]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AudioEncoding {
-	/// Signed 16-bit PCM encoding.
+	/// Signed 16-bit PCM encoding, of unspecified byte order.
+	#[deprecated = "use `AudioEncoding::PcmI16Le` or `AudioEncoding::PcmI16Be` instead"]
 	PcmI16,
-	/// 32-bit floating point PCM encoding.
+	/// Signed 16-bit little-endian PCM encoding.
+	PcmI16Le,
+	/// Signed 16-bit big-endian PCM encoding.
+	PcmI16Be,
+	/// Signed 24-bit PCM encoding, of unspecified byte order.
+	#[deprecated = "use `AudioEncoding::PcmI24Le` or `AudioEncoding::PcmI24Be` instead"]
+	PcmI24,
+	/// Signed 24-bit little-endian PCM encoding.
+	PcmI24Le,
+	/// Signed 24-bit big-endian PCM encoding.
+	PcmI24Be,
+	/// Signed 32-bit PCM encoding, of unspecified byte order.
+	#[deprecated = "use `AudioEncoding::PcmI32Le` or `AudioEncoding::PcmI32Be` instead"]
+	PcmI32,
+	/// Signed 32-bit little-endian PCM encoding.
+	PcmI32Le,
+	/// Signed 32-bit big-endian PCM encoding.
+	PcmI32Be,
+	/// 32-bit floating point PCM encoding, of unspecified byte order.
+	#[deprecated = "use `AudioEncoding::PcmF32Le` or `AudioEncoding::PcmF32Be` instead"]
 	PcmF32,
+	/// 32-bit little-endian floating point PCM encoding.
+	PcmF32Le,
+	/// 32-bit big-endian floating point PCM encoding.
+	PcmF32Be,
+	/// 64-bit floating point PCM encoding, of unspecified byte order.
+	#[deprecated = "use `AudioEncoding::PcmF64Le` or `AudioEncoding::PcmF64Be` instead"]
+	PcmF64,
+	/// 64-bit little-endian floating point PCM encoding, for research & analysis pipelines which operate on raw vocoder
+	/// output.
+	///
+	/// Most synthesisers do not support 64-bit output, so a preference for `PcmF64Le` will almost always be negotiated
+	/// down to [`AudioEncoding::PcmF32Le`]. 64-bit float is scored below 32-bit float by
+	/// [`AudioFormat::quality_score`], since the extra precision is inaudible.
+	PcmF64Le,
+	/// 64-bit big-endian floating point PCM encoding. See [`AudioEncoding::PcmF64Le`].
+	PcmF64Be,
 	/// 8-bit A-law encoding.
 	ALaw,
 	/// 8-bit μ-law encoding.
 	MuLaw
 }
 
+/// The byte order of multi-byte PCM samples.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Endianness {
+	/// Least significant byte first, as used by RIFF WAVE files & most hardware.
+	Little,
+	/// Most significant byte first, as used by RTP (`audio/L16`) & AIFF.
+	Big
+}
+
+#[allow(deprecated)]
+impl AudioEncoding {
+	/// Returns the number of bits used to store a single sample in this encoding.
+	pub fn bits_per_sample(self) -> u8 {
+		match self {
+			AudioEncoding::PcmI16 | AudioEncoding::PcmI16Le | AudioEncoding::PcmI16Be => 16,
+			AudioEncoding::PcmI24 | AudioEncoding::PcmI24Le | AudioEncoding::PcmI24Be => 24,
+			AudioEncoding::PcmI32 | AudioEncoding::PcmI32Le | AudioEncoding::PcmI32Be => 32,
+			AudioEncoding::PcmF32 | AudioEncoding::PcmF32Le | AudioEncoding::PcmF32Be => 32,
+			AudioEncoding::PcmF64 | AudioEncoding::PcmF64Le | AudioEncoding::PcmF64Be => 64,
+			AudioEncoding::ALaw | AudioEncoding::MuLaw => 8
+		}
+	}
+
+	/// Returns the byte order of samples in this encoding, or `None` for 8-bit encodings where byte order is
+	/// irrelevant (A-law & μ-law).
+	///
+	/// The deprecated unspecified-order encodings are assumed to be little-endian, except in AIFF; see
+	/// [`AudioContainer::endianness`].
+	pub fn endianness(self) -> Option<Endianness> {
+		match self {
+			AudioEncoding::PcmI16Be | AudioEncoding::PcmI24Be | AudioEncoding::PcmI32Be | AudioEncoding::PcmF32Be | AudioEncoding::PcmF64Be => {
+				Some(Endianness::Big)
+			}
+			AudioEncoding::ALaw | AudioEncoding::MuLaw => None,
+			_ => Some(Endianness::Little)
+		}
+	}
+
+	/// Maps the deprecated unspecified-order encodings ([`AudioEncoding::PcmI16`], [`AudioEncoding::PcmI24`],
+	/// [`AudioEncoding::PcmI32`], [`AudioEncoding::PcmF32`], & [`AudioEncoding::PcmF64`]) to their little-endian
+	/// counterparts. All other encodings are returned as-is.
+	pub fn normalize(self) -> AudioEncoding {
+		match self {
+			AudioEncoding::PcmI16 => AudioEncoding::PcmI16Le,
+			AudioEncoding::PcmI24 => AudioEncoding::PcmI24Le,
+			AudioEncoding::PcmI32 => AudioEncoding::PcmI32Le,
+			AudioEncoding::PcmF32 => AudioEncoding::PcmF32Le,
+			AudioEncoding::PcmF64 => AudioEncoding::PcmF64Le,
+			encoding => encoding
+		}
+	}
+
+	/// Returns `true` if the two encodings are compatible for the purposes of format negotiation.
+	///
+	/// The deprecated unspecified-order encodings match both their little-endian and big-endian counterparts; i.e.
+	/// [`AudioEncoding::PcmI16`] matches [`AudioEncoding::PcmI16Le`] and [`AudioEncoding::PcmI16Be`]. All other
+	/// encodings only match themselves.
+	pub fn matches(self, other: AudioEncoding) -> bool {
+		let is_ambiguous = |encoding| {
+			matches!(encoding, AudioEncoding::PcmI16 | AudioEncoding::PcmI24 | AudioEncoding::PcmI32 | AudioEncoding::PcmF32 | AudioEncoding::PcmF64)
+		};
+		if is_ambiguous(self) || is_ambiguous(other) {
+			let is_float = |encoding| {
+				matches!(
+					encoding,
+					AudioEncoding::PcmF32
+						| AudioEncoding::PcmF32Le
+						| AudioEncoding::PcmF32Be
+						| AudioEncoding::PcmF64
+						| AudioEncoding::PcmF64Le
+						| AudioEncoding::PcmF64Be
+				)
+			};
+			return self.bits_per_sample() == other.bits_per_sample() && is_float(self) == is_float(other) && other.endianness().is_some();
+		}
+		self == other
+	}
+
+	/// Returns the number of bytes used to store a single sample in this encoding.
+	pub fn byte_width(self) -> u8 {
+		self.bits_per_sample() / 8
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 #[non_exhaustive]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AudioChannels {
 	/// Single channel (mono) audio.
 	Mono,
@@ -27,8 +169,36 @@ pub enum AudioChannels {
 	Stereo
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+impl AudioChannels {
+	/// Returns the number of channels.
+	pub fn count(self) -> u8 {
+		match self {
+			AudioChannels::Mono => 1,
+			AudioChannels::Stereo => 2
+		}
+	}
+
+	/// Returns the channel layout with the given number of channels, or `None` if there is no such layout.
+	pub fn from_count(count: u8) -> Option<AudioChannels> {
+		match count {
+			1 => Some(AudioChannels::Mono),
+			2 => Some(AudioChannels::Stereo),
+			_ => None
+		}
+	}
+
+	/// Returns the name of the channel layout as used by ffmpeg & GStreamer, i.e. `"mono"` or `"stereo"`.
+	pub fn layout_string(self) -> &'static str {
+		match self {
+			AudioChannels::Mono => "mono",
+			AudioChannels::Stereo => "stereo"
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 #[non_exhaustive]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AudioContainer {
 	/// Containerless audio, only used with PCM, A-law, and mu-law encodings.
 	Raw(AudioEncoding),
@@ -36,23 +206,187 @@ pub enum AudioContainer {
 	Riff(AudioEncoding),
 	/// MP3 format audio.
 	Mp3,
+	/// FLAC lossless audio.
+	///
+	/// FLAC has its own internal encoding of 16 or 24-bit integer samples, and thus does not take an
+	/// [`AudioEncoding`]. In particular, A-law and μ-law cannot be used with FLAC.
+	Flac,
 	/// OGG format audio.
 	Ogg(AudioCodec),
 	/// WEBM format audio.
-	Webm(AudioCodec)
+	Webm(AudioCodec),
+	/// Audio Interchange File Format, as used by legacy Mac & iOS audio APIs.
+	///
+	/// AIFF stores big-endian samples, so it should be used with the big-endian encodings (i.e.
+	/// [`AudioEncoding::PcmI16Be`]); the deprecated unspecified-order encodings are taken to be big-endian. Floating
+	/// point samples are stored in an AIFF-C file.
+	Aiff(AudioEncoding),
+	/// Apple Core Audio Format.
+	///
+	/// Like RIFF, CAF stores uncompressed samples, but supports files longer than RIFF's 4 GiB limit. CAF is specific
+	/// to Apple platforms, and is not supported by most synthesisers.
+	Caf(AudioEncoding),
+	/// AAC-LC audio with ADTS framing.
+	Aac,
+	/// ISO Base Media (MP4/M4A) format audio.
+	///
+	/// Only [`AudioCodec::Aac`] and [`AudioCodec::Opus`] are valid codecs for MP4.
+	Mp4(AudioCodec),
+	/// Not audio, but a timed-text transcript of the synthesised speech, for synthesisers which can output subtitles
+	/// directly. Synthesis in this format yields [`UtteranceEvent::AudioChunk`](crate::UtteranceEvent::AudioChunk)s
+	/// of UTF-8 encoded subtitle text instead of audio.
+	///
+	/// The sample rate, channels & bitrate of an [`AudioFormat`] are meaningless for timed text, and are ignored; see
+	/// [`AudioFormat::timed_text`]. To generate subtitles from the boundary events of an audio stream instead, see
+	/// [`subtitles`](crate::subtitles).
+	TimedText(TimedTextFormat)
+}
+
+#[allow(deprecated)]
+impl AudioContainer {
+	/// Returns the MIME type of audio in this container, i.e. for use in a `Content-Type` header.
+	///
+	/// `audio/L16` & `audio/L24` are big-endian by definition, so little-endian raw PCM is reported as
+	/// `application/octet-stream`.
+	pub fn to_mime_type(self) -> &'static str {
+		match self {
+			AudioContainer::Raw(AudioEncoding::PcmI16 | AudioEncoding::PcmI16Be) => "audio/L16",
+			AudioContainer::Raw(AudioEncoding::PcmI24 | AudioEncoding::PcmI24Be) => "audio/L24",
+			AudioContainer::Raw(AudioEncoding::ALaw) => "audio/PCMA",
+			AudioContainer::Raw(AudioEncoding::MuLaw) => "audio/PCMU",
+			AudioContainer::Raw(_) => "application/octet-stream",
+			AudioContainer::Riff(_) => "audio/wav",
+			AudioContainer::Aiff(_) => "audio/aiff",
+			AudioContainer::Caf(_) => "audio/x-caf",
+			AudioContainer::Mp3 => "audio/mpeg",
+			AudioContainer::Flac => "audio/flac",
+			AudioContainer::Ogg(AudioCodec::Opus) => "audio/ogg; codecs=opus",
+			AudioContainer::Ogg(AudioCodec::Vorbis) => "audio/ogg; codecs=vorbis",
+			AudioContainer::Webm(AudioCodec::Opus) => "audio/webm; codecs=opus",
+			AudioContainer::Webm(AudioCodec::Vorbis) => "audio/webm; codecs=vorbis",
+			AudioContainer::Aac => "audio/aac",
+			AudioContainer::Mp4(AudioCodec::Aac) => "audio/mp4; codecs=mp4a.40.2",
+			AudioContainer::Mp4(AudioCodec::Opus) => "audio/mp4; codecs=opus",
+			AudioContainer::TimedText(TimedTextFormat::WebVtt) => "text/vtt",
+			AudioContainer::TimedText(TimedTextFormat::Srt) => "application/x-subrip",
+			// Invalid codec & container combinations.
+			AudioContainer::Ogg(AudioCodec::Aac) | AudioContainer::Webm(AudioCodec::Aac) | AudioContainer::Mp4(AudioCodec::Vorbis) => "application/octet-stream"
+		}
+	}
+
+	/// Returns `true` if this container holds uncompressed samples: [`AudioContainer::Raw`], [`AudioContainer::Riff`],
+	/// [`AudioContainer::Aiff`], or [`AudioContainer::Caf`]. This includes A-law & μ-law samples, which are companded
+	/// but not compressed.
+	pub fn is_pcm(self) -> bool {
+		matches!(self, AudioContainer::Raw(_) | AudioContainer::Riff(_) | AudioContainer::Aiff(_) | AudioContainer::Caf(_))
+	}
+
+	/// Returns the byte order of samples in this container, or `None` for compressed containers & 8-bit encodings.
+	///
+	/// This is the [endianness of the encoding](AudioEncoding::endianness), except that the deprecated
+	/// unspecified-order encodings are big-endian in [`AudioContainer::Aiff`].
+	pub fn endianness(self) -> Option<Endianness> {
+		match self {
+			AudioContainer::Raw(encoding) | AudioContainer::Riff(encoding) | AudioContainer::Caf(encoding) => encoding.endianness(),
+			AudioContainer::Aiff(encoding) => to_big_endian(encoding).endianness(),
+			_ => None
+		}
+	}
+
+	/// Returns `true` if audio in this container can be decoded progressively as it is received, without random access
+	/// to the whole file.
+	///
+	/// This is `false` only for [`AudioContainer::Mp4`], whose index (the `moov` atom) is typically written at the end
+	/// of the file unless it is fragmented. RIFF & CAF headers written before the total length is known may contain a
+	/// placeholder length, which most decoders tolerate.
+	pub fn is_streamable(self) -> bool {
+		!matches!(self, AudioContainer::Mp4(_))
+	}
+
+	/// Returns `true` if audio in this container is stored losslessly, i.e. FLAC or linear PCM in
+	/// [`AudioContainer::Raw`], [`AudioContainer::Riff`], [`AudioContainer::Aiff`], or [`AudioContainer::Caf`]. A-law &
+	/// μ-law are considered lossy.
+	pub fn is_lossless(self) -> bool {
+		match self {
+			AudioContainer::Raw(encoding) | AudioContainer::Riff(encoding) | AudioContainer::Aiff(encoding) | AudioContainer::Caf(encoding) => {
+				!matches!(encoding, AudioEncoding::ALaw | AudioEncoding::MuLaw)
+			}
+			AudioContainer::Flac => true,
+			_ => false
+		}
+	}
+
+	/// Returns `true` if this is [`AudioContainer::Raw`], i.e. audio samples without any container or framing.
+	pub fn is_containerless(self) -> bool {
+		matches!(self, AudioContainer::Raw(_))
+	}
+
+	/// Returns `true` if the two containers are compatible for the purposes of format negotiation, i.e. if they are the
+	/// same container and their encodings [match](AudioEncoding::matches).
+	pub fn matches(self, other: AudioContainer) -> bool {
+		match (self, other) {
+			(AudioContainer::Raw(a), AudioContainer::Raw(b))
+			| (AudioContainer::Riff(a), AudioContainer::Riff(b))
+			| (AudioContainer::Aiff(a), AudioContainer::Aiff(b))
+			| (AudioContainer::Caf(a), AudioContainer::Caf(b)) => a.matches(b),
+			(a, b) => a == b
+		}
+	}
+}
+
+/// A high-level audio quality preset, for use with [`AudioFormatPreference::with_prefer_quality`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum QualityPreset {
+	/// The smallest files possible: 64 kbps mono MP3.
+	Low,
+	/// 128 kbps stereo MP3, or 64 kbps Opus in OGG.
+	Medium,
+	/// 128 kbps stereo Opus in OGG, or FLAC.
+	High,
+	/// Lossless audio: FLAC, or 16-bit little-endian PCM in RIFF.
+	Lossless
 }
 
 /// Struct used for negotiating an audio format supported by both the application and the speech synthesiser.
 #[derive(Debug, Default, Clone)]
 #[non_exhaustive]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AudioFormatPreference {
 	pub sample_rates: Option<Vec<u32>>,
 	pub channels: Option<Vec<AudioChannels>>,
 	pub bitrates: Option<Vec<u16>>,
-	pub containers: Option<Vec<AudioContainer>>
+	pub containers: Option<Vec<AudioContainer>>,
+	/// A sample rate the negotiated format must have, i.e. for hardware which cannot resample. Unlike
+	/// [`AudioFormatPreference::sample_rates`], this is checked before any other preference.
+	pub required_sample_rate: Option<u32>,
+	/// A container the negotiated format must use (see [`AudioContainer::matches`]).
+	pub required_container: Option<AudioContainer>,
+	/// A channel layout the negotiated format must have.
+	pub required_channels: Option<AudioChannels>,
+	/// Containers the negotiated format must not use (see [`AudioContainer::matches`]), i.e. for licensing reasons.
+	pub blacklist: Option<Vec<AudioContainer>>,
+	/// The only containers the negotiated format may use (see [`AudioContainer::matches`]), i.e. those an embedded
+	/// device can decode.
+	pub whitelist: Option<Vec<AudioContainer>>
 }
 
 impl AudioFormatPreference {
+	/// Creates a preference for exactly `format`, i.e. to resume with a previously negotiated format after
+	/// reconnecting. If the format has no bitrate, no bitrate is preferred.
+	///
+	/// These are preferences only; use the `require_*` methods to fail negotiation if the format is not supported.
+	pub fn from_audio_format(format: &AudioFormat) -> Self {
+		AudioFormatPreference {
+			sample_rates: Some(vec![format.sample_rate]),
+			channels: Some(vec![format.channels]),
+			bitrates: format.bitrate.map(|bitrate| vec![bitrate]),
+			containers: Some(vec![format.container]),
+			..AudioFormatPreference::default()
+		}
+	}
+
 	pub fn with_prefer_sample_rates(mut self, pref: impl IntoIterator<Item = u32>) -> Self {
 		match self.sample_rates.as_mut() {
 			None => self.sample_rates = Some(pref.into_iter().collect()),
@@ -84,9 +418,243 @@ impl AudioFormatPreference {
 		}
 		self
 	}
+
+	/// Requires the negotiated format to have exactly the given sample rate; negotiation fails rather than selecting
+	/// another rate if it is not supported.
+	pub fn require_sample_rate(mut self, sample_rate: u32) -> Self {
+		self.required_sample_rate = Some(sample_rate);
+		self
+	}
+
+	/// Requires the negotiated format to use the given container; negotiation fails rather than selecting another
+	/// container if it is not supported.
+	pub fn require_container(mut self, container: AudioContainer) -> Self {
+		self.required_container = Some(container);
+		self
+	}
+
+	/// Requires the negotiated format to have the given channel layout; negotiation fails rather than selecting another
+	/// layout if it is not supported.
+	pub fn require_channels(mut self, channels: AudioChannels) -> Self {
+		self.required_channels = Some(channels);
+		self
+	}
+
+	/// Forbids the negotiated format from using any of the given containers, regardless of other preferences.
+	///
+	/// If a container is both blacklisted and [whitelisted](AudioFormatPreference::with_whitelist), negotiation always
+	/// fails; see [`AudioFormatPreference::has_conflicting_filters`].
+	pub fn with_blacklist(mut self, containers: Vec<AudioContainer>) -> Self {
+		self.blacklist = Some(containers);
+		self
+	}
+
+	/// Restricts the negotiated format to only the given containers, regardless of other preferences.
+	///
+	/// If a container is both [blacklisted](AudioFormatPreference::with_blacklist) and whitelisted, negotiation always
+	/// fails; see [`AudioFormatPreference::has_conflicting_filters`].
+	pub fn with_whitelist(mut self, containers: Vec<AudioContainer>) -> Self {
+		self.whitelist = Some(containers);
+		self
+	}
+
+	/// Returns `true` if any whitelisted container is also blacklisted, in which case the preference is considered
+	/// invalid and no format meets its requirements.
+	pub fn has_conflicting_filters(&self) -> bool {
+		match (&self.whitelist, &self.blacklist) {
+			(Some(whitelist), Some(blacklist)) => whitelist.iter().any(|a| blacklist.iter().any(|b| a.matches(*b))),
+			_ => false
+		}
+	}
+
+	/// Returns `true` if `format` meets every hard requirement of this preference (see
+	/// [`AudioFormatPreference::require_sample_rate`], [`AudioFormatPreference::require_container`],
+	/// [`AudioFormatPreference::require_channels`], [`AudioFormatPreference::with_blacklist`], &
+	/// [`AudioFormatPreference::with_whitelist`]). No format meets the requirements of a preference with
+	/// [conflicting filters](AudioFormatPreference::has_conflicting_filters).
+	///
+	/// Synthesisers implementing
+	/// [`SpeechSynthesiser::negotiate_audio_format`](crate::SpeechSynthesiser::negotiate_audio_format) manually should
+	/// check this before applying any other preference, and return `None` if no supported format meets
+	/// the requirements.
+	pub fn meets_requirements(&self, format: &AudioFormat) -> bool {
+		self.required_sample_rate.map_or(true, |rate| format.sample_rate == rate)
+			&& self.required_channels.map_or(true, |channels| format.channels == channels)
+			&& self.required_container.map_or(true, |container| container.matches(format.container))
+			&& self
+				.blacklist
+				.as_ref()
+				.map_or(true, |blacklist| !blacklist.iter().any(|c| c.matches(format.container)))
+			&& self
+				.whitelist
+				.as_ref()
+				.map_or(true, |whitelist| whitelist.iter().any(|c| c.matches(format.container)))
+			&& !self.has_conflicting_filters()
+	}
+
+	/// Adds the containers, bitrates, and channel layouts of a [`QualityPreset`] to this preference, in priority order.
+	///
+	/// Like the other `with_prefer_*` methods, these are appended after any existing preferences.
+	pub fn with_prefer_quality(self, quality: QualityPreset) -> Self {
+		match quality {
+			QualityPreset::Low => self
+				.with_prefer_containers([AudioContainer::Mp3])
+				.with_prefer_bitrates([64])
+				.with_prefer_channels([AudioChannels::Mono]),
+			QualityPreset::Medium => self
+				.with_prefer_containers([AudioContainer::Mp3, AudioContainer::Ogg(AudioCodec::Opus)])
+				.with_prefer_bitrates([128, 64])
+				.with_prefer_channels([AudioChannels::Stereo, AudioChannels::Mono]),
+			QualityPreset::High => self
+				.with_prefer_containers([AudioContainer::Ogg(AudioCodec::Opus), AudioContainer::Flac])
+				.with_prefer_bitrates([128])
+				.with_prefer_channels([AudioChannels::Stereo]),
+			QualityPreset::Lossless => self
+				.with_prefer_containers([AudioContainer::Flac, AudioContainer::Riff(AudioEncoding::PcmI16Le)])
+				.with_prefer_channels([AudioChannels::Stereo])
+		}
+	}
+
+	/// Selects the best of a synthesiser's supported formats according to this preference, i.e. to implement
+	/// [`SpeechSynthesiser::negotiate_audio_format`](crate::SpeechSynthesiser::negotiate_audio_format).
+	///
+	/// Candidates which do not meet the [requirements](AudioFormatPreference::meets_requirements) of this preference
+	/// are rejected first. Candidates which do not match a requested sample rate, channel layout, or container (see
+	/// [`AudioContainer::matches`]) are rejected. Bitrate is
+	/// treated as a soft preference: of the remaining candidates, those with a bitrate closest to a requested bitrate
+	/// are preferred, favouring a higher bitrate over an equally close lower one. Ties are broken by
+	/// [`AudioFormat::quality_score`].
+	pub fn select_best(&self, candidates: &[AudioFormat]) -> Option<AudioFormat> {
+		candidates
+			.iter()
+			.filter(|format| self.meets_requirements(format))
+			.filter(|format| {
+				accepts(&self.sample_rates, &format.sample_rate)
+					&& accepts(&self.channels, &format.channels)
+					&& self
+						.containers
+						.as_ref()
+						.map_or(true, |containers| containers.iter().any(|c| c.matches(format.container)))
+			})
+			.min_by(|a, b| {
+				self.bitrate_distance(a)
+					.cmp(&self.bitrate_distance(b))
+					.then_with(|| b.quality_score().total_cmp(&a.quality_score()))
+			})
+			.cloned()
+	}
+
+	fn bitrate_distance(&self, format: &AudioFormat) -> u32 {
+		match (&self.bitrates, format.bitrate) {
+			(Some(bitrates), Some(bitrate)) if !format.container.is_lossless() => bitrates
+				.iter()
+				.map(|&pref| match bitrate.checked_sub(pref) {
+					Some(above) => above as u32 * 2,
+					None => (pref - bitrate) as u32 * 2 + 1
+				})
+				.min()
+				.unwrap_or(0),
+			_ => 0
+		}
+	}
+
+	/// Computes the intersection of two preference sets.
+	///
+	/// For each field, if both preferences specify values, the result contains only the values present in both, in
+	/// the priority order of `self`. If only one preference specifies values (i.e. the other accepts any value), the
+	/// result contains those values. If neither specifies values, the result also accepts any value.
+	///
+	/// Note that if both preferences specify values but none overlap, the resulting field will be an empty list, which
+	/// no synthesiser can satisfy. Use [`AudioFormatPreference::intersect_strict`] to detect this case.
+	///
+	/// Requirements of either preference are kept; if both preferences require a different value for the same
+	/// property, the requirement of `self` is kept. The blacklists of both preferences are combined, and the whitelist
+	/// is intersected like the other fields.
+	pub fn intersect(&self, other: &AudioFormatPreference) -> AudioFormatPreference {
+		AudioFormatPreference {
+			sample_rates: intersect_field(&self.sample_rates, &other.sample_rates),
+			channels: intersect_field(&self.channels, &other.channels),
+			bitrates: intersect_field(&self.bitrates, &other.bitrates),
+			containers: match (&self.containers, &other.containers) {
+				(Some(a), Some(b)) => Some(a.iter().filter(|x| b.iter().any(|y| x.matches(*y))).copied().collect()),
+				(a, b) => intersect_field(a, b)
+			},
+			required_sample_rate: self.required_sample_rate.or(other.required_sample_rate),
+			required_container: self.required_container.or(other.required_container),
+			required_channels: self.required_channels.or(other.required_channels),
+			blacklist: match (&self.blacklist, &other.blacklist) {
+				(Some(a), Some(b)) => Some(a.iter().chain(b.iter().filter(|x| !a.contains(x))).copied().collect()),
+				(a, b) => a.clone().or_else(|| b.clone())
+			},
+			whitelist: match (&self.whitelist, &other.whitelist) {
+				(Some(a), Some(b)) => Some(a.iter().filter(|x| b.iter().any(|y| x.matches(*y))).copied().collect()),
+				(a, b) => intersect_field(a, b)
+			}
+		}
+	}
+
+	/// Computes the intersection of two preference sets like [`AudioFormatPreference::intersect`], but returns `None`
+	/// if any field has no overlap or the two preferences have conflicting requirements, i.e. the two preferences can
+	/// never be satisfied simultaneously.
+	pub fn intersect_strict(&self, other: &AudioFormatPreference) -> Option<AudioFormatPreference> {
+		if conflicts(self.required_sample_rate, other.required_sample_rate, |a, b| a == b)
+			|| conflicts(self.required_channels, other.required_channels, |a, b| a == b)
+			|| conflicts(self.required_container, other.required_container, AudioContainer::matches)
+		{
+			return None;
+		}
+
+		let intersection = self.intersect(other);
+		if is_unsatisfiable(&intersection.sample_rates)
+			|| is_unsatisfiable(&intersection.channels)
+			|| is_unsatisfiable(&intersection.bitrates)
+			|| is_unsatisfiable(&intersection.containers)
+			|| is_unsatisfiable(&intersection.whitelist)
+			|| intersection.has_conflicting_filters()
+		{
+			return None;
+		}
+		Some(intersection)
+	}
+}
+
+impl From<&AudioFormat> for AudioFormatPreference {
+	fn from(format: &AudioFormat) -> Self {
+		AudioFormatPreference::from_audio_format(format)
+	}
 }
 
-#[derive(Debug, Clone)]
+fn accepts<T: PartialEq>(field: &Option<Vec<T>>, value: &T) -> bool {
+	field.as_ref().map_or(true, |values| values.contains(value))
+}
+
+fn conflicts<T>(a: Option<T>, b: Option<T>, compatible: impl FnOnce(T, T) -> bool) -> bool {
+	match (a, b) {
+		(Some(a), Some(b)) => !compatible(a, b),
+		_ => false
+	}
+}
+
+fn is_unsatisfiable<T>(field: &Option<Vec<T>>) -> bool {
+	field.as_ref().is_some_and(Vec::is_empty)
+}
+
+fn intersect_field<T: PartialEq + Clone>(a: &Option<Vec<T>>, b: &Option<Vec<T>>) -> Option<Vec<T>> {
+	match (a, b) {
+		(Some(a), Some(b)) => Some(a.iter().filter(|x| b.contains(x)).cloned().collect()),
+		(Some(x), None) | (None, Some(x)) => Some(x.clone()),
+		(None, None) => None
+	}
+}
+
+/// An audio format, as negotiated with
+/// [`SpeechSynthesiser::negotiate_audio_format`](crate::SpeechSynthesiser::negotiate_audio_format).
+///
+/// Formats are ordered by [quality](AudioFormat::quality_score), so the maximum of a set of formats is the highest
+/// quality. Formats of equal quality are ordered by sample rate, channels, bitrate, and finally container, so that the
+/// ordering is consistent with equality.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AudioFormat {
 	sample_rate: u32,
 	channels: AudioChannels,
@@ -104,6 +672,12 @@ impl AudioFormat {
 		}
 	}
 
+	/// Creates a format for [timed text](AudioContainer::TimedText) output in the given subtitle format. The sample
+	/// rate is zeroed, and the channels & bitrate are ignored.
+	pub fn timed_text(format: TimedTextFormat) -> Self {
+		AudioFormat::new(0, AudioChannels::Mono, None, AudioContainer::TimedText(format))
+	}
+
 	pub fn sample_rate(&self) -> u32 {
 		self.sample_rate
 	}
@@ -119,4 +693,901 @@ impl AudioFormat {
 	pub fn container(&self) -> AudioContainer {
 		self.container
 	}
+
+	/// Returns a score for comparing the quality of audio formats, where a higher score is higher quality.
+	///
+	/// The score is computed as `10 * tier + 5 * rate + 2 * stereo + 3 * depth`, where:
+	/// - `tier` is `2` for [lossless](AudioContainer::is_lossless) formats, `1` for lossy compressed formats, and `0`
+	///   for A-law & μ-law,
+	/// - `rate` is the sample rate divided by 96000 Hz, capped at `1.0`,
+	/// - `stereo` is `1` for stereo audio and `0` for mono,
+	/// - and `depth` is the bits per sample divided by 32 for PCM (`0.9` for 64-bit float, so that it is not preferred
+	///   over 32-bit float unless requested, and `0.5` for FLAC), the bitrate divided by 320 Kbps (capped at `1.0`, or
+	///   `0.5` if unknown) for lossy compressed formats, and `0` for A-law & μ-law.
+	///
+	/// The terms besides `tier` contribute at most `10` in total, so a lossless format always scores higher than a
+	/// lossy one.
+	pub fn quality_score(&self) -> f32 {
+		let (tier, depth) = match self.container {
+			AudioContainer::Raw(AudioEncoding::ALaw | AudioEncoding::MuLaw)
+			| AudioContainer::Riff(AudioEncoding::ALaw | AudioEncoding::MuLaw)
+			| AudioContainer::Aiff(AudioEncoding::ALaw | AudioEncoding::MuLaw)
+			| AudioContainer::Caf(AudioEncoding::ALaw | AudioEncoding::MuLaw) => (0.0, 0.0),
+			AudioContainer::Raw(encoding) | AudioContainer::Riff(encoding) | AudioContainer::Aiff(encoding) | AudioContainer::Caf(encoding) => {
+				(2.0, if encoding.bits_per_sample() == 64 { 0.9 } else { encoding.bits_per_sample() as f32 / 32.0 })
+			}
+			AudioContainer::Flac => (2.0, 0.5),
+			AudioContainer::TimedText(_) => (0.0, 0.0),
+			_ => (1.0, self.bitrate.map_or(0.5, |bitrate| (bitrate as f32 / 320.0).min(1.0)))
+		};
+		let rate = (self.sample_rate as f32 / 96000.0).min(1.0);
+		let stereo = (self.channels.count() - 1) as f32;
+		10.0 * tier + 5.0 * rate + 2.0 * stereo + 3.0 * depth
+	}
+
+	/// Returns `true` if this format holds uncompressed samples. See [`AudioContainer::is_pcm`].
+	pub fn is_pcm(&self) -> bool {
+		self.container.is_pcm()
+	}
+
+	/// Returns `true` if audio in this format is stored losslessly. See [`AudioContainer::is_lossless`].
+	pub fn is_lossless(&self) -> bool {
+		self.container.is_lossless()
+	}
+
+	/// Returns `true` if audio in this format can be decoded progressively. See [`AudioContainer::is_streamable`].
+	pub fn is_streamable(&self) -> bool {
+		self.container.is_streamable()
+	}
+
+	/// Returns the MIME type of audio in this format, i.e. for use in a `Content-Type` header.
+	///
+	/// See [`AudioContainer::to_mime_type`].
+	pub fn to_mime_type(&self) -> &'static str {
+		self.container.to_mime_type()
+	}
+
+	/// Parses a MIME type into an audio format, on a best-effort basis. Returns `None` if the MIME type is not
+	/// recognized.
+	///
+	/// Most MIME types do not describe properties like sample rate or channel count, so fields which cannot be inferred
+	/// from the MIME type are taken from `base`. The `rate` & `channels` parameters of raw PCM MIME types (e.g.
+	/// `audio/L16; rate=16000; channels=1`) are respected.
+	pub fn from_mime_type(mime: &str, base: &AudioFormat) -> Option<AudioFormat> {
+		let mut parts = mime.split(';');
+		let essence = parts.next()?.trim().to_ascii_lowercase();
+
+		let mut format = base.clone();
+		let mut codec = None;
+		for param in parts {
+			let Some((key, value)) = param.split_once('=') else {
+				continue;
+			};
+			let value = value.trim().trim_matches('"');
+			match key.trim().to_ascii_lowercase().as_str() {
+				"codecs" => {
+					codec = match value.to_ascii_lowercase().as_str() {
+						"opus" => Some(AudioCodec::Opus),
+						"vorbis" => Some(AudioCodec::Vorbis),
+						"mp4a.40.2" | "mp4a.40" | "aac" => Some(AudioCodec::Aac),
+						_ => return None
+					};
+				}
+				"rate" => format.sample_rate = value.parse().ok()?,
+				"channels" => format.channels = AudioChannels::from_count(value.parse().ok()?)?,
+				_ => {}
+			}
+		}
+
+		let base_encoding = match base.container {
+			AudioContainer::Raw(encoding) | AudioContainer::Riff(encoding) | AudioContainer::Aiff(encoding) | AudioContainer::Caf(encoding) => encoding,
+			_ => AudioEncoding::PcmI16Le
+		};
+		let base_codec = match base.container {
+			AudioContainer::Ogg(codec) | AudioContainer::Webm(codec) | AudioContainer::Mp4(codec) => Some(codec),
+			_ => None
+		};
+		format.container = match essence.as_str() {
+			"audio/l16" => AudioContainer::Raw(AudioEncoding::PcmI16Be),
+			"audio/l24" => AudioContainer::Raw(AudioEncoding::PcmI24Be),
+			"audio/pcma" => AudioContainer::Raw(AudioEncoding::ALaw),
+			"audio/pcmu" | "audio/basic" => AudioContainer::Raw(AudioEncoding::MuLaw),
+			"audio/wav" | "audio/wave" | "audio/x-wav" | "audio/vnd.wave" => AudioContainer::Riff(base_encoding),
+			"audio/aiff" | "audio/x-aiff" => AudioContainer::Aiff(match base_encoding.endianness() {
+				Some(Endianness::Little) => AudioEncoding::PcmI16Be,
+				_ => base_encoding
+			}),
+			"audio/x-caf" => AudioContainer::Caf(base_encoding),
+			"audio/mpeg" | "audio/mp3" => AudioContainer::Mp3,
+			"audio/flac" | "audio/x-flac" => AudioContainer::Flac,
+			"audio/opus" => AudioContainer::Ogg(AudioCodec::Opus),
+			"audio/ogg" => AudioContainer::Ogg(codec.or(base_codec).unwrap_or(AudioCodec::Vorbis)),
+			"audio/webm" => AudioContainer::Webm(codec.or(base_codec).unwrap_or(AudioCodec::Opus)),
+			"audio/aac" | "audio/x-aac" | "audio/aacp" => AudioContainer::Aac,
+			"audio/mp4" | "audio/m4a" | "audio/x-m4a" => AudioContainer::Mp4(codec.or(base_codec).unwrap_or(AudioCodec::Aac)),
+			"text/vtt" => AudioContainer::TimedText(TimedTextFormat::WebVtt),
+			"application/x-subrip" | "text/srt" => AudioContainer::TimedText(TimedTextFormat::Srt),
+			_ => return None
+		};
+		Some(format)
+	}
+
+	/// Returns the size in bytes of a single frame (one sample for each channel) of audio in this format.
+	///
+	/// Returns `None` for compressed containers, where frames are not of a fixed size.
+	pub fn frame_size_bytes(&self) -> Option<u64> {
+		match self.container {
+			AudioContainer::Raw(encoding) | AudioContainer::Riff(encoding) | AudioContainer::Aiff(encoding) | AudioContainer::Caf(encoding) => {
+				Some(self.channels.count() as u64 * encoding.byte_width() as u64)
+			}
+			_ => None
+		}
+	}
+
+	/// Returns the number of bytes of audio data per second of audio in this format.
+	///
+	/// For [`AudioContainer::Raw`], [`AudioContainer::Riff`], [`AudioContainer::Aiff`], and [`AudioContainer::Caf`],
+	/// this is calculated from the sample rate, channel count, and sample encoding. Note that this does not account for
+	/// the size of the RIFF, AIFF, or CAF headers. For all other containers, this is derived from the
+	/// [bitrate](AudioFormat::bitrate), and returns `None` if the bitrate is unknown.
+	pub fn bytes_per_second(&self) -> Option<u64> {
+		match self.container {
+			AudioContainer::Raw(_) | AudioContainer::Riff(_) | AudioContainer::Aiff(_) | AudioContainer::Caf(_) => {
+				Some(self.sample_rate as u64 * self.frame_size_bytes()?)
+			}
+			_ => self.bitrate.map(|bitrate| bitrate as u64 * 1000 / 8)
+		}
+	}
+
+	/// Returns the duration of audio represented by `bytes` bytes of audio data in this format.
+	///
+	/// Returns `None` if the byte rate of this format is unknown; see [`AudioFormat::bytes_per_second`].
+	pub fn duration_from_byte_count(&self, bytes: u64) -> Option<Duration> {
+		let bytes_per_second = self.bytes_per_second()?;
+		if bytes_per_second == 0 {
+			return None;
+		}
+		let nanos = bytes as u128 * 1_000_000_000 / bytes_per_second as u128;
+		Some(Duration::new((nanos / 1_000_000_000) as u64, (nanos % 1_000_000_000) as u32))
+	}
+
+	/// Returns the offset in bytes of the frame at `millis` milliseconds into audio in this format, rounded down to a
+	/// whole number of frames.
+	///
+	/// Returns `None` for compressed containers, where byte positions do not map cleanly to time. Like
+	/// [`AudioFormat::bytes_per_second`], the offset does not account for a RIFF, AIFF, or CAF header.
+	pub fn byte_offset_for_millis(&self, millis: f32) -> Option<u64> {
+		let frame_size = self.frame_size_bytes()?;
+		let frames = (millis.max(0.0) as f64 * self.sample_rate as f64 / 1000.0).floor() as u64;
+		frames.checked_mul(frame_size)
+	}
+
+	/// Returns `true` if byte ranges of audio in this format correspond to fixed-duration ranges of samples, i.e. so
+	/// that a server can respond to HTTP `Range` requests for it. This is the case for uncompressed containers; see
+	/// [`AudioFormat::is_pcm`].
+	pub fn supports_range_requests(&self) -> bool {
+		self.is_pcm()
+	}
+
+	/// Returns the value of a `Content-Range` header for the bytes `start` to `end` (inclusive) of audio in this
+	/// format, i.e. `bytes 0-1023/4096`. If the total length of the audio is unknown, i.e. because it is still being
+	/// synthesised, `total` may be `None`, in which case it is given as `*`.
+	pub fn content_range_header(&self, start: u64, end: u64, total: Option<u64>) -> String {
+		match total {
+			Some(total) => format!("bytes {start}-{end}/{total}"),
+			None => format!("bytes {start}-{end}/*")
+		}
+	}
+
+	/// Generates `duration` worth of silent audio in this format, rounded down to a whole number of frames.
+	///
+	/// Returns `None` for compressed containers, which would require an encoder to generate silence. Like
+	/// [`AudioFormat::bytes_per_second`], the returned audio does not include a RIFF, AIFF, or CAF header.
+	pub fn silence_bytes(&self, duration: Duration) -> Option<Vec<u8>> {
+		let encoding = match self.container {
+			AudioContainer::Raw(encoding) | AudioContainer::Riff(encoding) | AudioContainer::Aiff(encoding) | AudioContainer::Caf(encoding) => encoding,
+			_ => return None
+		};
+		// Silence in G.711 is the encoding of a zero sample, which is not a zero byte.
+		let byte = match encoding {
+			AudioEncoding::ALaw => 0xD5,
+			AudioEncoding::MuLaw => 0xFF,
+			_ => 0x00
+		};
+		let frames = duration.as_nanos() * self.sample_rate as u128 / 1_000_000_000;
+		let len = usize::try_from(frames * self.frame_size_bytes()? as u128).ok()?;
+		Some(vec![byte; len])
+	}
+
+	/// Generates a 44-byte RIFF WAVE header for `data_byte_count` bytes of audio in this format, i.e. for writing
+	/// [`AudioContainer::Raw`] audio to a `.wav` file.
+	///
+	/// Returns `None` for containers other than [`AudioContainer::Raw`] and [`AudioContainer::Riff`], for big-endian
+	/// encodings, or if the total file size would not fit in a RIFF header.
+	pub fn to_wav_header(&self, data_byte_count: u32) -> Option<Vec<u8>> {
+		let encoding = match self.container {
+			AudioContainer::Raw(encoding) | AudioContainer::Riff(encoding) => encoding,
+			_ => return None
+		};
+		#[allow(deprecated)]
+		let format_tag: u16 = match encoding {
+			AudioEncoding::PcmI16
+			| AudioEncoding::PcmI16Le
+			| AudioEncoding::PcmI24
+			| AudioEncoding::PcmI24Le
+			| AudioEncoding::PcmI32
+			| AudioEncoding::PcmI32Le => 1,
+			AudioEncoding::PcmF32 | AudioEncoding::PcmF32Le | AudioEncoding::PcmF64 | AudioEncoding::PcmF64Le => 3,
+			// RIFF only supports little-endian samples.
+			AudioEncoding::PcmI16Be | AudioEncoding::PcmI24Be | AudioEncoding::PcmI32Be | AudioEncoding::PcmF32Be | AudioEncoding::PcmF64Be => return None,
+			AudioEncoding::ALaw => 6,
+			AudioEncoding::MuLaw => 7
+		};
+		let channels = self.channels.count() as u16;
+		let block_align = channels * encoding.byte_width() as u16;
+		let byte_rate = self.sample_rate.checked_mul(block_align as u32)?;
+
+		let mut header = Vec::with_capacity(44);
+		header.extend_from_slice(b"RIFF");
+		header.extend_from_slice(&data_byte_count.checked_add(36)?.to_le_bytes());
+		header.extend_from_slice(b"WAVE");
+		header.extend_from_slice(b"fmt ");
+		header.extend_from_slice(&16u32.to_le_bytes());
+		header.extend_from_slice(&format_tag.to_le_bytes());
+		header.extend_from_slice(&channels.to_le_bytes());
+		header.extend_from_slice(&self.sample_rate.to_le_bytes());
+		header.extend_from_slice(&byte_rate.to_le_bytes());
+		header.extend_from_slice(&block_align.to_le_bytes());
+		header.extend_from_slice(&(encoding.bits_per_sample() as u16).to_le_bytes());
+		header.extend_from_slice(b"data");
+		header.extend_from_slice(&data_byte_count.to_le_bytes());
+		Some(header)
+	}
+
+	/// Parses the format of a RIFF WAVE file from its header, i.e. the first 44 bytes of a standard `.wav` file. This
+	/// is the inverse of [`AudioFormat::to_wav_header`], and produces an [`AudioContainer::Riff`] format.
+	///
+	/// Any chunks preceding the `fmt ` chunk are skipped. The header need not include the `data` chunk.
+	pub fn from_riff_header(bytes: &[u8]) -> Result<AudioFormat, RiffParseError> {
+		let u16_at = |offset: usize| bytes.get(offset..offset + 2).map(|x| u16::from_le_bytes([x[0], x[1]]));
+		let u32_at = |offset: usize| bytes.get(offset..offset + 4).map(|x| u32::from_le_bytes([x[0], x[1], x[2], x[3]]));
+
+		if bytes.len() < 12 {
+			return Err(RiffParseError::InsufficientBytes);
+		}
+		if &bytes[..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+			return Err(RiffParseError::Malformed);
+		}
+		let mut offset = 12;
+		let fmt = loop {
+			let (id, size) = match (bytes.get(offset..offset + 4), u32_at(offset + 4)) {
+				(Some(id), Some(size)) => (id, size as usize),
+				_ => return Err(RiffParseError::InsufficientBytes)
+			};
+			if id == b"fmt " {
+				break offset + 8;
+			}
+			// Chunks are padded to an even length.
+			offset = offset.checked_add(8 + size + size % 2).ok_or(RiffParseError::Malformed)?;
+		};
+
+		let field = |value: Option<u16>| value.ok_or(RiffParseError::InsufficientBytes);
+		let format_tag = field(u16_at(fmt))?;
+		let channel_count = field(u16_at(fmt + 2))?;
+		let sample_rate = u32_at(fmt + 4).ok_or(RiffParseError::InsufficientBytes)?;
+		let byte_rate = u32_at(fmt + 8).ok_or(RiffParseError::InsufficientBytes)?;
+		let block_align = field(u16_at(fmt + 12))?;
+		let bits = field(u16_at(fmt + 14))?;
+
+		let encoding = match (format_tag, bits) {
+			(1, 16) => AudioEncoding::PcmI16Le,
+			(1, 24) => AudioEncoding::PcmI24Le,
+			(1, 32) => AudioEncoding::PcmI32Le,
+			(3, 32) => AudioEncoding::PcmF32Le,
+			(3, 64) => AudioEncoding::PcmF64Le,
+			(6, 8) => AudioEncoding::ALaw,
+			(7, 8) => AudioEncoding::MuLaw,
+			(1 | 3 | 6 | 7, bits) => return Err(RiffParseError::UnsupportedBitDepth(bits)),
+			(format_tag, _) => return Err(RiffParseError::UnsupportedFormatTag(format_tag))
+		};
+		let channels = u8::try_from(channel_count)
+			.ok()
+			.and_then(AudioChannels::from_count)
+			.ok_or(RiffParseError::UnsupportedChannels(channel_count))?;
+
+		let expected_block_align = channel_count * encoding.byte_width() as u16;
+		if sample_rate == 0 || block_align != expected_block_align || byte_rate != sample_rate.wrapping_mul(block_align as u32) {
+			return Err(RiffParseError::Malformed);
+		}
+		Ok(AudioFormat::new(sample_rate, channels, None, AudioContainer::Riff(encoding)))
+	}
+
+	/// Prepends a RIFF WAVE header to a buffer of headerless audio samples in this format. See
+	/// [`AudioFormat::to_wav_header`].
+	pub fn wrap_raw_in_wav(&self, pcm_data: &[u8]) -> Option<Vec<u8>> {
+		let mut wav = self.to_wav_header(u32::try_from(pcm_data.len()).ok()?)?;
+		wav.extend_from_slice(pcm_data);
+		Some(wav)
+	}
+
+	/// Generates an AIFF header (the `FORM`, `COMM`, and `SSND` chunk headers) for `data_byte_count` bytes of audio in
+	/// this format, i.e. for writing [`AudioContainer::Raw`] audio to a `.aiff` file. Floating point formats produce an
+	/// AIFF-C header, which additionally includes a `FVER` chunk.
+	///
+	/// AIFF chunks must be of an even length, so if `data_byte_count` is odd, the audio data must be followed by a
+	/// single zero pad byte.
+	///
+	/// Returns `None` for containers other than [`AudioContainer::Raw`] and [`AudioContainer::Aiff`], for little-endian
+	/// or 8-bit encodings, or if the total file size would not fit in an AIFF header.
+	pub fn to_aiff_header(&self, data_byte_count: u32) -> Option<Vec<u8>> {
+		let encoding = match self.container {
+			AudioContainer::Raw(encoding) | AudioContainer::Aiff(encoding) => to_big_endian(encoding),
+			_ => return None
+		};
+		let compression_type = match encoding {
+			AudioEncoding::PcmI16Be | AudioEncoding::PcmI24Be | AudioEncoding::PcmI32Be => None,
+			AudioEncoding::PcmF32Be => Some(b"fl32"),
+			AudioEncoding::PcmF64Be => Some(b"fl64"),
+			_ => return None
+		};
+		let channels = self.channels.count() as u16;
+		let frames = data_byte_count / (channels as u32 * encoding.byte_width() as u32);
+		// The compression type is followed by an empty Pascal string (plus its pad byte) as the compression name.
+		let comm_size: u32 = if compression_type.is_some() { 24 } else { 18 };
+		let fver_size: u32 = if compression_type.is_some() { 12 } else { 0 };
+		let form_size = data_byte_count
+			.checked_add(data_byte_count & 1)?
+			.checked_add(4 + fver_size + 8 + comm_size + 16)?;
+
+		let mut header = Vec::with_capacity(8 + form_size as usize - data_byte_count as usize);
+		header.extend_from_slice(b"FORM");
+		header.extend_from_slice(&form_size.to_be_bytes());
+		header.extend_from_slice(if compression_type.is_some() { b"AIFC" } else { b"AIFF" });
+		if compression_type.is_some() {
+			header.extend_from_slice(b"FVER");
+			header.extend_from_slice(&4u32.to_be_bytes());
+			// AIFF-C version 1.
+			header.extend_from_slice(&0xA280_5140u32.to_be_bytes());
+		}
+		header.extend_from_slice(b"COMM");
+		header.extend_from_slice(&comm_size.to_be_bytes());
+		header.extend_from_slice(&channels.to_be_bytes());
+		header.extend_from_slice(&frames.to_be_bytes());
+		header.extend_from_slice(&(encoding.bits_per_sample() as u16).to_be_bytes());
+		header.extend_from_slice(&extended_sample_rate(self.sample_rate));
+		if let Some(compression_type) = compression_type {
+			header.extend_from_slice(compression_type);
+			header.extend_from_slice(&[0, 0]);
+		}
+		header.extend_from_slice(b"SSND");
+		header.extend_from_slice(&data_byte_count.checked_add(8)?.to_be_bytes());
+		// Offset & block size.
+		header.extend_from_slice(&[0; 8]);
+		Some(header)
+	}
+
+	/// Returns the ffmpeg arguments (`-f`, `-acodec`, `-ar`, `-ac`, and `-ab` for lossy formats with a known bitrate)
+	/// which produce output in this format, i.e. when post-processing synthesised audio.
+	///
+	/// For example, 44100 Hz stereo Vorbis in OGG at 128 Kbps produces
+	/// `["-f", "ogg", "-acodec", "libvorbis", "-ar", "44100", "-ac", "2", "-ab", "128k"]`. For
+	/// [`AudioContainer::TimedText`], only `-f` & `-scodec` are returned.
+	pub fn ffmpeg_args(&self) -> Vec<String> {
+		let (format, codec) = self.ffmpeg_format(true);
+		let mut args = self.ffmpeg_common_args(format, codec);
+		if let Some(bitrate) = self.bitrate.filter(|_| !self.container.is_lossless() && !self.container.is_pcm()) {
+			args.extend(["-ab".to_string(), format!("{bitrate}k")]);
+		}
+		args
+	}
+
+	/// Returns the ffmpeg arguments (`-f`, `-acodec`, `-ar`, `-ac`) describing input in this format, to be placed
+	/// before `-i` in a transcoding pipeline. These are required for [`AudioContainer::Raw`] audio, which ffmpeg
+	/// cannot probe.
+	pub fn ffmpeg_input_args(&self) -> Vec<String> {
+		let (format, codec) = self.ffmpeg_format(false);
+		self.ffmpeg_common_args(format, codec)
+	}
+
+	/// Returns the name of the channel layout of this format. See [`AudioChannels::layout_string`].
+	pub fn channel_layout_string(&self) -> &'static str {
+		self.channels.layout_string()
+	}
+
+	/// Returns a GStreamer caps string describing audio in this format, i.e. for use with `appsrc`.
+	///
+	/// For example, 44100 Hz stereo [`AudioEncoding::PcmI16Le`] in [`AudioContainer::Raw`] produces
+	/// `"audio/x-raw, format=S16LE, rate=44100, channels=2, layout=interleaved"`, and 44100 Hz stereo MP3 produces
+	/// `"audio/mpeg, mpegversion=1, layer=3, rate=44100, channels=2"`. Formats in a container only specify the
+	/// container's media type, since the stream parameters are read from the container by the demuxer.
+	pub fn gstreamer_caps(&self) -> String {
+		let params = format!("rate={}, channels={}", self.sample_rate, self.channels.count());
+		match self.container {
+			AudioContainer::Raw(AudioEncoding::ALaw) => format!("audio/x-alaw, {params}"),
+			AudioContainer::Raw(AudioEncoding::MuLaw) => format!("audio/x-mulaw, {params}"),
+			AudioContainer::Raw(encoding) => format!("audio/x-raw, format={}, {params}, layout=interleaved", gstreamer_pcm(encoding)),
+			AudioContainer::Mp3 => format!("audio/mpeg, mpegversion=1, layer=3, {params}"),
+			AudioContainer::Aac => format!("audio/mpeg, mpegversion=4, stream-format=adts, {params}"),
+			AudioContainer::Flac => format!("audio/x-flac, {params}"),
+			AudioContainer::Riff(_) => "audio/x-wav".to_string(),
+			AudioContainer::Aiff(_) => "audio/x-aiff".to_string(),
+			AudioContainer::Caf(_) => "audio/x-caf".to_string(),
+			AudioContainer::Ogg(_) => "application/ogg".to_string(),
+			AudioContainer::Webm(_) => "audio/webm".to_string(),
+			AudioContainer::Mp4(_) => "audio/x-m4a".to_string(),
+			AudioContainer::TimedText(TimedTextFormat::WebVtt) => "text/vtt".to_string(),
+			AudioContainer::TimedText(TimedTextFormat::Srt) => "application/x-subtitle".to_string()
+		}
+	}
+
+	fn ffmpeg_common_args(&self, format: &str, codec: &str) -> Vec<String> {
+		if let AudioContainer::TimedText(_) = self.container {
+			return ["-f", format, "-scodec", codec].into_iter().map(str::to_string).collect();
+		}
+		["-f", format, "-acodec", codec, "-ar", &self.sample_rate.to_string(), "-ac", &self.channels.count().to_string()]
+			.into_iter()
+			.map(str::to_string)
+			.collect()
+	}
+
+	/// Returns the ffmpeg muxer/demuxer & encoder/decoder names for this format.
+	fn ffmpeg_format(&self, encode: bool) -> (&'static str, &'static str) {
+		let codec = |codec: AudioCodec| match (codec, encode) {
+			(AudioCodec::Opus, true) => "libopus",
+			(AudioCodec::Opus, false) => "opus",
+			(AudioCodec::Vorbis, true) => "libvorbis",
+			(AudioCodec::Vorbis, false) => "vorbis",
+			(AudioCodec::Aac, _) => "aac"
+		};
+		match self.container {
+			AudioContainer::Raw(encoding) => ffmpeg_pcm(encoding),
+			AudioContainer::Riff(encoding) => ("wav", ffmpeg_pcm(encoding).1),
+			AudioContainer::Aiff(encoding) => ("aiff", ffmpeg_pcm(to_big_endian(encoding)).1),
+			AudioContainer::Caf(encoding) => ("caf", ffmpeg_pcm(encoding).1),
+			AudioContainer::Mp3 => ("mp3", if encode { "libmp3lame" } else { "mp3" }),
+			AudioContainer::Flac => ("flac", "flac"),
+			AudioContainer::Ogg(c) => ("ogg", codec(c)),
+			AudioContainer::Webm(c) => ("webm", codec(c)),
+			AudioContainer::Aac => ("adts", "aac"),
+			AudioContainer::Mp4(c) => ("mp4", codec(c)),
+			AudioContainer::TimedText(TimedTextFormat::WebVtt) => ("webvtt", "webvtt"),
+			AudioContainer::TimedText(TimedTextFormat::Srt) => ("srt", "subrip")
+		}
+	}
+}
+
+impl PartialOrd for AudioFormat {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for AudioFormat {
+	fn cmp(&self, other: &Self) -> Ordering {
+		self.quality_score()
+			.total_cmp(&other.quality_score())
+			.then_with(|| self.sample_rate.cmp(&other.sample_rate))
+			.then_with(|| self.channels.cmp(&other.channels))
+			.then_with(|| self.bitrate.cmp(&other.bitrate))
+			.then_with(|| self.container.cmp(&other.container))
+	}
+}
+
+/// Returns the ffmpeg raw format & PCM codec names for an encoding.
+#[allow(deprecated)]
+fn ffmpeg_pcm(encoding: AudioEncoding) -> (&'static str, &'static str) {
+	match encoding {
+		AudioEncoding::PcmI16 | AudioEncoding::PcmI16Le => ("s16le", "pcm_s16le"),
+		AudioEncoding::PcmI16Be => ("s16be", "pcm_s16be"),
+		AudioEncoding::PcmI24 | AudioEncoding::PcmI24Le => ("s24le", "pcm_s24le"),
+		AudioEncoding::PcmI24Be => ("s24be", "pcm_s24be"),
+		AudioEncoding::PcmI32 | AudioEncoding::PcmI32Le => ("s32le", "pcm_s32le"),
+		AudioEncoding::PcmI32Be => ("s32be", "pcm_s32be"),
+		AudioEncoding::PcmF32 | AudioEncoding::PcmF32Le => ("f32le", "pcm_f32le"),
+		AudioEncoding::PcmF32Be => ("f32be", "pcm_f32be"),
+		AudioEncoding::PcmF64 | AudioEncoding::PcmF64Le => ("f64le", "pcm_f64le"),
+		AudioEncoding::PcmF64Be => ("f64be", "pcm_f64be"),
+		AudioEncoding::ALaw => ("alaw", "pcm_alaw"),
+		AudioEncoding::MuLaw => ("mulaw", "pcm_mulaw")
+	}
+}
+
+/// Returns the GStreamer raw audio format name for a PCM encoding.
+#[allow(deprecated)]
+fn gstreamer_pcm(encoding: AudioEncoding) -> &'static str {
+	match encoding {
+		AudioEncoding::PcmI16 | AudioEncoding::PcmI16Le => "S16LE",
+		AudioEncoding::PcmI16Be => "S16BE",
+		AudioEncoding::PcmI24 | AudioEncoding::PcmI24Le => "S24LE",
+		AudioEncoding::PcmI24Be => "S24BE",
+		AudioEncoding::PcmI32 | AudioEncoding::PcmI32Le => "S32LE",
+		AudioEncoding::PcmI32Be => "S32BE",
+		AudioEncoding::PcmF32 | AudioEncoding::PcmF32Le => "F32LE",
+		AudioEncoding::PcmF32Be => "F32BE",
+		AudioEncoding::PcmF64 | AudioEncoding::PcmF64Le => "F64LE",
+		AudioEncoding::PcmF64Be => "F64BE",
+		// Companded encodings are not raw formats in GStreamer, and are handled by the caller.
+		AudioEncoding::ALaw | AudioEncoding::MuLaw => "unknown"
+	}
+}
+
+/// Maps the deprecated unspecified-order encodings to their big-endian counterparts, as they are interpreted in AIFF.
+#[allow(deprecated)]
+fn to_big_endian(encoding: AudioEncoding) -> AudioEncoding {
+	match encoding {
+		AudioEncoding::PcmI16 => AudioEncoding::PcmI16Be,
+		AudioEncoding::PcmI24 => AudioEncoding::PcmI24Be,
+		AudioEncoding::PcmI32 => AudioEncoding::PcmI32Be,
+		AudioEncoding::PcmF32 => AudioEncoding::PcmF32Be,
+		AudioEncoding::PcmF64 => AudioEncoding::PcmF64Be,
+		encoding => encoding
+	}
+}
+
+/// Encodes a sample rate as the 80-bit IEEE 754 extended precision float used by AIFF.
+fn extended_sample_rate(sample_rate: u32) -> [u8; 10] {
+	let mut bytes = [0; 10];
+	if sample_rate != 0 {
+		let shift = (sample_rate as u64).leading_zeros();
+		let exponent = 16383 + 63 - shift as u16;
+		bytes[..2].copy_from_slice(&exponent.to_be_bytes());
+		bytes[2..].copy_from_slice(&((sample_rate as u64) << shift).to_be_bytes());
+	}
+	bytes
+}
+
+/// Returns the offset of the first byte of audio data in a RIFF WAVE file, i.e. just past the `data` chunk header, or
+/// `None` if `bytes` ends before the `data` chunk header is complete.
+pub(crate) fn riff_data_offset(bytes: &[u8]) -> Option<usize> {
+	let mut offset = 12;
+	loop {
+		let id = bytes.get(offset..offset + 4)?;
+		let size = bytes.get(offset + 4..offset + 8)?;
+		if id == b"data" {
+			return Some(offset + 8);
+		}
+		let size = u32::from_le_bytes([size[0], size[1], size[2], size[3]]) as usize;
+		// Chunks are padded to an even length.
+		offset = offset.checked_add(8 + size + size % 2)?;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+
+	use super::*;
+
+	#[test]
+	fn byte_rates() {
+		let pcm = AudioFormat::new(44100, AudioChannels::Stereo, None, AudioContainer::Raw(AudioEncoding::PcmI16Le));
+		assert_eq!(pcm.bytes_per_second(), Some(176_400));
+		assert_eq!(pcm.duration_from_byte_count(88_200), Some(Duration::from_millis(500)));
+
+		let mulaw = AudioFormat::new(8000, AudioChannels::Mono, None, AudioContainer::Riff(AudioEncoding::MuLaw));
+		assert_eq!(mulaw.bytes_per_second(), Some(8000));
+		assert_eq!(mulaw.duration_from_byte_count(2000), Some(Duration::from_millis(250)));
+
+		let mp3 = AudioFormat::new(24000, AudioChannels::Mono, Some(128), AudioContainer::Mp3);
+		assert_eq!(mp3.bytes_per_second(), Some(16_000));
+		assert_eq!(mp3.duration_from_byte_count(32_000), Some(Duration::from_secs(2)));
+
+		let unknown_bitrate = AudioFormat::new(24000, AudioChannels::Mono, None, AudioContainer::Mp3);
+		assert_eq!(unknown_bitrate.bytes_per_second(), None);
+		assert_eq!(unknown_bitrate.duration_from_byte_count(32_000), None);
+	}
+
+	#[test]
+	fn intersect_preferences() {
+		let any_rate = AudioFormatPreference::default().with_prefer_channels([AudioChannels::Stereo, AudioChannels::Mono]);
+		let app = AudioFormatPreference::default()
+			.with_prefer_sample_rates([48000, 24000])
+			.with_prefer_channels([AudioChannels::Mono]);
+
+		let intersection = any_rate.intersect(&app);
+		assert_eq!(intersection.sample_rates, Some(vec![48000, 24000]));
+		assert_eq!(intersection.channels, Some(vec![AudioChannels::Mono]));
+		assert_eq!(intersection.bitrates, None);
+		assert_eq!(app.intersect(&any_rate).sample_rates, Some(vec![48000, 24000]));
+		assert!(any_rate.intersect_strict(&app).is_some());
+	}
+
+	#[test]
+	fn intersect_preserves_priority_of_self() {
+		let a = AudioFormatPreference::default().with_prefer_sample_rates([16000, 24000, 48000]);
+		let b = AudioFormatPreference::default().with_prefer_sample_rates([48000, 16000]);
+		assert_eq!(a.intersect(&b).sample_rates, Some(vec![16000, 48000]));
+		assert_eq!(b.intersect(&a).sample_rates, Some(vec![48000, 16000]));
+	}
+
+	#[test]
+	fn intersect_strict_detects_no_overlap() {
+		let a = AudioFormatPreference::default().with_prefer_sample_rates([16000]);
+		let b = AudioFormatPreference::default().with_prefer_sample_rates([48000]);
+		assert_eq!(a.intersect(&b).sample_rates, Some(vec![]));
+		assert!(a.intersect_strict(&b).is_none());
+
+		let a = AudioFormatPreference::default().require_sample_rate(16000);
+		let b = AudioFormatPreference::default().require_sample_rate(48000);
+		assert!(a.intersect_strict(&b).is_none());
+	}
+
+	#[test]
+	fn sample_widths() {
+		assert_eq!(AudioEncoding::ALaw.bits_per_sample(), 8);
+		assert_eq!(AudioEncoding::PcmI16Le.bits_per_sample(), 16);
+		assert_eq!(AudioEncoding::PcmI24Le.bits_per_sample(), 24);
+		assert_eq!(AudioEncoding::PcmI32Le.bits_per_sample(), 32);
+		assert_eq!(AudioEncoding::PcmF32Le.bits_per_sample(), 32);
+		assert_eq!(AudioEncoding::PcmI24Be.byte_width(), 3);
+		assert_eq!(AudioEncoding::PcmF64Le.byte_width(), 8);
+		assert_eq!(AudioEncoding::PcmF64Be.endianness(), Some(Endianness::Big));
+
+		let format = AudioFormat::new(48000, AudioChannels::Stereo, None, AudioContainer::Riff(AudioEncoding::PcmI24Le));
+		assert_eq!(format.frame_size_bytes(), Some(6));
+	}
+
+	#[test]
+	#[allow(deprecated)]
+	fn unspecified_byte_order() {
+		for (encoding, little, big) in [
+			(AudioEncoding::PcmI16, AudioEncoding::PcmI16Le, AudioEncoding::PcmI16Be),
+			(AudioEncoding::PcmI24, AudioEncoding::PcmI24Le, AudioEncoding::PcmI24Be),
+			(AudioEncoding::PcmI32, AudioEncoding::PcmI32Le, AudioEncoding::PcmI32Be),
+			(AudioEncoding::PcmF32, AudioEncoding::PcmF32Le, AudioEncoding::PcmF32Be),
+			(AudioEncoding::PcmF64, AudioEncoding::PcmF64Le, AudioEncoding::PcmF64Be)
+		] {
+			assert_eq!(encoding.normalize(), little);
+			assert_eq!(to_big_endian(encoding), big);
+			assert!(encoding.matches(little) && encoding.matches(big));
+			assert!(!little.matches(big));
+			assert_eq!(AudioContainer::Raw(encoding).endianness(), Some(Endianness::Little));
+			assert_eq!(AudioContainer::Aiff(encoding).endianness(), Some(Endianness::Big));
+		}
+		assert!(!AudioEncoding::PcmI32.matches(AudioEncoding::PcmF32Le));
+	}
+
+	#[test]
+	#[allow(deprecated)]
+	fn aiff_header_for_unspecified_byte_order() {
+		let header = |encoding| AudioFormat::new(44100, AudioChannels::Mono, None, AudioContainer::Aiff(encoding)).to_aiff_header(8);
+		assert_eq!(header(AudioEncoding::PcmI32), header(AudioEncoding::PcmI32Be));
+		assert!(header(AudioEncoding::PcmI32).is_some());
+		assert_eq!(header(AudioEncoding::PcmI32Le), None);
+		assert!(header(AudioEncoding::PcmF64Be).is_some_and(|header| header.windows(4).any(|id| id == b"fl64")));
+	}
+
+	#[test]
+	fn negotiation_rejects_unsupported_bit_depth() {
+		let supported = [AudioFormat::new(24000, AudioChannels::Mono, None, AudioContainer::Raw(AudioEncoding::PcmI16Le))];
+		let pref = AudioFormatPreference::default().require_container(AudioContainer::Raw(AudioEncoding::PcmI24Le));
+		assert_eq!(pref.select_best(&supported), None);
+		let pref = AudioFormatPreference::default().with_prefer_containers([AudioContainer::Riff(AudioEncoding::PcmI24Le)]);
+		assert_eq!(pref.select_best(&supported), None);
+
+		let pref = AudioFormatPreference::default().require_container(AudioContainer::Raw(AudioEncoding::PcmI16Le));
+		assert_eq!(pref.select_best(&supported).as_ref(), supported.first());
+	}
+
+	#[test]
+	fn silence_matches_byte_rate() {
+		for encoding in [AudioEncoding::PcmI16Le, AudioEncoding::PcmI24Le, AudioEncoding::PcmF32Le] {
+			let format = AudioFormat::new(22050, AudioChannels::Stereo, None, AudioContainer::Raw(encoding));
+			let silence = format.silence_bytes(Duration::from_secs(2)).unwrap();
+			assert_eq!(silence.len() as u64, format.bytes_per_second().unwrap() * 2);
+			assert!(silence.iter().all(|&b| b == 0));
+		}
+
+		// Rounded down to a whole number of frames.
+		let format = AudioFormat::new(8000, AudioChannels::Stereo, None, AudioContainer::Riff(AudioEncoding::PcmI16Le));
+		assert_eq!(format.silence_bytes(Duration::from_micros(1100)).unwrap().len(), 8 * 4);
+	}
+
+	#[test]
+	fn companded_silence() {
+		let alaw = AudioFormat::new(8000, AudioChannels::Mono, None, AudioContainer::Raw(AudioEncoding::ALaw));
+		assert_eq!(alaw.silence_bytes(Duration::from_millis(10)), Some(vec![0xD5; 80]));
+		let mulaw = AudioFormat::new(8000, AudioChannels::Mono, None, AudioContainer::Riff(AudioEncoding::MuLaw));
+		assert_eq!(mulaw.silence_bytes(Duration::from_millis(10)), Some(vec![0xFF; 80]));
+	}
+
+	#[test]
+	fn no_silence_for_compressed_formats() {
+		let mp3 = AudioFormat::new(24000, AudioChannels::Mono, Some(64), AudioContainer::Mp3);
+		assert_eq!(mp3.silence_bytes(Duration::from_secs(1)), None);
+		let opus = AudioFormat::new(48000, AudioChannels::Mono, Some(64), AudioContainer::Ogg(AudioCodec::Opus));
+		assert_eq!(opus.silence_bytes(Duration::from_secs(1)), None);
+	}
+
+	#[test]
+	fn mp4_mime_roundtrip() {
+		let base = AudioFormat::new(48000, AudioChannels::Stereo, Some(96), AudioContainer::Mp3);
+		for container in [AudioContainer::Mp4(AudioCodec::Opus), AudioContainer::Mp4(AudioCodec::Aac), AudioContainer::Aac] {
+			let format = AudioFormat::new(48000, AudioChannels::Stereo, Some(96), container);
+			assert_eq!(AudioFormat::from_mime_type(format.to_mime_type(), &base), Some(format));
+		}
+		assert_eq!(AudioContainer::Mp4(AudioCodec::Opus).to_mime_type(), "audio/mp4; codecs=opus");
+	}
+
+	#[test]
+	fn only_raw_is_containerless() {
+		assert!(AudioContainer::Raw(AudioEncoding::PcmI16Le).is_containerless());
+		for container in [AudioContainer::Riff(AudioEncoding::PcmI16Le), AudioContainer::Aac, AudioContainer::Mp4(AudioCodec::Aac)] {
+			assert!(!container.is_containerless());
+		}
+	}
+
+	#[test]
+	fn mp4_negotiation_matches_codec() {
+		let supported = [
+			AudioFormat::new(24000, AudioChannels::Mono, Some(64), AudioContainer::Mp4(AudioCodec::Aac)),
+			AudioFormat::new(24000, AudioChannels::Mono, Some(64), AudioContainer::Ogg(AudioCodec::Opus))
+		];
+		let pref = AudioFormatPreference::default().with_prefer_containers([AudioContainer::Mp4(AudioCodec::Opus)]);
+		assert_eq!(pref.select_best(&supported), None);
+		let pref = AudioFormatPreference::default().require_container(AudioContainer::Mp4(AudioCodec::Aac));
+		assert_eq!(pref.select_best(&supported).as_ref(), supported.first());
+	}
+
+	fn u16_at(bytes: &[u8], offset: usize) -> u16 {
+		u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+	}
+
+	fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+		u32::from_le_bytes([bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]])
+	}
+
+	#[test]
+	fn wav_header_fields() {
+		let format = AudioFormat::new(44100, AudioChannels::Stereo, None, AudioContainer::Raw(AudioEncoding::PcmI24Le));
+		let header = format.to_wav_header(1000).unwrap();
+		assert_eq!(header.len(), 44);
+		assert_eq!(&header[0..4], b"RIFF");
+		assert_eq!(u32_at(&header, 4), 1036);
+		assert_eq!(&header[8..16], b"WAVEfmt ");
+		assert_eq!(u32_at(&header, 16), 16);
+		// Format tag, channels, sample rate, byte rate, block align, bits per sample.
+		assert_eq!(u16_at(&header, 20), 1);
+		assert_eq!(u16_at(&header, 22), 2);
+		assert_eq!(u32_at(&header, 24), 44100);
+		assert_eq!(u32_at(&header, 28), 44100 * 6);
+		assert_eq!(u16_at(&header, 32), 6);
+		assert_eq!(u16_at(&header, 34), 24);
+		assert_eq!(&header[36..40], b"data");
+		assert_eq!(u32_at(&header, 40), 1000);
+	}
+
+	#[test]
+	fn wav_format_tags() {
+		let tag = |encoding| {
+			let header = AudioFormat::new(8000, AudioChannels::Mono, None, AudioContainer::Riff(encoding)).to_wav_header(0)?;
+			Some(u16_at(&header, 20))
+		};
+		assert_eq!(tag(AudioEncoding::PcmI16Le), Some(1));
+		assert_eq!(tag(AudioEncoding::PcmF32Le), Some(3));
+		assert_eq!(tag(AudioEncoding::PcmF64Le), Some(3));
+		assert_eq!(tag(AudioEncoding::PcmF64Be), None);
+		assert_eq!(tag(AudioEncoding::ALaw), Some(6));
+		assert_eq!(tag(AudioEncoding::MuLaw), Some(7));
+		assert_eq!(tag(AudioEncoding::PcmI16Be), None);
+		assert_eq!(AudioFormat::new(24000, AudioChannels::Mono, Some(64), AudioContainer::Mp3).to_wav_header(0), None);
+	}
+
+	#[test]
+	fn wrap_raw_in_wav() {
+		let format = AudioFormat::new(16000, AudioChannels::Mono, None, AudioContainer::Raw(AudioEncoding::PcmI16Le));
+		let wav = format.wrap_raw_in_wav(&[1, 2, 3, 4]).unwrap();
+		assert_eq!(wav.len(), 48);
+		assert_eq!(u32_at(&wav, 40), 4);
+		assert_eq!(&wav[44..], [1, 2, 3, 4]);
+	}
+
+	#[test]
+	fn ffmpeg_output_args() {
+		let vorbis = AudioFormat::new(44100, AudioChannels::Stereo, Some(128), AudioContainer::Ogg(AudioCodec::Vorbis));
+		assert_eq!(vorbis.ffmpeg_args(), ["-f", "ogg", "-acodec", "libvorbis", "-ar", "44100", "-ac", "2", "-ab", "128k"]);
+		// Bitrate is ignored for lossless & PCM formats.
+		let flac = AudioFormat::new(48000, AudioChannels::Mono, Some(128), AudioContainer::Flac);
+		assert_eq!(flac.ffmpeg_args(), ["-f", "flac", "-acodec", "flac", "-ar", "48000", "-ac", "1"]);
+		let pcm = AudioFormat::new(16000, AudioChannels::Mono, Some(256), AudioContainer::Raw(AudioEncoding::PcmI16Le));
+		assert_eq!(pcm.ffmpeg_args(), ["-f", "s16le", "-acodec", "pcm_s16le", "-ar", "16000", "-ac", "1"]);
+		let srt = AudioFormat::timed_text(TimedTextFormat::Srt);
+		assert_eq!(srt.ffmpeg_args(), ["-f", "srt", "-scodec", "subrip"]);
+	}
+
+	#[test]
+	fn ffmpeg_args_for_every_container() {
+		let containers = [
+			(AudioContainer::Raw(AudioEncoding::MuLaw), "mulaw", "pcm_mulaw", "pcm_mulaw"),
+			(AudioContainer::Riff(AudioEncoding::PcmF32Le), "wav", "pcm_f32le", "pcm_f32le"),
+			(AudioContainer::Aiff(AudioEncoding::PcmI16Be), "aiff", "pcm_s16be", "pcm_s16be"),
+			(AudioContainer::Caf(AudioEncoding::PcmI24Le), "caf", "pcm_s24le", "pcm_s24le"),
+			(AudioContainer::Mp3, "mp3", "libmp3lame", "mp3"),
+			(AudioContainer::Flac, "flac", "flac", "flac"),
+			(AudioContainer::Ogg(AudioCodec::Opus), "ogg", "libopus", "opus"),
+			(AudioContainer::Webm(AudioCodec::Vorbis), "webm", "libvorbis", "vorbis"),
+			(AudioContainer::Aac, "adts", "aac", "aac"),
+			(AudioContainer::Mp4(AudioCodec::Aac), "mp4", "aac", "aac")
+		];
+		for (container, format, encoder, decoder) in containers {
+			let audio_format = AudioFormat::new(24000, AudioChannels::Mono, None, container);
+			assert_eq!(audio_format.ffmpeg_args(), ["-f", format, "-acodec", encoder, "-ar", "24000", "-ac", "1"], "{container:?}");
+			assert_eq!(audio_format.ffmpeg_input_args(), ["-f", format, "-acodec", decoder, "-ar", "24000", "-ac", "1"], "{container:?}");
+		}
+	}
+
+	#[test]
+	fn riff_header_roundtrip() {
+		for encoding in [
+			AudioEncoding::PcmI16Le,
+			AudioEncoding::PcmI24Le,
+			AudioEncoding::PcmF32Le,
+			AudioEncoding::PcmF64Le,
+			AudioEncoding::ALaw,
+			AudioEncoding::MuLaw
+		] {
+			let format = AudioFormat::new(22050, AudioChannels::Stereo, None, AudioContainer::Riff(encoding));
+			assert_eq!(AudioFormat::from_riff_header(&format.to_wav_header(0).unwrap()), Ok(format));
+		}
+		// Deprecated encodings are parsed as little-endian.
+		#[allow(deprecated)]
+		let format = AudioFormat::new(16000, AudioChannels::Mono, None, AudioContainer::Raw(AudioEncoding::PcmI16));
+		let parsed = AudioFormat::from_riff_header(&format.to_wav_header(0).unwrap()).unwrap();
+		assert_eq!(parsed.container(), AudioContainer::Riff(AudioEncoding::PcmI16Le));
+	}
+
+	#[test]
+	fn riff_header_multi_byte_fields() {
+		// 0x0002_B110 = 176400 Hz exercises all bytes of the sample rate field.
+		let format = AudioFormat::new(0x0002_B110, AudioChannels::Mono, None, AudioContainer::Riff(AudioEncoding::PcmI16Le));
+		let header = format.to_wav_header(0).unwrap();
+		assert_eq!(&header[24..28], [0x10, 0xB1, 0x02, 0x00]);
+		assert_eq!(AudioFormat::from_riff_header(&header).unwrap().sample_rate(), 176_400);
+	}
+
+	#[test]
+	fn riff_header_skips_leading_chunks() {
+		let format = AudioFormat::new(16000, AudioChannels::Mono, None, AudioContainer::Riff(AudioEncoding::PcmI16Le));
+		let header = format.to_wav_header(0).unwrap();
+		// An odd-length `LIST` chunk, which is padded to an even length.
+		let mut bytes = header[..12].to_vec();
+		bytes.extend_from_slice(b"LIST");
+		bytes.extend_from_slice(&3u32.to_le_bytes());
+		bytes.extend_from_slice(&[1, 2, 3, 0]);
+		bytes.extend_from_slice(&header[12..]);
+		assert_eq!(AudioFormat::from_riff_header(&bytes), Ok(format));
+	}
+
+	#[test]
+	fn riff_header_rejects_malformed() {
+		let format = AudioFormat::new(16000, AudioChannels::Mono, None, AudioContainer::Riff(AudioEncoding::PcmI16Le));
+		let header = format.to_wav_header(0).unwrap();
+
+		assert_eq!(AudioFormat::from_riff_header(&header[..8]), Err(RiffParseError::InsufficientBytes));
+		assert_eq!(AudioFormat::from_riff_header(&header[..30]), Err(RiffParseError::InsufficientBytes));
+
+		let mut not_wave = header.clone();
+		not_wave[8..12].copy_from_slice(b"AVI ");
+		assert_eq!(AudioFormat::from_riff_header(&not_wave), Err(RiffParseError::Malformed));
+
+		let mut bad_block_align = header.clone();
+		bad_block_align[32] = 4;
+		assert_eq!(AudioFormat::from_riff_header(&bad_block_align), Err(RiffParseError::Malformed));
+
+		let mut bad_byte_rate = header.clone();
+		bad_byte_rate[28] ^= 1;
+		assert_eq!(AudioFormat::from_riff_header(&bad_byte_rate), Err(RiffParseError::Malformed));
+
+		let mut extensible = header.clone();
+		extensible[20..22].copy_from_slice(&0xFFFEu16.to_le_bytes());
+		assert_eq!(AudioFormat::from_riff_header(&extensible), Err(RiffParseError::UnsupportedFormatTag(0xFFFE)));
+
+		let mut eight_bit = header.clone();
+		eight_bit[34] = 8;
+		assert_eq!(AudioFormat::from_riff_header(&eight_bit), Err(RiffParseError::UnsupportedBitDepth(8)));
+
+		let mut surround = header;
+		surround[22] = 6;
+		assert_eq!(AudioFormat::from_riff_header(&surround), Err(RiffParseError::UnsupportedChannels(6)));
+	}
 }
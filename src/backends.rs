@@ -0,0 +1,277 @@
+//! Raw, backend-specific representations of synthesiser data.
+
+use crate::{AudioChannels, AudioCodec, AudioContainer, AudioEncoding, AudioFormat, StandardViseme};
+
+/// A viseme ID as emitted by Azure Cognitive Speech Services.
+///
+/// Azure viseme IDs correspond directly to [`StandardViseme`]s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AzureViseme(pub u8);
+
+impl AzureViseme {
+	/// Maps this viseme to a [`StandardViseme`], or `None` if the ID is out of range.
+	pub fn to_standard(self) -> Option<StandardViseme> {
+		StandardViseme::from_id(self.0)
+	}
+}
+
+/// A viseme symbol as emitted by Amazon Polly, i.e. `b'p'` or `b'@'`.
+///
+/// Polly represents silence with the string `sil` rather than a single symbol; use [`StandardViseme::Silence`]
+/// directly in that case.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AmazonViseme(pub u8);
+
+impl AmazonViseme {
+	/// Maps this viseme to the closest [`StandardViseme`], or `None` if the symbol is not recognized.
+	pub fn to_standard(self) -> Option<StandardViseme> {
+		Some(match self.0 {
+			b'p' => StandardViseme::Pbm,
+			b't' => StandardViseme::Dtn,
+			b'S' => StandardViseme::ShChJhZh,
+			b'T' => StandardViseme::ThDh,
+			b'f' => StandardViseme::Fv,
+			b'k' => StandardViseme::KGNg,
+			b'i' => StandardViseme::YIyIhIx,
+			b'r' => StandardViseme::R,
+			b's' => StandardViseme::Sz,
+			b'u' => StandardViseme::WUw,
+			b'@' => StandardViseme::AeAxAh,
+			b'a' => StandardViseme::Aa,
+			b'e' => StandardViseme::EyEhUh,
+			b'E' => StandardViseme::Er,
+			b'o' => StandardViseme::Ow,
+			b'O' => StandardViseme::Ao,
+			_ => return None
+		})
+	}
+}
+
+/// Azure Cognitive Speech Services output formats, as `(identifier, sample rate, bitrate, container)`.
+///
+/// Formats with no corresponding [`AudioContainer`] (AMR-WB, SILK, and Opus without a container) are omitted. Where
+/// multiple formats differ only in bitrate, they are listed in ascending order of bitrate.
+const AZURE_FORMATS: &[(&str, u32, Option<u16>, AudioContainer)] = &[
+	("raw-8khz-8bit-mono-alaw", 8000, None, AudioContainer::Raw(AudioEncoding::ALaw)),
+	("raw-8khz-8bit-mono-mulaw", 8000, None, AudioContainer::Raw(AudioEncoding::MuLaw)),
+	("raw-8khz-16bit-mono-pcm", 8000, None, AudioContainer::Raw(AudioEncoding::PcmI16Le)),
+	("raw-16khz-16bit-mono-pcm", 16000, None, AudioContainer::Raw(AudioEncoding::PcmI16Le)),
+	("raw-22050hz-16bit-mono-pcm", 22050, None, AudioContainer::Raw(AudioEncoding::PcmI16Le)),
+	("raw-24khz-16bit-mono-pcm", 24000, None, AudioContainer::Raw(AudioEncoding::PcmI16Le)),
+	("raw-44100hz-16bit-mono-pcm", 44100, None, AudioContainer::Raw(AudioEncoding::PcmI16Le)),
+	("raw-48khz-16bit-mono-pcm", 48000, None, AudioContainer::Raw(AudioEncoding::PcmI16Le)),
+	("riff-8khz-8bit-mono-alaw", 8000, None, AudioContainer::Riff(AudioEncoding::ALaw)),
+	("riff-8khz-8bit-mono-mulaw", 8000, None, AudioContainer::Riff(AudioEncoding::MuLaw)),
+	("riff-8khz-16bit-mono-pcm", 8000, None, AudioContainer::Riff(AudioEncoding::PcmI16Le)),
+	("riff-16khz-16bit-mono-pcm", 16000, None, AudioContainer::Riff(AudioEncoding::PcmI16Le)),
+	("riff-22050hz-16bit-mono-pcm", 22050, None, AudioContainer::Riff(AudioEncoding::PcmI16Le)),
+	("riff-24khz-16bit-mono-pcm", 24000, None, AudioContainer::Riff(AudioEncoding::PcmI16Le)),
+	("riff-44100hz-16bit-mono-pcm", 44100, None, AudioContainer::Riff(AudioEncoding::PcmI16Le)),
+	("riff-48khz-16bit-mono-pcm", 48000, None, AudioContainer::Riff(AudioEncoding::PcmI16Le)),
+	("audio-16khz-32kbitrate-mono-mp3", 16000, Some(32), AudioContainer::Mp3),
+	("audio-16khz-64kbitrate-mono-mp3", 16000, Some(64), AudioContainer::Mp3),
+	("audio-16khz-128kbitrate-mono-mp3", 16000, Some(128), AudioContainer::Mp3),
+	("audio-24khz-48kbitrate-mono-mp3", 24000, Some(48), AudioContainer::Mp3),
+	("audio-24khz-96kbitrate-mono-mp3", 24000, Some(96), AudioContainer::Mp3),
+	("audio-24khz-160kbitrate-mono-mp3", 24000, Some(160), AudioContainer::Mp3),
+	("audio-48khz-96kbitrate-mono-mp3", 48000, Some(96), AudioContainer::Mp3),
+	("audio-48khz-192kbitrate-mono-mp3", 48000, Some(192), AudioContainer::Mp3),
+	("ogg-16khz-16bit-mono-opus", 16000, None, AudioContainer::Ogg(AudioCodec::Opus)),
+	("ogg-24khz-16bit-mono-opus", 24000, None, AudioContainer::Ogg(AudioCodec::Opus)),
+	("ogg-48khz-16bit-mono-opus", 48000, None, AudioContainer::Ogg(AudioCodec::Opus)),
+	("webm-16khz-16bit-mono-opus", 16000, None, AudioContainer::Webm(AudioCodec::Opus)),
+	("webm-24khz-16bit-mono-opus", 24000, None, AudioContainer::Webm(AudioCodec::Opus)),
+	("webm-24khz-16bit-24kbps-mono-opus", 24000, Some(24), AudioContainer::Webm(AudioCodec::Opus))
+];
+
+/// Parses an Azure Cognitive Speech Services output format identifier (i.e. `audio-16khz-128kbitrate-mono-mp3`) into
+/// an [`AudioFormat`], or `None` if the identifier is not recognized. Identifiers are matched case-insensitively.
+pub fn from_azure_format_string(s: &str) -> Option<AudioFormat> {
+	AZURE_FORMATS
+		.iter()
+		.find(|(name, ..)| name.eq_ignore_ascii_case(s))
+		.map(|&(_, sample_rate, bitrate, container)| AudioFormat::new(sample_rate, AudioChannels::Mono, bitrate, container))
+}
+
+/// Returns the Azure Cognitive Speech Services output format identifier for `fmt`, or `None` if Azure does not
+/// support the format.
+///
+/// If `fmt` does not specify a [bitrate](AudioFormat::bitrate) and Azure offers the format at multiple bitrates, the
+/// lowest bitrate is chosen.
+pub fn to_azure_format_string(fmt: &AudioFormat) -> Option<&'static str> {
+	if fmt.channels() != AudioChannels::Mono {
+		return None;
+	}
+	let candidates = || {
+		AZURE_FORMATS
+			.iter()
+			.filter(|&&(_, sample_rate, _, container)| sample_rate == fmt.sample_rate() && container.matches(fmt.container()))
+	};
+	candidates()
+		.find(|&&(_, _, bitrate, _)| bitrate == fmt.bitrate())
+		.or_else(|| fmt.bitrate().is_none().then(|| candidates().next()).flatten())
+		.map(|&(name, ..)| name)
+}
+
+/// Google Cloud Text-to-Speech audio encodings, as `(identifier, container)`.
+const GOOGLE_FORMATS: &[(&str, AudioContainer)] = &[
+	// Google's `LINEAR16`, `MULAW` & `ALAW` audio includes a WAV header; `PCM` does not.
+	("LINEAR16", AudioContainer::Riff(AudioEncoding::PcmI16Le)),
+	("MULAW", AudioContainer::Riff(AudioEncoding::MuLaw)),
+	("ALAW", AudioContainer::Riff(AudioEncoding::ALaw)),
+	("PCM", AudioContainer::Raw(AudioEncoding::PcmI16Le)),
+	("MP3", AudioContainer::Mp3),
+	("OGG_OPUS", AudioContainer::Ogg(AudioCodec::Opus))
+];
+
+/// Parses a Google Cloud Text-to-Speech `AudioEncoding` (i.e. `MP3`) into an [`AudioFormat`], or `None` if the
+/// encoding is not recognized.
+///
+/// Google specifies the sample rate separately from the encoding (as `sampleRateHertz`), so it must be given as
+/// `sample_rate`.
+pub fn from_google_format_string(s: &str, sample_rate: u32) -> Option<AudioFormat> {
+	GOOGLE_FORMATS
+		.iter()
+		.find(|(name, _)| name.eq_ignore_ascii_case(s))
+		.map(|&(_, container)| AudioFormat::new(sample_rate, AudioChannels::Mono, None, container))
+}
+
+/// Returns the Google Cloud Text-to-Speech `AudioEncoding` for `fmt`, or `None` if Google does not support the format.
+/// The sample rate of `fmt` should be sent separately, as `sampleRateHertz`.
+pub fn to_google_format_string(fmt: &AudioFormat) -> Option<&'static str> {
+	if fmt.channels() != AudioChannels::Mono {
+		return None;
+	}
+	GOOGLE_FORMATS
+		.iter()
+		.find(|&&(_, container)| container.matches(fmt.container()))
+		.map(|&(name, _)| name)
+}
+
+/// Amazon Polly output formats, as `(identifier, supported sample rates, container)`.
+const AMAZON_FORMATS: &[(&str, &[u32], AudioContainer)] = &[
+	("mp3", &[8000, 16000, 22050, 24000], AudioContainer::Mp3),
+	("ogg_vorbis", &[8000, 16000, 22050, 24000], AudioContainer::Ogg(AudioCodec::Vorbis)),
+	("pcm", &[8000, 16000], AudioContainer::Raw(AudioEncoding::PcmI16Le))
+];
+
+/// Parses an Amazon Polly `OutputFormat` (i.e. `mp3`) into an [`AudioFormat`], or `None` if the format is not
+/// recognized or does not support `sample_rate`.
+///
+/// Polly specifies the sample rate separately from the format (as `SampleRate`), so it must be given as
+/// `sample_rate`. The `json` format, used for speech marks, is not audio and is not recognized.
+pub fn from_amazon_format_string(s: &str, sample_rate: u32) -> Option<AudioFormat> {
+	AMAZON_FORMATS
+		.iter()
+		.find(|(name, ..)| name.eq_ignore_ascii_case(s))
+		.filter(|(_, sample_rates, _)| sample_rates.contains(&sample_rate))
+		.map(|&(_, _, container)| AudioFormat::new(sample_rate, AudioChannels::Mono, None, container))
+}
+
+/// Returns the Amazon Polly `OutputFormat` for `fmt`, or `None` if Polly does not support the format at its sample
+/// rate. The sample rate of `fmt` should be sent separately, as `SampleRate`.
+pub fn to_amazon_format_string(fmt: &AudioFormat) -> Option<&'static str> {
+	if fmt.channels() != AudioChannels::Mono {
+		return None;
+	}
+	AMAZON_FORMATS
+		.iter()
+		.find(|&&(_, sample_rates, container)| sample_rates.contains(&fmt.sample_rate()) && container.matches(fmt.container()))
+		.map(|&(name, ..)| name)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn azure_formats() {
+		// The `SpeechSynthesisOutputFormat` identifiers with a corresponding `AudioFormat`.
+		let documented = [
+			"raw-8khz-8bit-mono-alaw",
+			"raw-8khz-8bit-mono-mulaw",
+			"raw-8khz-16bit-mono-pcm",
+			"raw-16khz-16bit-mono-pcm",
+			"raw-22050hz-16bit-mono-pcm",
+			"raw-24khz-16bit-mono-pcm",
+			"raw-44100hz-16bit-mono-pcm",
+			"raw-48khz-16bit-mono-pcm",
+			"riff-8khz-8bit-mono-alaw",
+			"riff-8khz-8bit-mono-mulaw",
+			"riff-8khz-16bit-mono-pcm",
+			"riff-16khz-16bit-mono-pcm",
+			"riff-22050hz-16bit-mono-pcm",
+			"riff-24khz-16bit-mono-pcm",
+			"riff-44100hz-16bit-mono-pcm",
+			"riff-48khz-16bit-mono-pcm",
+			"audio-16khz-32kbitrate-mono-mp3",
+			"audio-16khz-64kbitrate-mono-mp3",
+			"audio-16khz-128kbitrate-mono-mp3",
+			"audio-24khz-48kbitrate-mono-mp3",
+			"audio-24khz-96kbitrate-mono-mp3",
+			"audio-24khz-160kbitrate-mono-mp3",
+			"audio-48khz-96kbitrate-mono-mp3",
+			"audio-48khz-192kbitrate-mono-mp3",
+			"ogg-16khz-16bit-mono-opus",
+			"ogg-24khz-16bit-mono-opus",
+			"ogg-48khz-16bit-mono-opus",
+			"webm-16khz-16bit-mono-opus",
+			"webm-24khz-16bit-mono-opus",
+			"webm-24khz-16bit-24kbps-mono-opus"
+		];
+		for name in documented {
+			let format = from_azure_format_string(name).unwrap_or_else(|| panic!("{name} was not recognized"));
+			assert_eq!(to_azure_format_string(&format), Some(name));
+		}
+
+		assert_eq!(
+			from_azure_format_string("Audio-24khz-96KBitRate-Mono-MP3"),
+			Some(AudioFormat::new(24000, AudioChannels::Mono, Some(96), AudioContainer::Mp3))
+		);
+		for name in ["amr-wb-16000hz", "raw-24khz-16bit-mono-truesilk", "audio-16khz-16bit-32kbps-mono-opus", "g722-16khz-64kbps"] {
+			assert_eq!(from_azure_format_string(name), None, "{name}");
+		}
+	}
+
+	#[test]
+	fn azure_bitrate_selection() {
+		let mp3 = |bitrate| AudioFormat::new(24000, AudioChannels::Mono, bitrate, AudioContainer::Mp3);
+		assert_eq!(to_azure_format_string(&mp3(None)), Some("audio-24khz-48kbitrate-mono-mp3"));
+		assert_eq!(to_azure_format_string(&mp3(Some(160))), Some("audio-24khz-160kbitrate-mono-mp3"));
+		assert_eq!(to_azure_format_string(&mp3(Some(128))), None);
+
+		let stereo = AudioFormat::new(24000, AudioChannels::Stereo, None, AudioContainer::Raw(AudioEncoding::PcmI16Le));
+		assert_eq!(to_azure_format_string(&stereo), None);
+	}
+
+	#[test]
+	fn google_formats() {
+		for name in ["LINEAR16", "MP3", "OGG_OPUS", "MULAW", "ALAW", "PCM"] {
+			let format = from_google_format_string(name, 24000).unwrap_or_else(|| panic!("{name} was not recognized"));
+			assert_eq!(format.sample_rate(), 24000);
+			assert_eq!(to_google_format_string(&format), Some(name));
+		}
+		assert_eq!(from_google_format_string("LINEAR16", 16000).unwrap().container(), AudioContainer::Riff(AudioEncoding::PcmI16Le));
+		assert_eq!(from_google_format_string("AUDIO_ENCODING_UNSPECIFIED", 24000), None);
+
+		let flac = AudioFormat::new(24000, AudioChannels::Mono, None, AudioContainer::Flac);
+		assert_eq!(to_google_format_string(&flac), None);
+	}
+
+	#[test]
+	fn amazon_formats() {
+		for (name, sample_rates) in [("mp3", &[8000, 16000, 22050, 24000][..]), ("ogg_vorbis", &[8000, 16000, 22050, 24000]), ("pcm", &[8000, 16000])] {
+			for &sample_rate in sample_rates {
+				let format = from_amazon_format_string(name, sample_rate).unwrap_or_else(|| panic!("{name} at {sample_rate} Hz was not recognized"));
+				assert_eq!(to_amazon_format_string(&format), Some(name));
+			}
+		}
+		assert_eq!(from_amazon_format_string("pcm", 24000), None);
+		assert_eq!(from_amazon_format_string("json", 16000), None);
+
+		let pcm = AudioFormat::new(24000, AudioChannels::Mono, None, AudioContainer::Raw(AudioEncoding::PcmI16Le));
+		assert_eq!(to_amazon_format_string(&pcm), None);
+	}
+}
@@ -0,0 +1,128 @@
+use core::{
+	future::Future,
+	pin::Pin,
+	task::{Context, Poll}
+};
+use std::collections::VecDeque;
+
+use futures_core::Stream;
+
+use crate::{AudioFormat, SpeechSynthesiser, UtteranceConfig, UtteranceEventStream};
+
+/// Configuration for
+/// [`SpeechSynthesiserExt::synthesise_text_chunks`](crate::SpeechSynthesiserExt::synthesise_text_chunks).
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct BatchConfig {
+	/// The maximum number of chunks to request synthesis of at once. Defaults to `4`.
+	pub max_concurrent: usize
+}
+
+impl Default for BatchConfig {
+	fn default() -> Self {
+		BatchConfig { max_concurrent: 4 }
+	}
+}
+
+impl BatchConfig {
+	/// Configures the maximum number of chunks to request synthesis of at once. A value of `0` is treated as `1`.
+	pub fn with_max_concurrent(mut self, x: usize) -> Self {
+		self.max_concurrent = x;
+		self
+	}
+}
+
+type ChunkStream<E> = Pin<Box<dyn UtteranceEventStream<E>>>;
+type StartFuture<'a, E> = Pin<Box<dyn Future<Output = Result<ChunkStream<E>, E>> + Send + 'a>>;
+
+/// Stream returned by
+/// [`SpeechSynthesiserExt::synthesise_text_chunks`](crate::SpeechSynthesiserExt::synthesise_text_chunks).
+pub(crate) struct BatchStream<'a, S: SpeechSynthesiser + ?Sized> {
+	synthesiser: &'a S,
+	audio_format: &'a AudioFormat,
+	config: &'a UtteranceConfig,
+	max_concurrent: usize,
+	chunks: VecDeque<(usize, String)>,
+	in_flight: Vec<(usize, StartFuture<'a, S::Error>)>
+}
+
+impl<'a, S: SpeechSynthesiser + Sync + ?Sized> BatchStream<'a, S> {
+	pub(crate) fn new(synthesiser: &'a S, chunks: Vec<String>, audio_format: &'a AudioFormat, config: &'a UtteranceConfig, batch: &BatchConfig) -> Self {
+		BatchStream {
+			synthesiser,
+			audio_format,
+			config,
+			max_concurrent: batch.max_concurrent.max(1),
+			chunks: chunks.into_iter().enumerate().collect(),
+			in_flight: Vec::new()
+		}
+	}
+}
+
+impl<'a, S: SpeechSynthesiser + Sync + ?Sized> Stream for BatchStream<'a, S> {
+	type Item = (usize, Result<ChunkStream<S::Error>, S::Error>);
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let this = self.get_mut();
+
+		while this.in_flight.len() < this.max_concurrent {
+			let Some((index, chunk)) = this.chunks.pop_front() else {
+				break;
+			};
+			let (synthesiser, audio_format, config) = (this.synthesiser, this.audio_format, this.config);
+			this.in_flight.push((
+				index,
+				Box::pin(async move {
+					let stream = synthesiser.synthesise_text_stream(&chunk, audio_format, config).await?;
+					Ok(Box::pin(stream) as ChunkStream<S::Error>)
+				})
+			));
+		}
+
+		for i in 0..this.in_flight.len() {
+			let (index, starting) = &mut this.in_flight[i];
+			let Poll::Ready(result) = starting.as_mut().poll(cx) else {
+				continue;
+			};
+			let index = *index;
+			drop(this.in_flight.remove(i));
+			return Poll::Ready(Some((index, result)));
+		}
+
+		if this.in_flight.is_empty() {
+			return Poll::Ready(None);
+		}
+		Poll::Pending
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use core::pin::pin;
+
+	use crate::{
+		AudioChannels, AudioContainer, AudioEncoding, AudioFormat, BatchConfig, SpeechSynthesiserExt, UtteranceConfig, stream,
+		testing::{MockError, MockSpeechSynthesiser}
+	};
+
+	#[tokio::test]
+	async fn failed_request_does_not_cancel_others() {
+		let synthesiser = MockSpeechSynthesiser::new();
+		// Only two responses are queued, so the third request fails.
+		for _ in 0..2 {
+			synthesiser.push_response(vec![0; 16], Vec::new());
+		}
+		let format = AudioFormat::new(16000, AudioChannels::Mono, None, AudioContainer::Raw(AudioEncoding::PcmI16Le));
+		let config = UtteranceConfig::default();
+		let chunks = vec!["one".to_string(), "two".to_string(), "three".to_string(), "four".to_string()];
+		let batch = synthesiser.synthesise_text_chunks(chunks, &format, &config, &BatchConfig::default().with_max_concurrent(2));
+
+		let mut batch = pin!(batch);
+		let mut results = Vec::new();
+		while let Some((index, result)) = stream::next(batch.as_mut()).await {
+			results.push((index, result.err()));
+		}
+		results.sort_by_key(|(index, _)| *index);
+		assert_eq!(results, [(0, None), (1, None), (2, Some(MockError::NoResponse)), (3, Some(MockError::NoResponse))]);
+	}
+}
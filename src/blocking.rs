@@ -0,0 +1,147 @@
+//! Synchronous wrappers for [`SpeechSynthesiser`]s, for use in applications without an async runtime.
+//!
+//! Requires the `blocking` feature.
+
+use core::{pin::Pin, time::Duration};
+use std::io;
+
+use tokio::runtime::{Builder, Runtime};
+
+use crate::{
+	AudioContainer, AudioFormat, AudioFormatPreference, LanguageInfo, SpeechSynthesiser, SsmlValidationError, SynthesiserCapabilities, UtteranceConfig,
+	UtteranceEvent, UtteranceEventStream, VoiceInfo, stream::next
+};
+
+/// Wraps a [`SpeechSynthesiser`] to provide synchronous versions of its methods.
+///
+/// Each `BlockingSpeechSynthesiser` owns a single-threaded Tokio runtime, which is used to drive the synthesiser's
+/// futures & streams. As such, its methods must **not** be called from within an async context, as this will panic.
+#[derive(Debug)]
+pub struct BlockingSpeechSynthesiser<S> {
+	inner: S,
+	runtime: Runtime
+}
+
+impl<S: SpeechSynthesiser> BlockingSpeechSynthesiser<S> {
+	/// Wraps a synthesiser.
+	///
+	/// # Panics
+	/// Panics if the internal Tokio runtime could not be created. See [`BlockingSpeechSynthesiser::try_new`] for a
+	/// fallible version.
+	pub fn new(synthesiser: S) -> Self {
+		Self::try_new(synthesiser).expect("failed to create Tokio runtime")
+	}
+
+	/// Wraps a synthesiser, returning an error if the internal Tokio runtime could not be created.
+	pub fn try_new(synthesiser: S) -> io::Result<Self> {
+		let runtime = Builder::new_current_thread().enable_all().build()?;
+		Ok(BlockingSpeechSynthesiser { inner: synthesiser, runtime })
+	}
+
+	pub fn inner(&self) -> &S {
+		&self.inner
+	}
+
+	pub fn into_inner(self) -> S {
+		self.inner
+	}
+
+	/// See [`SpeechSynthesiser::negotiate_audio_format`].
+	pub fn negotiate_audio_format(&self, pref: &AudioFormatPreference) -> Option<AudioFormat> {
+		self.inner.negotiate_audio_format(pref)
+	}
+
+	/// See [`SpeechSynthesiser::capabilities`].
+	pub fn capabilities(&self) -> SynthesiserCapabilities {
+		self.inner.capabilities()
+	}
+
+	/// See [`SpeechSynthesiser::validate_ssml`].
+	pub fn validate_ssml(&self, input: &ssml::Speak<'_>) -> Result<(), Vec<SsmlValidationError>> {
+		self.inner.validate_ssml(input)
+	}
+
+	/// See [`SpeechSynthesiser::estimate_duration`].
+	pub fn estimate_duration(&self, text: &str, config: &UtteranceConfig) -> Option<Duration> {
+		self.inner.estimate_duration(text, config)
+	}
+
+	/// See [`SpeechSynthesiser::supported_sample_rates`].
+	pub fn supported_sample_rates(&self) -> &[u32] {
+		self.inner.supported_sample_rates()
+	}
+
+	/// See [`SpeechSynthesiser::supported_containers`].
+	pub fn supported_containers(&self) -> &[AudioContainer] {
+		self.inner.supported_containers()
+	}
+
+	/// Synchronously synthesise an [`ssml`] document. See [`SpeechSynthesiser::synthesise_ssml_stream`].
+	///
+	/// Events are pulled from the underlying stream one at a time as the returned iterator is advanced.
+	pub fn synthesise_ssml_blocking(
+		&self,
+		input: &ssml::Speak<'_>,
+		audio_format: &AudioFormat,
+		config: &UtteranceConfig
+	) -> Result<impl Iterator<Item = Result<UtteranceEvent, S::Error>> + '_, S::Error> {
+		let stream = self.runtime.block_on(self.inner.synthesise_ssml_stream(input, audio_format, config))?;
+		Ok(BlockingIter {
+			runtime: &self.runtime,
+			stream: Some(Box::pin(stream))
+		})
+	}
+
+	/// Synchronously synthesise raw text. See [`SpeechSynthesiser::synthesise_text_stream`].
+	///
+	/// Events are pulled from the underlying stream one at a time as the returned iterator is advanced.
+	pub fn synthesise_text_blocking(
+		&self,
+		input: &str,
+		audio_format: &AudioFormat,
+		config: &UtteranceConfig
+	) -> Result<impl Iterator<Item = Result<UtteranceEvent, S::Error>> + '_, S::Error> {
+		let stream = self.runtime.block_on(self.inner.synthesise_text_stream(input, audio_format, config))?;
+		Ok(BlockingIter {
+			runtime: &self.runtime,
+			stream: Some(Box::pin(stream))
+		})
+	}
+
+	/// See [`SpeechSynthesiser::list_voices`].
+	pub fn list_voices(&self) -> Result<Vec<VoiceInfo>, S::Error> {
+		self.runtime.block_on(self.inner.list_voices())
+	}
+
+	/// See [`SpeechSynthesiser::supported_languages`].
+	pub fn supported_languages(&self) -> Result<Vec<LanguageInfo>, S::Error> {
+		self.runtime.block_on(self.inner.supported_languages())
+	}
+
+	/// See [`SpeechSynthesiser::health_check`].
+	pub fn health_check(&self) -> Result<(), S::Error>
+	where
+		S: Sync
+	{
+		self.runtime.block_on(self.inner.health_check())
+	}
+}
+
+struct BlockingIter<'r, E> {
+	runtime: &'r Runtime,
+	stream: Option<Pin<Box<dyn UtteranceEventStream<E>>>>
+}
+
+impl<E> Iterator for BlockingIter<'_, E> {
+	type Item = Result<UtteranceEvent, E>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let stream = self.stream.as_mut()?;
+		let item = self.runtime.block_on(next(stream.as_mut()));
+		if item.is_none() {
+			// Drop the stream so we don't poll it again after it has finished.
+			self.stream = None;
+		}
+		item
+	}
+}
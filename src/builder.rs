@@ -0,0 +1,487 @@
+//! A typestate builder for [`UtteranceConfig`], which makes some invalid configurations unrepresentable.
+//!
+//! The builder's type parameters track whether the voice, the events to emit, & the audio format have been configured:
+//! - The voice can only be configured once, either [by voice](UtteranceConfigBuilder::with_voice) (which sets the
+//!   language to the voice's locale), [by name](UtteranceConfigBuilder::with_voice_name), or [by
+//!   language](UtteranceConfigBuilder::with_language), so that a voice & a conflicting language can't both be set.
+//! - Events can only be requested once the synthesiser's capabilities are known, via
+//!   [`UtteranceConfigBuilder::with_events_for`], and are checked against them in [`UtteranceConfigBuilder::build`].
+//! - Blend shape visemes can only be requested once the negotiated audio format is known not to be PCM, via
+//!   [`UtteranceConfigBuilder::with_audio_format`].
+//!
+//! Options which are not tracked by the builder's state are configured with
+//! [`UtteranceConfigBuilder::with_options`]. The method-chaining API on [`UtteranceConfig`] itself remains available
+//! for cases where these checks are not needed.
+
+use core::{marker::PhantomData, time::Duration};
+
+use crate::{
+	AudioFormat, CancellationToken, ConfigValidationError, SentenceDetectionMode, SynthesiserCapabilities, UtteranceConfig, VoiceAge, VoiceGender, VoiceInfo,
+	VoiceRole
+};
+
+mod sealed {
+	pub trait Sealed {}
+}
+
+/// Whether the voice of an [`UtteranceConfigBuilder`] has been configured; either [`VoiceUnconfigured`] or
+/// [`VoiceConfigured`].
+pub trait VoiceState: sealed::Sealed {}
+
+/// Whether the events of an [`UtteranceConfigBuilder`] have been configured; either [`EventsUnconfigured`] or
+/// [`EventsConfigured`].
+pub trait EventState: sealed::Sealed {}
+
+/// Whether the audio format of an [`UtteranceConfigBuilder`] is known; either [`FormatUnconfigured`], [`PcmFormat`],
+/// or [`EncodedFormat`].
+pub trait FormatState: sealed::Sealed {}
+
+/// The voice has not been configured; the synthesiser's default voice will be used.
+#[derive(Debug, Clone)]
+pub enum VoiceUnconfigured {}
+
+/// The voice has been configured, and can't be configured again.
+#[derive(Debug, Clone)]
+pub enum VoiceConfigured {}
+
+/// No events have been requested; only [`UtteranceEvent::AudioChunk`](crate::UtteranceEvent::AudioChunk)s will be
+/// emitted.
+#[derive(Debug, Clone)]
+pub enum EventsUnconfigured {}
+
+/// The synthesiser's capabilities are known, so events can be requested.
+#[derive(Debug, Clone)]
+pub enum EventsConfigured {}
+
+/// The audio format is not known.
+#[derive(Debug, Clone)]
+pub enum FormatUnconfigured {}
+
+/// The audio format is [PCM](AudioFormat::is_pcm).
+#[derive(Debug, Clone)]
+pub enum PcmFormat {}
+
+/// The audio format is encoded, i.e. not [PCM](AudioFormat::is_pcm), so blend shape visemes can be requested.
+#[derive(Debug, Clone)]
+pub enum EncodedFormat {}
+
+impl sealed::Sealed for VoiceUnconfigured {}
+impl sealed::Sealed for VoiceConfigured {}
+impl sealed::Sealed for EventsUnconfigured {}
+impl sealed::Sealed for EventsConfigured {}
+impl sealed::Sealed for FormatUnconfigured {}
+impl sealed::Sealed for PcmFormat {}
+impl sealed::Sealed for EncodedFormat {}
+impl VoiceState for VoiceUnconfigured {}
+impl VoiceState for VoiceConfigured {}
+impl EventState for EventsUnconfigured {}
+impl EventState for EventsConfigured {}
+impl FormatState for FormatUnconfigured {}
+impl FormatState for PcmFormat {}
+impl FormatState for EncodedFormat {}
+
+/// A builder for [`UtteranceConfig`]; see the [module documentation](self). Created with
+/// [`UtteranceConfig::builder`].
+#[derive(Debug, Clone)]
+pub struct UtteranceConfigBuilder<V: VoiceState = VoiceUnconfigured, E: EventState = EventsUnconfigured, F: FormatState = FormatUnconfigured> {
+	config: UtteranceConfig,
+	capabilities: Option<SynthesiserCapabilities>,
+	/// Whether blend shape visemes specifically were requested, in which case basic visemes alone are not sufficient.
+	blend_shape_visemes: bool,
+	_state: PhantomData<(V, E, F)>
+}
+
+impl UtteranceConfigBuilder {
+	pub fn new() -> Self {
+		UtteranceConfigBuilder {
+			config: UtteranceConfig::default(),
+			capabilities: None,
+			blend_shape_visemes: false,
+			_state: PhantomData
+		}
+	}
+}
+
+impl Default for UtteranceConfigBuilder {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<V: VoiceState, E: EventState, F: FormatState> UtteranceConfigBuilder<V, E, F> {
+	fn into_state<V2: VoiceState, E2: EventState, F2: FormatState>(self) -> UtteranceConfigBuilder<V2, E2, F2> {
+		UtteranceConfigBuilder {
+			config: self.config,
+			capabilities: self.capabilities,
+			blend_shape_visemes: self.blend_shape_visemes,
+			_state: PhantomData
+		}
+	}
+
+	/// Configures options which are not tracked by the builder's state, i.e. the speaking rate or timeout.
+	///
+	/// The voice, language, & events can't be configured through [`UtteranceOptions`]; use the builder's own methods
+	/// for those.
+	pub fn with_options(mut self, f: impl FnOnce(UtteranceOptions) -> UtteranceOptions) -> Self {
+		self.config = f(UtteranceOptions(self.config)).0;
+		self
+	}
+
+	/// Builds the [`UtteranceConfig`], checking that all configured values are within their valid ranges (as with
+	/// [`UtteranceConfig::validate`]) and, if [events were configured](UtteranceConfigBuilder::with_events_for), that
+	/// all requested events are supported by the synthesiser (as with [`UtteranceConfig::validated_for`]).
+	///
+	/// Every problem found by either check is returned.
+	pub fn build(self) -> Result<UtteranceConfig, Vec<ConfigValidationError>> {
+		let mut errors = self.config.validate().err().unwrap_or_default();
+		if let Some(capabilities) = &self.capabilities {
+			errors.extend(self.config.validated_for(capabilities).err().unwrap_or_default());
+			if self.blend_shape_visemes && !capabilities.supports_blend_shape_visemes {
+				errors.push(ConfigValidationError::Unsupported("blend shape visemes"));
+			}
+		}
+		if errors.is_empty() { Ok(self.config) } else { Err(errors) }
+	}
+}
+
+impl<E: EventState, F: FormatState> UtteranceConfigBuilder<VoiceUnconfigured, E, F> {
+	/// Configures the voice to use for synthesis, along with its language.
+	pub fn with_voice(mut self, voice: &VoiceInfo) -> UtteranceConfigBuilder<VoiceConfigured, E, F> {
+		self.config.voice = Some(voice.name.clone());
+		self.config.language = Some(voice.locale.clone());
+		self.into_state()
+	}
+
+	/// Configures the name of the voice to use for synthesis. The language is determined by the voice.
+	pub fn with_voice_name(mut self, name: impl Into<Box<str>>) -> UtteranceConfigBuilder<VoiceConfigured, E, F> {
+		self.config.voice = Some(name.into());
+		self.into_state()
+	}
+
+	/// Configures the language to use for raw text synthesis. The voice is selected by the synthesiser, taking voice
+	/// preferences such as [`UtteranceConfig::preferred_voice_gender`] into account.
+	pub fn with_language(mut self, language: impl Into<Box<str>>) -> UtteranceConfigBuilder<VoiceConfigured, E, F> {
+		self.config.language = Some(language.into());
+		self.into_state()
+	}
+}
+
+impl<V: VoiceState, F: FormatState> UtteranceConfigBuilder<V, EventsUnconfigured, F> {
+	/// Allows events to be requested from a synthesiser with the given capabilities, i.e. as returned by
+	/// [`SpeechSynthesiser::capabilities`](crate::SpeechSynthesiser::capabilities).
+	pub fn with_events_for(mut self, capabilities: &SynthesiserCapabilities) -> UtteranceConfigBuilder<V, EventsConfigured, F> {
+		self.capabilities = Some(capabilities.clone());
+		self.into_state()
+	}
+}
+
+/// An [`UtteranceConfigBuilder`] whose audio format has been configured with
+/// [`UtteranceConfigBuilder::with_audio_format`], in the state corresponding to the kind of format.
+#[derive(Debug, Clone)]
+pub enum FormatConfigured<V: VoiceState, E: EventState> {
+	/// The format is [PCM](AudioFormat::is_pcm).
+	Pcm(UtteranceConfigBuilder<V, E, PcmFormat>),
+	/// The format is encoded, so blend shape visemes can be requested.
+	Encoded(UtteranceConfigBuilder<V, E, EncodedFormat>)
+}
+
+impl<V: VoiceState, E: EventState> UtteranceConfigBuilder<V, E, FormatUnconfigured> {
+	/// Configures the audio format that will be synthesised, i.e. as returned by
+	/// [`SpeechSynthesiser::negotiate_audio_format`](crate::SpeechSynthesiser::negotiate_audio_format). The format
+	/// itself is not part of the [`UtteranceConfig`]; it only determines which options are available.
+	pub fn with_audio_format(self, format: &AudioFormat) -> FormatConfigured<V, E> {
+		match format.is_pcm() {
+			true => FormatConfigured::Pcm(self.into_state()),
+			false => FormatConfigured::Encoded(self.into_state())
+		}
+	}
+}
+
+impl<V: VoiceState, F: FormatState> UtteranceConfigBuilder<V, EventsConfigured, F> {
+	/// Requests [`UtteranceEvent::WordBoundary`](crate::UtteranceEvent::WordBoundary) events.
+	pub fn with_emit_word_boundary_events(mut self) -> Self {
+		self.config.emit_word_boundary_events = true;
+		self
+	}
+
+	/// Requests [`UtteranceEvent::SentenceBoundary`](crate::UtteranceEvent::SentenceBoundary) events.
+	pub fn with_emit_sentence_boundary_events(mut self) -> Self {
+		self.config.emit_sentence_boundary_events = true;
+		self
+	}
+
+	/// Requests [`UtteranceEvent::ParagraphBoundary`](crate::UtteranceEvent::ParagraphBoundary) events.
+	pub fn with_emit_paragraph_boundary_events(mut self) -> Self {
+		self.config.emit_paragraph_boundary_events = true;
+		self
+	}
+
+	/// Requests [`UtteranceEvent::VisemesChunk`](crate::UtteranceEvent::VisemesChunk)/
+	/// [`UtteranceEvent::BlendShapeVisemesChunk`](crate::UtteranceEvent::BlendShapeVisemesChunk) events.
+	pub fn with_emit_visemes(mut self) -> Self {
+		self.config.emit_visemes = true;
+		self
+	}
+
+	/// Requests [`UtteranceEvent::Phoneme`](crate::UtteranceEvent::Phoneme) events.
+	pub fn with_emit_phoneme_events(mut self) -> Self {
+		self.config.emit_phoneme_events = true;
+		self
+	}
+
+	/// Requests [`UtteranceEvent::SpeakerChange`](crate::UtteranceEvent::SpeakerChange) events.
+	pub fn with_emit_speaker_change_events(mut self) -> Self {
+		self.config.emit_speaker_change_events = true;
+		self
+	}
+
+	/// Requests [`UtteranceEvent::PauseDetected`](crate::UtteranceEvent::PauseDetected) events.
+	pub fn with_emit_pause_events(mut self) -> Self {
+		self.config.emit_pause_events = true;
+		self
+	}
+
+	/// Requests [`UtteranceEvent::SsmlMark`](crate::UtteranceEvent::SsmlMark) events.
+	pub fn with_emit_ssml_marks(mut self) -> Self {
+		self.config.emit_ssml_marks = true;
+		self
+	}
+
+	/// Requests every kind of event supported by the synthesiser.
+	pub fn with_emit_supported_events(mut self) -> Self {
+		let capabilities = self.capabilities.clone().unwrap_or_default();
+		self.config.emit_word_boundary_events = capabilities.supports_word_boundary;
+		self.config.emit_sentence_boundary_events = capabilities.supports_sentence_boundary;
+		self.config.emit_paragraph_boundary_events = capabilities.supports_paragraph_boundary;
+		self.config.emit_visemes = capabilities.supports_visemes || capabilities.supports_blend_shape_visemes;
+		self.config.emit_phoneme_events = capabilities.supports_phoneme_events;
+		self.config.emit_speaker_change_events = capabilities.supports_speaker_change;
+		self.config.emit_pause_events = capabilities.supports_pause_events;
+		self.config.emit_ssml_marks = capabilities.supports_ssml_marks;
+		self
+	}
+}
+
+impl<V: VoiceState> UtteranceConfigBuilder<V, EventsConfigured, EncodedFormat> {
+	/// Requests [`UtteranceEvent::BlendShapeVisemesChunk`](crate::UtteranceEvent::BlendShapeVisemesChunk) events
+	/// specifically; [`UtteranceConfigBuilder::build`] fails if the synthesiser only supports basic visemes.
+	pub fn with_emit_blend_shape_visemes(mut self) -> Self {
+		self.config.emit_visemes = true;
+		self.blend_shape_visemes = true;
+		self
+	}
+}
+
+/// A view of an [`UtteranceConfig`] which can only configure the options not tracked by an
+/// [`UtteranceConfigBuilder`]'s state; see [`UtteranceConfigBuilder::with_options`].
+#[derive(Debug, Clone)]
+pub struct UtteranceOptions(UtteranceConfig);
+
+impl UtteranceOptions {
+	/// See [`UtteranceConfig::with_preferred_voice_gender`].
+	pub fn with_preferred_voice_gender(self, x: VoiceGender) -> Self {
+		UtteranceOptions(self.0.with_preferred_voice_gender(x))
+	}
+
+	/// See [`UtteranceConfig::with_preferred_voice_age`].
+	pub fn with_preferred_voice_age(self, x: VoiceAge) -> Self {
+		UtteranceOptions(self.0.with_preferred_voice_age(x))
+	}
+
+	/// See [`UtteranceConfig::with_voice_gender_neutral`].
+	pub fn with_voice_gender_neutral(self, x: bool) -> Self {
+		UtteranceOptions(self.0.with_voice_gender_neutral(x))
+	}
+
+	/// See [`UtteranceConfig::with_require_gender_neutral`].
+	pub fn with_require_gender_neutral(self, x: bool) -> Self {
+		UtteranceOptions(self.0.with_require_gender_neutral(x))
+	}
+
+	/// See [`UtteranceConfig::with_auto_detect_language`].
+	pub fn with_auto_detect_language(self, x: bool) -> Self {
+		UtteranceOptions(self.0.with_auto_detect_language(x))
+	}
+
+	/// See [`UtteranceConfig::with_speaking_rate`].
+	pub fn with_speaking_rate(self, x: f32) -> Self {
+		UtteranceOptions(self.0.with_speaking_rate(x))
+	}
+
+	/// See [`UtteranceConfig::with_pitch_shift_semitones`].
+	pub fn with_pitch_shift_semitones(self, x: f32) -> Self {
+		UtteranceOptions(self.0.with_pitch_shift_semitones(x))
+	}
+
+	/// See [`UtteranceConfig::with_volume_db`].
+	pub fn with_volume_db(self, x: f32) -> Self {
+		UtteranceOptions(self.0.with_volume_db(x))
+	}
+
+	/// See [`UtteranceConfig::with_sentence_detection`].
+	pub fn with_sentence_detection(self, mode: SentenceDetectionMode) -> Self {
+		UtteranceOptions(self.0.with_sentence_detection(mode))
+	}
+
+	/// See [`UtteranceConfig::with_inter_sentence_pause`].
+	pub fn with_inter_sentence_pause(self, x: Duration) -> Self {
+		UtteranceOptions(self.0.with_inter_sentence_pause(x))
+	}
+
+	/// See [`UtteranceConfig::with_break_before`].
+	pub fn with_break_before(self, x: Duration) -> Self {
+		UtteranceOptions(self.0.with_break_before(x))
+	}
+
+	/// See [`UtteranceConfig::with_break_after`].
+	pub fn with_break_after(self, x: Duration) -> Self {
+		UtteranceOptions(self.0.with_break_after(x))
+	}
+
+	/// See [`UtteranceConfig::with_silence_threshold_millis`].
+	pub fn with_silence_threshold_millis(self, x: f32) -> Self {
+		UtteranceOptions(self.0.with_silence_threshold_millis(x))
+	}
+
+	/// See [`UtteranceConfig::with_voice_style`].
+	pub fn with_voice_style(self, x: impl Into<Box<str>>) -> Self {
+		UtteranceOptions(self.0.with_voice_style(x))
+	}
+
+	/// See [`UtteranceConfig::with_voice_style_degree`].
+	pub fn with_voice_style_degree(self, x: f32) -> Self {
+		UtteranceOptions(self.0.with_voice_style_degree(x))
+	}
+
+	/// See [`UtteranceConfig::with_role`].
+	pub fn with_role(self, x: VoiceRole) -> Self {
+		UtteranceOptions(self.0.with_role(x))
+	}
+
+	/// See [`UtteranceConfig::with_pronunciation_lexicon`].
+	pub fn with_pronunciation_lexicon(self, x: impl Into<Box<str>>) -> Self {
+		UtteranceOptions(self.0.with_pronunciation_lexicon(x))
+	}
+
+	/// See [`UtteranceConfig::with_max_audio_chunk_size`].
+	pub fn with_max_audio_chunk_size(self, x: usize) -> Self {
+		UtteranceOptions(self.0.with_max_audio_chunk_size(x))
+	}
+
+	/// See [`UtteranceConfig::with_max_chars_per_request`].
+	pub fn with_max_chars_per_request(self, x: usize) -> Self {
+		UtteranceOptions(self.0.with_max_chars_per_request(x))
+	}
+
+	/// See [`UtteranceConfig::with_prefetch_chapters`].
+	pub fn with_prefetch_chapters(self, x: bool) -> Self {
+		UtteranceOptions(self.0.with_prefetch_chapters(x))
+	}
+
+	/// See [`UtteranceConfig::with_cancellation_token`].
+	pub fn with_cancellation_token(self, x: CancellationToken) -> Self {
+		UtteranceOptions(self.0.with_cancellation_token(x))
+	}
+
+	/// See [`UtteranceConfig::with_timeout`].
+	pub fn with_timeout(self, x: Duration) -> Self {
+		UtteranceOptions(self.0.with_timeout(x))
+	}
+
+	/// See [`UtteranceConfig::with_chunk_timeout`].
+	pub fn with_chunk_timeout(self, x: Duration) -> Self {
+		UtteranceOptions(self.0.with_chunk_timeout(x))
+	}
+
+	/// See [`UtteranceConfig::with_trace_id`].
+	pub fn with_trace_id(self, x: impl Into<Box<str>>) -> Self {
+		UtteranceOptions(self.0.with_trace_id(x))
+	}
+
+	/// See [`UtteranceConfig::with_request_id`].
+	pub fn with_request_id(self, x: impl Into<Box<str>>) -> Self {
+		UtteranceOptions(self.0.with_request_id(x))
+	}
+
+	/// See [`UtteranceConfig::with_custom_endpoint`].
+	pub fn with_custom_endpoint(self, url: impl Into<Box<str>>) -> Self {
+		UtteranceOptions(self.0.with_custom_endpoint(url))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use core::time::Duration;
+
+	use super::FormatConfigured;
+	use crate::{AudioChannels, AudioContainer, AudioEncoding, AudioFormat, ConfigValidationError, SynthesiserCapabilities, UtteranceConfig, VoiceInfo};
+
+	#[test]
+	fn voice_sets_language() {
+		let config = UtteranceConfig::builder()
+			.with_voice(&VoiceInfo::new("en-GB-Sonia", "en-GB"))
+			.build()
+			.unwrap();
+		assert_eq!(config.voice.as_deref(), Some("en-GB-Sonia"));
+		assert_eq!(config.language.as_deref(), Some("en-GB"));
+	}
+
+	#[test]
+	fn options_do_not_touch_tracked_fields() {
+		let config = UtteranceConfig::builder()
+			.with_language("de-DE")
+			.with_options(|options| options.with_speaking_rate(1.5).with_timeout(Duration::from_secs(5)))
+			.build()
+			.unwrap();
+		assert_eq!(config.language.as_deref(), Some("de-DE"));
+		assert_eq!(config.speaking_rate, Some(1.5));
+		assert_eq!(config.synthesis_timeout, Some(Duration::from_secs(5)));
+	}
+
+	#[test]
+	fn supported_events_follow_capabilities() {
+		let capabilities = SynthesiserCapabilities::default()
+			.with_supports_phoneme_events(false)
+			.with_supports_pause_events(false)
+			.with_supports_ssml_marks(false);
+		let config = UtteranceConfig::builder()
+			.with_events_for(&capabilities)
+			.with_emit_supported_events()
+			.build()
+			.unwrap();
+		assert!(config.emit_word_boundary_events && config.emit_visemes);
+		assert!(!config.emit_phoneme_events && !config.emit_pause_events && !config.emit_ssml_marks);
+	}
+
+	#[test]
+	fn build_reports_every_error() {
+		let capabilities = SynthesiserCapabilities::default().with_supports_word_boundary(false);
+		let result = UtteranceConfig::builder()
+			.with_events_for(&capabilities)
+			.with_emit_word_boundary_events()
+			.with_options(|options| options.with_speaking_rate(-1.0))
+			.build();
+		assert_eq!(result.unwrap_err(), [ConfigValidationError::SpeakingRateOutOfRange(-1.0), ConfigValidationError::Unsupported("word boundary events")]);
+	}
+
+	#[test]
+	fn blend_shape_visemes_require_encoded_format_and_support() {
+		let mp3 = AudioFormat::new(24000, AudioChannels::Mono, Some(64), AudioContainer::Mp3);
+		let capabilities = SynthesiserCapabilities::default().with_supports_blend_shape_visemes(false);
+		let FormatConfigured::Encoded(builder) = UtteranceConfig::builder().with_events_for(&capabilities).with_audio_format(&mp3) else {
+			panic!("MP3 is not PCM");
+		};
+		assert_eq!(builder.with_emit_blend_shape_visemes().build().unwrap_err(), [ConfigValidationError::Unsupported("blend shape visemes")]);
+
+		// Basic visemes are still sufficient for `with_emit_visemes`.
+		let config = UtteranceConfig::builder()
+			.with_events_for(&capabilities)
+			.with_emit_visemes()
+			.build()
+			.unwrap();
+		assert!(config.emit_visemes);
+
+		let pcm = AudioFormat::new(24000, AudioChannels::Mono, None, AudioContainer::Raw(AudioEncoding::PcmI16Le));
+		assert!(matches!(UtteranceConfig::builder().with_audio_format(&pcm), FormatConfigured::Pcm(_)));
+	}
+}
@@ -0,0 +1,465 @@
+use core::{
+	fmt,
+	future::{Future, poll_fn},
+	pin::{Pin, pin},
+	task::{Context, Poll, Waker},
+	time::Duration
+};
+use std::sync::{
+	Arc, Mutex, MutexGuard,
+	atomic::{AtomicBool, Ordering}
+};
+
+use futures_core::Stream;
+use pin_project_lite::pin_project;
+
+use crate::{
+	AudioContainer, AudioFormat, AudioFormatPreference, LanguageInfo, SpeechSynthesiser, SsmlValidationError, SynthesisError, SynthesiserCapabilities,
+	UtteranceConfig, UtteranceEvent, UtteranceEventStream, VoiceInfo
+};
+
+#[derive(Default)]
+struct TokenInner {
+	cancelled: AtomicBool,
+	wakers: Mutex<Wakers>
+}
+
+/// The wakers of each [`WaitForCancellation`] waiting on a token, indexed by the key of each listener.
+///
+/// A listener's slot is only freed when the listener is dropped or resolves, so the number of slots is bounded by
+/// the number of listeners waiting at once, no matter how many requests share the token.
+#[derive(Default)]
+struct Wakers {
+	slots: Vec<Option<Waker>>,
+	free: Vec<usize>
+}
+
+impl Wakers {
+	fn register(&mut self, key: Option<usize>, waker: &Waker) -> usize {
+		if let Some(key) = key {
+			match &mut self.slots[key] {
+				Some(existing) if existing.will_wake(waker) => {}
+				slot => *slot = Some(waker.clone())
+			}
+			return key;
+		}
+		match self.free.pop() {
+			Some(key) => {
+				self.slots[key] = Some(waker.clone());
+				key
+			}
+			None => {
+				self.slots.push(Some(waker.clone()));
+				self.slots.len() - 1
+			}
+		}
+	}
+
+	fn deregister(&mut self, key: usize) {
+		self.slots[key] = None;
+		self.free.push(key);
+	}
+}
+
+/// A token used to cancel an in-progress synthesis.
+///
+/// Tokens are cheap to clone; all clones refer to the same cancellation state. Pass a token to a synthesiser via
+/// [`UtteranceConfig::with_cancellation_token`], then call [`CancellationToken::cancel`] to stop synthesis.
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+	inner: Arc<TokenInner>
+}
+
+impl CancellationToken {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Cancels this token, waking any streams or futures waiting on it.
+	pub fn cancel(&self) {
+		if !self.inner.cancelled.swap(true, Ordering::AcqRel) {
+			// Slots remain allocated until their listeners deregister themselves.
+			let wakers: Vec<Waker> = self.inner.lock_wakers().slots.iter_mut().filter_map(Option::take).collect();
+			for waker in wakers {
+				waker.wake();
+			}
+		}
+	}
+
+	/// Returns `true` if this token has been cancelled.
+	pub fn is_cancelled(&self) -> bool {
+		self.inner.cancelled.load(Ordering::Acquire)
+	}
+
+	/// Returns a guard which cancels this token when dropped.
+	pub fn drop_guard(self) -> CancellationDropGuard {
+		CancellationDropGuard { token: Some(self) }
+	}
+
+	/// Returns a future which resolves once this token is cancelled.
+	pub fn cancelled(&self) -> WaitForCancellation {
+		WaitForCancellation { token: self.clone(), key: None }
+	}
+}
+
+impl TokenInner {
+	fn lock_wakers(&self) -> MutexGuard<'_, Wakers> {
+		self.wakers.lock().unwrap_or_else(|e| e.into_inner())
+	}
+}
+
+/// Future returned by [`CancellationToken::cancelled`], which resolves once the token is cancelled.
+///
+/// Only the most recent waker is kept while waiting, and it is deregistered from the token once the future resolves
+/// or is dropped.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct WaitForCancellation {
+	token: CancellationToken,
+	/// The key of this listener's waker slot, if it is registered.
+	key: Option<usize>
+}
+
+impl WaitForCancellation {
+	/// Returns the token this future is waiting on.
+	pub fn token(&self) -> &CancellationToken {
+		&self.token
+	}
+
+	fn deregister(&mut self) {
+		if let Some(key) = self.key.take() {
+			self.token.inner.lock_wakers().deregister(key);
+		}
+	}
+}
+
+impl Future for WaitForCancellation {
+	type Output = ();
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+		if !self.token.is_cancelled() {
+			let key = self.token.inner.lock_wakers().register(self.key, cx.waker());
+			self.key = Some(key);
+			// Check again in case we were cancelled while registering the waker.
+			if !self.token.is_cancelled() {
+				return Poll::Pending;
+			}
+		}
+		self.deregister();
+		Poll::Ready(())
+	}
+}
+
+impl Drop for WaitForCancellation {
+	fn drop(&mut self) {
+		self.deregister();
+	}
+}
+
+impl fmt::Debug for CancellationToken {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("CancellationToken").field("is_cancelled", &self.is_cancelled()).finish()
+	}
+}
+
+/// A guard which cancels its [`CancellationToken`] when dropped. Created via [`CancellationToken::drop_guard`].
+#[derive(Debug)]
+#[must_use = "the token is cancelled as soon as the guard is dropped"]
+pub struct CancellationDropGuard {
+	token: Option<CancellationToken>
+}
+
+impl CancellationDropGuard {
+	/// Returns the token without cancelling it.
+	pub fn disarm(mut self) -> CancellationToken {
+		self.token.take().expect("guard was already disarmed")
+	}
+}
+
+impl Drop for CancellationDropGuard {
+	fn drop(&mut self) {
+		if let Some(token) = self.token.take() {
+			token.cancel();
+		}
+	}
+}
+
+pin_project! {
+	/// Stream returned by [`cancellable`].
+	pub struct Cancellable<S> {
+		#[pin]
+		inner: Option<S>,
+		cancelled: WaitForCancellation
+	}
+}
+
+/// Wraps an [`UtteranceEventStream`] such that it terminates once `token` is cancelled.
+///
+/// When cancelled, the inner stream is dropped immediately (discarding any audio it may have buffered), the stream
+/// yields a single [`SynthesisError::Cancelled`], and then ends.
+pub fn cancellable<E, S: UtteranceEventStream<E>>(stream: S, token: CancellationToken) -> Cancellable<S> {
+	Cancellable {
+		inner: Some(stream),
+		cancelled: token.cancelled()
+	}
+}
+
+impl<E, S: Stream<Item = Result<UtteranceEvent, E>>> Stream for Cancellable<S> {
+	type Item = Result<UtteranceEvent, SynthesisError<E>>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let mut this = self.project();
+		let Some(inner) = this.inner.as_mut().as_pin_mut() else {
+			return Poll::Ready(None);
+		};
+
+		if Pin::new(&mut *this.cancelled).poll(cx).is_ready() {
+			this.inner.set(None);
+			return Poll::Ready(Some(Err(SynthesisError::Cancelled)));
+		}
+
+		match inner.poll_next(cx) {
+			Poll::Ready(None) => {
+				this.inner.set(None);
+				Poll::Ready(None)
+			}
+			Poll::Ready(Some(item)) => Poll::Ready(Some(item.map_err(SynthesisError::Synthesiser))),
+			Poll::Pending => Poll::Pending
+		}
+	}
+}
+
+/// Wraps a [`SpeechSynthesiser`] to honour [`UtteranceConfig::cancellation_token`].
+///
+/// If the token is cancelled before the synthesiser returns a stream, the synthesis future resolves to
+/// [`SynthesisError::Cancelled`]. If it is cancelled while the stream is being consumed, the stream behaves as
+/// described in [`cancellable`].
+#[derive(Debug, Clone)]
+pub struct CancellableSpeechSynthesiser<S>(S);
+
+impl<S: SpeechSynthesiser> CancellableSpeechSynthesiser<S> {
+	pub fn new(synthesiser: S) -> Self {
+		CancellableSpeechSynthesiser(synthesiser)
+	}
+
+	pub fn inner(&self) -> &S {
+		&self.0
+	}
+
+	pub fn into_inner(self) -> S {
+		self.0
+	}
+}
+
+async fn race_cancellation<T, E>(fut: impl Future<Output = Result<T, E>>, token: CancellationToken) -> Result<T, SynthesisError<E>> {
+	let mut fut = pin!(fut);
+	let mut cancelled = token.cancelled();
+	poll_fn(|cx| {
+		if Pin::new(&mut cancelled).poll(cx).is_ready() {
+			return Poll::Ready(Err(SynthesisError::Cancelled));
+		}
+		fut.as_mut().poll(cx).map_err(SynthesisError::Synthesiser)
+	})
+	.await
+}
+
+impl<S: SpeechSynthesiser + Sync> SpeechSynthesiser for CancellableSpeechSynthesiser<S> {
+	type Error = SynthesisError<S::Error>;
+
+	fn negotiate_audio_format(&self, pref: &AudioFormatPreference) -> Option<AudioFormat> {
+		self.0.negotiate_audio_format(pref)
+	}
+
+	fn capabilities(&self) -> SynthesiserCapabilities {
+		self.0.capabilities()
+	}
+
+	fn validate_ssml(&self, input: &ssml::Speak<'_>) -> Result<(), Vec<SsmlValidationError>> {
+		self.0.validate_ssml(input)
+	}
+
+	fn estimate_duration(&self, text: &str, config: &UtteranceConfig) -> Option<Duration> {
+		self.0.estimate_duration(text, config)
+	}
+
+	fn supported_sample_rates(&self) -> &[u32] {
+		self.0.supported_sample_rates()
+	}
+
+	fn supported_containers(&self) -> &[AudioContainer] {
+		self.0.supported_containers()
+	}
+
+	fn synthesise_ssml_stream(
+		&self,
+		input: &ssml::Speak<'_>,
+		audio_format: &AudioFormat,
+		config: &UtteranceConfig
+	) -> impl Future<Output = Result<impl UtteranceEventStream<Self::Error> + 'static, Self::Error>> + Send {
+		let token = config.cancellation_token.clone().unwrap_or_default();
+		let fut = self.0.synthesise_ssml_stream(input, audio_format, config);
+		async move {
+			let stream = race_cancellation(fut, token.clone()).await?;
+			Ok(cancellable(stream, token))
+		}
+	}
+
+	fn synthesise_text_stream(
+		&self,
+		input: &str,
+		audio_format: &AudioFormat,
+		config: &UtteranceConfig
+	) -> impl Future<Output = Result<impl UtteranceEventStream<Self::Error> + 'static, Self::Error>> + Send {
+		let token = config.cancellation_token.clone().unwrap_or_default();
+		let fut = self.0.synthesise_text_stream(input, audio_format, config);
+		async move {
+			let stream = race_cancellation(fut, token.clone()).await?;
+			Ok(cancellable(stream, token))
+		}
+	}
+
+	fn list_voices(&self) -> impl Future<Output = Result<Vec<VoiceInfo>, Self::Error>> + Send {
+		let fut = self.0.list_voices();
+		async move { fut.await.map_err(SynthesisError::Synthesiser) }
+	}
+
+	fn supported_languages(&self) -> impl Future<Output = Result<Vec<LanguageInfo>, Self::Error>> + Send {
+		let fut = self.0.supported_languages();
+		async move { fut.await.map_err(SynthesisError::Synthesiser) }
+	}
+
+	fn health_check(&self) -> impl Future<Output = Result<(), Self::Error>> + Send {
+		let fut = self.0.health_check();
+		async move { fut.await.map_err(SynthesisError::Synthesiser) }
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use core::{
+		pin::{Pin, pin},
+		task::{Context, Poll},
+		time::Duration
+	};
+	use std::sync::Arc;
+
+	use futures_core::Stream;
+
+	use super::{CancellableSpeechSynthesiser, CancellationToken, cancellable};
+	use crate::{
+		AudioChannels, AudioContainer, AudioEncoding, AudioFormat, SpeechSynthesiser, SynthesisError, UtteranceConfig, UtteranceEvent, stream,
+		testing::MockSpeechSynthesiser
+	};
+
+	/// A stream which never ends, holding `buffer` to observe when it is dropped.
+	struct Endless {
+		buffer: Arc<[u8]>
+	}
+
+	impl Stream for Endless {
+		type Item = Result<UtteranceEvent, ()>;
+
+		fn poll_next(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+			Poll::Ready(Some(Ok(UtteranceEvent::AudioChunk(self.buffer.to_vec().into_boxed_slice()))))
+		}
+	}
+
+	fn format() -> AudioFormat {
+		AudioFormat::new(16000, AudioChannels::Mono, None, AudioContainer::Raw(AudioEncoding::PcmI16Le))
+	}
+
+	fn mock_with_chunks(count: usize, latency: Duration) -> CancellableSpeechSynthesiser<MockSpeechSynthesiser> {
+		let synthesiser = MockSpeechSynthesiser::new().with_simulated_latency(latency);
+		let events = (0..count).map(|_| UtteranceEvent::AudioChunk(vec![0; 64].into_boxed_slice())).collect();
+		synthesiser.push_response(Vec::new(), events);
+		CancellableSpeechSynthesiser::new(synthesiser)
+	}
+
+	async fn drain<E: core::fmt::Debug>(stream: impl Stream<Item = Result<UtteranceEvent, SynthesisError<E>>>) -> (usize, Vec<SynthesisError<E>>) {
+		let mut stream = pin!(stream);
+		let (mut chunks, mut errors) = (0, Vec::new());
+		while let Some(item) = stream::next(stream.as_mut()).await {
+			match item {
+				Ok(event) => chunks += event.is_audio() as usize,
+				Err(e) => errors.push(e)
+			}
+		}
+		(chunks, errors)
+	}
+
+	#[tokio::test]
+	async fn cancelled_token_ends_stream() {
+		let synthesiser = mock_with_chunks(100, Duration::from_millis(20));
+		let token = CancellationToken::new();
+		let config = UtteranceConfig::default().with_cancellation_token(token.clone());
+		let stream = synthesiser.synthesise_text_stream("", &format(), &config).await.unwrap();
+
+		tokio::spawn(async move {
+			tokio::time::sleep(Duration::from_millis(70)).await;
+			token.cancel();
+		});
+		let (chunks, errors) = drain(stream).await;
+		assert!(chunks < 100);
+		assert!(matches!(errors[..], [SynthesisError::Cancelled]));
+	}
+
+	#[tokio::test]
+	async fn dropped_guard_ends_stream() {
+		let synthesiser = mock_with_chunks(100, Duration::from_millis(20));
+		let token = CancellationToken::new();
+		let config = UtteranceConfig::default().with_cancellation_token(token.clone());
+		let guard = token.drop_guard();
+		let stream = synthesiser.synthesise_text_stream("", &format(), &config).await.unwrap();
+
+		tokio::spawn(async move {
+			tokio::time::sleep(Duration::from_millis(70)).await;
+			drop(guard);
+		});
+		let (chunks, errors) = drain(stream).await;
+		assert!(chunks < 100);
+		assert!(matches!(errors[..], [SynthesisError::Cancelled]));
+	}
+
+	#[tokio::test]
+	async fn cancellation_before_stream_starts() {
+		let synthesiser = mock_with_chunks(1, Duration::ZERO);
+		let token = CancellationToken::new();
+		token.cancel();
+		let config = UtteranceConfig::default().with_cancellation_token(token);
+		let result = synthesiser.synthesise_text_stream("", &format(), &config).await;
+		assert!(matches!(result, Err(SynthesisError::Cancelled)));
+	}
+
+	#[tokio::test]
+	async fn cancellation_drops_buffered_audio() {
+		let buffer: Arc<[u8]> = Arc::from(vec![0; 1024]);
+		let token = CancellationToken::new();
+		let mut stream = pin!(cancellable(Endless { buffer: buffer.clone() }, token.clone()));
+		assert!(stream::next(stream.as_mut()).await.unwrap().is_ok());
+
+		token.cancel();
+		assert!(matches!(stream::next(stream.as_mut()).await, Some(Err(SynthesisError::Cancelled))));
+		// The inner stream is dropped as soon as cancellation is observed, not when the wrapper is dropped.
+		assert_eq!(Arc::strong_count(&buffer), 1);
+		assert!(stream::next(stream.as_mut()).await.is_none());
+	}
+
+	#[tokio::test]
+	async fn shared_token_does_not_accumulate_wakers() {
+		let token = CancellationToken::new();
+		// Each request runs in its own task, so that each registers a distinct waker.
+		for _ in 0..100 {
+			let token = token.clone();
+			let (chunks, errors) = tokio::spawn(async move {
+				let synthesiser = mock_with_chunks(2, Duration::from_millis(1));
+				let config = UtteranceConfig::default().with_cancellation_token(token);
+				let stream = synthesiser.synthesise_text_stream("", &format(), &config).await.unwrap();
+				drain(stream).await
+			})
+			.await
+			.unwrap();
+			assert_eq!((chunks, errors.len()), (2, 0));
+		}
+		assert!(token.inner.lock_wakers().slots.len() <= 1);
+	}
+}
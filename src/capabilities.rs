@@ -0,0 +1,152 @@
+/// Describes the features supported by a [`SpeechSynthesiser`](crate::SpeechSynthesiser), as returned by
+/// [`SpeechSynthesiser::capabilities`](crate::SpeechSynthesiser::capabilities).
+///
+/// The [`Default`] capabilities report every feature as supported, since that is a safe overestimate for synthesisers
+/// which do not describe their capabilities. Synthesisers should start from the default and disable the features they
+/// do not support.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default))]
+pub struct SynthesiserCapabilities {
+	/// Whether the synthesiser supports [SSML synthesis](crate::SpeechSynthesiser::synthesise_ssml_stream).
+	pub supports_ssml: bool,
+	/// Whether the synthesiser can emit [`UtteranceEvent::VisemesChunk`](crate::UtteranceEvent::VisemesChunk) events.
+	pub supports_visemes: bool,
+	/// Whether the synthesiser can emit
+	/// [`UtteranceEvent::BlendShapeVisemesChunk`](crate::UtteranceEvent::BlendShapeVisemesChunk) events.
+	pub supports_blend_shape_visemes: bool,
+	/// Whether the synthesiser can emit [`UtteranceEvent::WordBoundary`](crate::UtteranceEvent::WordBoundary) events.
+	pub supports_word_boundary: bool,
+	/// Whether the synthesiser can emit [`UtteranceEvent::SentenceBoundary`](crate::UtteranceEvent::SentenceBoundary)
+	/// events.
+	pub supports_sentence_boundary: bool,
+	/// Whether the synthesiser can emit [`UtteranceEvent::ParagraphBoundary`](crate::UtteranceEvent::ParagraphBoundary)
+	/// events.
+	pub supports_paragraph_boundary: bool,
+	/// Whether the synthesiser can emit [`UtteranceEvent::Phoneme`](crate::UtteranceEvent::Phoneme) events.
+	pub supports_phoneme_events: bool,
+	/// Whether the synthesiser can emit [`UtteranceEvent::SpeakerChange`](crate::UtteranceEvent::SpeakerChange) events.
+	pub supports_speaker_change: bool,
+	/// Whether the synthesiser can emit [`UtteranceEvent::PauseDetected`](crate::UtteranceEvent::PauseDetected) events,
+	/// either natively or by detecting them from the audio with
+	/// [`stream::detect_pauses`](crate::stream::detect_pauses).
+	pub supports_pause_events: bool,
+	/// Whether the synthesiser can emit [`UtteranceEvent::SsmlMark`](crate::UtteranceEvent::SsmlMark) events.
+	pub supports_ssml_marks: bool,
+	/// Whether the synthesiser can [detect the language](crate::UtteranceConfig::auto_detect_language) of raw text
+	/// input.
+	pub supports_language_detection: bool,
+	/// Whether the synthesiser supports [voice roles](crate::UtteranceConfig::voice_role).
+	pub supports_voice_roles: bool
+}
+
+impl Default for SynthesiserCapabilities {
+	fn default() -> Self {
+		SynthesiserCapabilities {
+			supports_ssml: true,
+			supports_visemes: true,
+			supports_blend_shape_visemes: true,
+			supports_word_boundary: true,
+			supports_sentence_boundary: true,
+			supports_paragraph_boundary: true,
+			supports_phoneme_events: true,
+			supports_speaker_change: true,
+			supports_pause_events: true,
+			supports_ssml_marks: true,
+			supports_language_detection: true,
+			supports_voice_roles: true
+		}
+	}
+}
+
+impl SynthesiserCapabilities {
+	/// Configures whether the synthesiser supports SSML synthesis.
+	pub fn with_supports_ssml(mut self, x: bool) -> Self {
+		self.supports_ssml = x;
+		self
+	}
+
+	/// Configures whether the synthesiser can emit basic viseme events.
+	pub fn with_supports_visemes(mut self, x: bool) -> Self {
+		self.supports_visemes = x;
+		self
+	}
+
+	/// Configures whether the synthesiser can emit blend shape viseme events.
+	pub fn with_supports_blend_shape_visemes(mut self, x: bool) -> Self {
+		self.supports_blend_shape_visemes = x;
+		self
+	}
+
+	/// Configures whether the synthesiser can emit word boundary events.
+	pub fn with_supports_word_boundary(mut self, x: bool) -> Self {
+		self.supports_word_boundary = x;
+		self
+	}
+
+	/// Configures whether the synthesiser can emit sentence boundary events.
+	pub fn with_supports_sentence_boundary(mut self, x: bool) -> Self {
+		self.supports_sentence_boundary = x;
+		self
+	}
+
+	/// Configures whether the synthesiser can emit paragraph boundary events.
+	pub fn with_supports_paragraph_boundary(mut self, x: bool) -> Self {
+		self.supports_paragraph_boundary = x;
+		self
+	}
+
+	/// Configures whether the synthesiser can emit phoneme events.
+	pub fn with_supports_phoneme_events(mut self, x: bool) -> Self {
+		self.supports_phoneme_events = x;
+		self
+	}
+
+	/// Configures whether the synthesiser can emit speaker change events.
+	pub fn with_supports_speaker_change(mut self, x: bool) -> Self {
+		self.supports_speaker_change = x;
+		self
+	}
+
+	/// Configures whether the synthesiser can emit pause events.
+	pub fn with_supports_pause_events(mut self, x: bool) -> Self {
+		self.supports_pause_events = x;
+		self
+	}
+
+	/// Configures whether the synthesiser can emit SSML mark events.
+	pub fn with_supports_ssml_marks(mut self, x: bool) -> Self {
+		self.supports_ssml_marks = x;
+		self
+	}
+
+	/// Configures whether the synthesiser can detect the language of raw text input.
+	pub fn with_supports_language_detection(mut self, x: bool) -> Self {
+		self.supports_language_detection = x;
+		self
+	}
+
+	/// Configures whether the synthesiser supports voice roles.
+	pub fn with_supports_voice_roles(mut self, x: bool) -> Self {
+		self.supports_voice_roles = x;
+		self
+	}
+
+	/// Returns the capabilities supported by both `self` and `other`.
+	pub fn intersect(&self, other: &SynthesiserCapabilities) -> SynthesiserCapabilities {
+		SynthesiserCapabilities {
+			supports_ssml: self.supports_ssml && other.supports_ssml,
+			supports_visemes: self.supports_visemes && other.supports_visemes,
+			supports_blend_shape_visemes: self.supports_blend_shape_visemes && other.supports_blend_shape_visemes,
+			supports_word_boundary: self.supports_word_boundary && other.supports_word_boundary,
+			supports_sentence_boundary: self.supports_sentence_boundary && other.supports_sentence_boundary,
+			supports_paragraph_boundary: self.supports_paragraph_boundary && other.supports_paragraph_boundary,
+			supports_phoneme_events: self.supports_phoneme_events && other.supports_phoneme_events,
+			supports_speaker_change: self.supports_speaker_change && other.supports_speaker_change,
+			supports_pause_events: self.supports_pause_events && other.supports_pause_events,
+			supports_ssml_marks: self.supports_ssml_marks && other.supports_ssml_marks,
+			supports_language_detection: self.supports_language_detection && other.supports_language_detection,
+			supports_voice_roles: self.supports_voice_roles && other.supports_voice_roles
+		}
+	}
+}
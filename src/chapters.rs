@@ -0,0 +1,80 @@
+use core::{
+	pin::Pin,
+	task::{Context, Poll}
+};
+use std::collections::VecDeque;
+
+use futures_core::Stream;
+
+use crate::{
+	AudioFormat, SpeechSynthesiser, UtteranceConfig, UtteranceEvent,
+	sequential::{SegmentEventStream, SequentialSynthesis, Step}
+};
+
+/// Stream returned by
+/// [`SpeechSynthesiserExt::synthesise_ssml_document`](crate::SpeechSynthesiserExt::synthesise_ssml_document).
+pub(crate) struct ChapterStream<'a, S: SpeechSynthesiser + ?Sized> {
+	synthesiser: &'a S,
+	audio_format: &'a AudioFormat,
+	config: &'a UtteranceConfig,
+	chapters: VecDeque<ssml::Speak<'a>>,
+	/// The index of the next chapter to be streamed.
+	next_index: u32,
+	sequence: SequentialSynthesis<'a, S::Error>
+}
+
+impl<'a, S: SpeechSynthesiser + Sync + ?Sized> ChapterStream<'a, S> {
+	pub(crate) fn new(synthesiser: &'a S, chapters: Vec<ssml::Speak<'a>>, audio_format: &'a AudioFormat, config: &'a UtteranceConfig) -> Self {
+		ChapterStream {
+			synthesiser,
+			audio_format,
+			config,
+			chapters: VecDeque::from(chapters),
+			next_index: 0,
+			sequence: SequentialSynthesis::new(audio_format)
+		}
+	}
+
+	/// Requests synthesis of the next chapter, if one remains. Returns `false` if there are no chapters left.
+	fn start_next(&mut self) -> bool {
+		let Some(chapter) = self.chapters.pop_front() else {
+			return false;
+		};
+		let (synthesiser, audio_format, config) = (self.synthesiser, self.audio_format, self.config);
+		self.sequence.start(async move {
+			let stream = synthesiser.synthesise_ssml_stream(&chapter, audio_format, config).await?;
+			Ok(Box::pin(stream) as SegmentEventStream<'a, S::Error>)
+		});
+		true
+	}
+}
+
+impl<'a, S: SpeechSynthesiser + Sync + ?Sized> Stream for ChapterStream<'a, S> {
+	type Item = Result<UtteranceEvent, S::Error>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let this = self.get_mut();
+		loop {
+			if this.config.prefetch_chapters && this.sequence.is_streaming() && !this.sequence.has_next() {
+				this.start_next();
+			}
+
+			match this.sequence.poll_step(cx) {
+				Poll::Ready(Some(Ok(Step::Event(event)))) => return Poll::Ready(Some(Ok(event))),
+				Poll::Ready(Some(Ok(Step::Started { at_millis }))) => {
+					let chapter_index = this.next_index;
+					this.next_index += 1;
+					return Poll::Ready(Some(Ok(UtteranceEvent::ChapterBoundary { chapter_index, at_millis })));
+				}
+				Poll::Ready(Some(Ok(Step::Idle))) => {
+					if !this.start_next() {
+						this.sequence.finish();
+					}
+				}
+				Poll::Ready(Some(Err(e))) => return Poll::Ready(Some(Err(e))),
+				Poll::Ready(None) => return Poll::Ready(None),
+				Poll::Pending => return Poll::Pending
+			}
+		}
+	}
+}
@@ -0,0 +1,100 @@
+use core::{future::Future, time::Duration};
+
+use crate::{
+	AudioContainer, AudioFormat, AudioFormatPreference, LanguageInfo, SpeechSynthesiser, SsmlValidationError, SynthesiserCapabilities, UtteranceConfig,
+	UtteranceEventStream, VoiceInfo, stream::limit_audio_chunk_size
+};
+
+/// Wraps a [`SpeechSynthesiser`] to honour [`UtteranceConfig::max_audio_chunk_size`].
+///
+/// Audio chunks larger than the configured size are split with [`limit_audio_chunk_size`]. For uncompressed audio (see
+/// [`AudioFormat::frame_size_bytes`]), the size is rounded down to a whole number of frames (but never less than one
+/// frame), so that no chunk ends partway through a sample.
+#[derive(Debug, Clone)]
+pub struct ChunkLimitingSpeechSynthesiser<S>(S);
+
+impl<S: SpeechSynthesiser> ChunkLimitingSpeechSynthesiser<S> {
+	pub fn new(synthesiser: S) -> Self {
+		ChunkLimitingSpeechSynthesiser(synthesiser)
+	}
+
+	pub fn inner(&self) -> &S {
+		&self.0
+	}
+
+	pub fn into_inner(self) -> S {
+		self.0
+	}
+}
+
+fn chunk_size(audio_format: &AudioFormat, config: &UtteranceConfig) -> usize {
+	let Some(max) = config.max_audio_chunk_size else {
+		return usize::MAX;
+	};
+	match audio_format.frame_size_bytes().and_then(|x| usize::try_from(x).ok()) {
+		Some(frame_size) if frame_size > 0 => (max / frame_size).max(1) * frame_size,
+		_ => max
+	}
+}
+
+impl<S: SpeechSynthesiser + Sync> SpeechSynthesiser for ChunkLimitingSpeechSynthesiser<S> {
+	type Error = S::Error;
+
+	fn negotiate_audio_format(&self, pref: &AudioFormatPreference) -> Option<AudioFormat> {
+		self.0.negotiate_audio_format(pref)
+	}
+
+	fn capabilities(&self) -> SynthesiserCapabilities {
+		self.0.capabilities()
+	}
+
+	fn validate_ssml(&self, input: &ssml::Speak<'_>) -> Result<(), Vec<SsmlValidationError>> {
+		self.0.validate_ssml(input)
+	}
+
+	fn estimate_duration(&self, text: &str, config: &UtteranceConfig) -> Option<Duration> {
+		self.0.estimate_duration(text, config)
+	}
+
+	fn supported_sample_rates(&self) -> &[u32] {
+		self.0.supported_sample_rates()
+	}
+
+	fn supported_containers(&self) -> &[AudioContainer] {
+		self.0.supported_containers()
+	}
+
+	fn synthesise_ssml_stream(
+		&self,
+		input: &ssml::Speak<'_>,
+		audio_format: &AudioFormat,
+		config: &UtteranceConfig
+	) -> impl Future<Output = Result<impl UtteranceEventStream<Self::Error> + 'static, Self::Error>> + Send {
+		let max = chunk_size(audio_format, config);
+		let fut = self.0.synthesise_ssml_stream(input, audio_format, config);
+		async move { Ok(limit_audio_chunk_size(fut.await?, max)) }
+	}
+
+	fn synthesise_text_stream(
+		&self,
+		input: &str,
+		audio_format: &AudioFormat,
+		config: &UtteranceConfig
+	) -> impl Future<Output = Result<impl UtteranceEventStream<Self::Error> + 'static, Self::Error>> + Send {
+		let max = chunk_size(audio_format, config);
+		let fut = self.0.synthesise_text_stream(input, audio_format, config);
+		async move { Ok(limit_audio_chunk_size(fut.await?, max)) }
+	}
+
+	fn list_voices(&self) -> impl Future<Output = Result<Vec<VoiceInfo>, Self::Error>> + Send {
+		self.0.list_voices()
+	}
+
+	fn supported_languages(&self) -> impl Future<Output = Result<Vec<LanguageInfo>, Self::Error>> + Send {
+		self.0.supported_languages()
+	}
+
+	fn health_check(&self) -> impl Future<Output = Result<(), Self::Error>> + Send {
+		self.0.health_check()
+	}
+}
@@ -0,0 +1,155 @@
+use core::{future::Future, time::Duration};
+
+use crate::{
+	AudioContainer, AudioFormat, AudioFormatPreference, LanguageInfo, PaddingSpeechSynthesiser, SentencePauseSpeechSynthesiser, SpeechSynthesiser,
+	SsmlValidationError, SynthesisError, SynthesiserCapabilities, TimedSpeechSynthesiser, UtteranceConfig, UtteranceEventStream, VoiceInfo,
+	VoiceStyleSpeechSynthesiser
+};
+
+type Layers<S> = TimedSpeechSynthesiser<PaddingSpeechSynthesiser<VoiceStyleSpeechSynthesiser<SentencePauseSpeechSynthesiser<S>>>>;
+
+/// Wraps a [`SpeechSynthesiser`] to honour every [`UtteranceConfig`] option which can be applied without any other
+/// resources: [`UtteranceConfig::inter_sentence_pause`], [`UtteranceConfig::break_before`] &
+/// [`UtteranceConfig::break_after`], [`UtteranceConfig::voice_style`], [`UtteranceConfig::voice_style_degree`] &
+/// [`UtteranceConfig::voice_role`], and [`UtteranceConfig::synthesis_timeout`] & [`UtteranceConfig::chunk_timeout`].
+///
+/// This is equivalent to wrapping the synthesiser in a [`SentencePauseSpeechSynthesiser`],
+/// [`VoiceStyleSpeechSynthesiser`], [`PaddingSpeechSynthesiser`], and [`TimedSpeechSynthesiser`] in turn; each option
+/// only takes effect when it is configured, so there is no cost to honouring options which are left unset. Use the
+/// individual wrappers instead to honour only some options.
+///
+/// The configuration is also [validated](UtteranceConfig::validate) before each request, failing with
+/// [`SynthesisError::InvalidConfig`] rather than passing invalid values on to the backend.
+#[derive(Debug, Clone)]
+pub struct ConfiguredSpeechSynthesiser<S>(Layers<S>);
+
+impl<S: SpeechSynthesiser + Sync> ConfiguredSpeechSynthesiser<S> {
+	pub fn new(synthesiser: S) -> Self {
+		ConfiguredSpeechSynthesiser(TimedSpeechSynthesiser::new(PaddingSpeechSynthesiser::new(VoiceStyleSpeechSynthesiser::new(
+			SentencePauseSpeechSynthesiser::new(synthesiser)
+		))))
+	}
+
+	pub fn inner(&self) -> &S {
+		self.0.inner().inner().inner().inner()
+	}
+
+	pub fn into_inner(self) -> S {
+		self.0.into_inner().into_inner().into_inner().into_inner()
+	}
+}
+
+impl<S: SpeechSynthesiser + Sync> SpeechSynthesiser for ConfiguredSpeechSynthesiser<S> {
+	type Error = SynthesisError<S::Error>;
+
+	fn negotiate_audio_format(&self, pref: &AudioFormatPreference) -> Option<AudioFormat> {
+		self.0.negotiate_audio_format(pref)
+	}
+
+	fn capabilities(&self) -> SynthesiserCapabilities {
+		self.0.capabilities()
+	}
+
+	fn validate_ssml(&self, input: &ssml::Speak<'_>) -> Result<(), Vec<SsmlValidationError>> {
+		self.0.validate_ssml(input)
+	}
+
+	fn estimate_duration(&self, text: &str, config: &UtteranceConfig) -> Option<Duration> {
+		self.0.estimate_duration(text, config)
+	}
+
+	fn supported_sample_rates(&self) -> &[u32] {
+		self.0.supported_sample_rates()
+	}
+
+	fn supported_containers(&self) -> &[AudioContainer] {
+		self.0.supported_containers()
+	}
+
+	async fn synthesise_ssml_stream(
+		&self,
+		input: &ssml::Speak<'_>,
+		audio_format: &AudioFormat,
+		config: &UtteranceConfig
+	) -> Result<impl UtteranceEventStream<Self::Error> + 'static, Self::Error> {
+		config.validate().map_err(SynthesisError::InvalidConfig)?;
+		self.0.synthesise_ssml_stream(input, audio_format, config).await
+	}
+
+	async fn synthesise_text_stream(
+		&self,
+		input: &str,
+		audio_format: &AudioFormat,
+		config: &UtteranceConfig
+	) -> Result<impl UtteranceEventStream<Self::Error> + 'static, Self::Error> {
+		config.validate().map_err(SynthesisError::InvalidConfig)?;
+		self.0.synthesise_text_stream(input, audio_format, config).await
+	}
+
+	fn list_voices(&self) -> impl Future<Output = Result<Vec<VoiceInfo>, Self::Error>> + Send {
+		self.0.list_voices()
+	}
+
+	fn supported_languages(&self) -> impl Future<Output = Result<Vec<LanguageInfo>, Self::Error>> + Send {
+		self.0.supported_languages()
+	}
+
+	fn health_check(&self) -> impl Future<Output = Result<(), Self::Error>> + Send {
+		self.0.health_check()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use core::{pin::pin, time::Duration};
+
+	use super::ConfiguredSpeechSynthesiser;
+	use crate::{
+		AudioChannels, AudioContainer, AudioEncoding, AudioFormat, ConfigValidationError, SpeechSynthesiser, SynthesisError, UtteranceConfig, UtteranceEvent,
+		stream, testing::MockSpeechSynthesiser
+	};
+
+	fn format() -> AudioFormat {
+		AudioFormat::new(1000, AudioChannels::Mono, None, AudioContainer::Raw(AudioEncoding::PcmI16Le))
+	}
+
+	#[tokio::test]
+	async fn honours_padding() {
+		let synthesiser = ConfiguredSpeechSynthesiser::new(MockSpeechSynthesiser::new());
+		synthesiser.inner().push_response(vec![1; 16], Vec::new());
+
+		let config = UtteranceConfig::default().with_break_before(Duration::from_millis(10));
+		let stream = synthesiser.synthesise_text_stream("", &format(), &config).await.unwrap();
+		let mut stream = pin!(stream);
+		let Some(Ok(UtteranceEvent::AudioChunk(silence))) = stream::next(stream.as_mut()).await else {
+			panic!("expected leading silence");
+		};
+		assert_eq!(&*silence, &[0; 20]);
+		assert!(matches!(stream::next(stream.as_mut()).await, Some(Ok(UtteranceEvent::AudioChunk(chunk))) if *chunk == [1; 16]));
+	}
+
+	#[tokio::test]
+	async fn rejects_invalid_config() {
+		let synthesiser = ConfiguredSpeechSynthesiser::new(MockSpeechSynthesiser::new());
+		synthesiser.inner().push_response(vec![0; 16], Vec::new());
+
+		let config = UtteranceConfig::default().with_voice_style_degree(3.0);
+		let result = synthesiser.synthesise_text_stream("", &format(), &config).await;
+		assert!(matches!(result, Err(SynthesisError::InvalidConfig(errors)) if errors == [ConfigValidationError::SpeakingStyleDegreeOutOfRange(3.0)]));
+		// The request never reached the inner synthesiser.
+		assert_eq!(synthesiser.inner().remaining_responses(), 1);
+	}
+
+	#[tokio::test]
+	async fn honours_timeouts() {
+		let mock = MockSpeechSynthesiser::new().with_simulated_latency(Duration::from_millis(200));
+		mock.push_response(vec![0; 16], Vec::new());
+		let synthesiser = ConfiguredSpeechSynthesiser::new(mock);
+
+		let config = UtteranceConfig::default().with_chunk_timeout(Duration::from_millis(20));
+		let stream = synthesiser.synthesise_text_stream("", &format(), &config).await.unwrap();
+		let mut stream = pin!(stream);
+		assert!(matches!(stream::next(stream.as_mut()).await, Some(Err(SynthesisError::Timeout))));
+		assert!(stream::next(stream.as_mut()).await.is_none());
+	}
+}
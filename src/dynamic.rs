@@ -0,0 +1,275 @@
+use core::{
+	fmt,
+	future::Future,
+	pin::Pin,
+	task::{Context, Poll},
+	time::Duration
+};
+
+use futures_core::Stream;
+use pin_project_lite::pin_project;
+
+use crate::{
+	AudioContainer, AudioFormat, AudioFormatPreference, LanguageInfo, SpeechSynthesiser, SsmlValidationError, SynthesiserCapabilities, UtteranceConfig,
+	UtteranceEvent, UtteranceEventStream, VoiceInfo
+};
+
+/// A type-erased error returned by a [`DynSpeechSynthesiserTrait`].
+pub struct DynError(Box<dyn std::error::Error + Send + Sync + 'static>);
+
+impl DynError {
+	pub fn new(error: impl std::error::Error + Send + Sync + 'static) -> Self {
+		DynError(Box::new(error))
+	}
+
+	/// Returns a reference to the underlying error if it is of type `E`.
+	pub fn downcast_ref<E: std::error::Error + 'static>(&self) -> Option<&E> {
+		self.0.downcast_ref()
+	}
+
+	pub fn into_inner(self) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+		self.0
+	}
+}
+
+impl fmt::Debug for DynError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Debug::fmt(&self.0, f)
+	}
+}
+
+impl fmt::Display for DynError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Display::fmt(&self.0, f)
+	}
+}
+
+impl std::error::Error for DynError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		self.0.source()
+	}
+}
+
+/// A boxed future returned by a [`DynSpeechSynthesiserTrait`].
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// A boxed [`UtteranceEventStream`] returned by a [`DynSpeechSynthesiserTrait`].
+pub type BoxUtteranceEventStream = Pin<Box<dyn UtteranceEventStream<DynError> + 'static>>;
+
+/// An object-safe version of [`SpeechSynthesiser`], where all futures, streams, and errors are boxed.
+///
+/// Any [`SpeechSynthesiser`] can be converted to a `Box<dyn DynSpeechSynthesiserTrait>` via [`into_dyn`]. Boxed
+/// synthesisers also implement [`SpeechSynthesiser`] themselves, with an error type of [`DynError`].
+pub trait DynSpeechSynthesiserTrait: Send + Sync {
+	/// See [`SpeechSynthesiser::negotiate_audio_format`].
+	fn negotiate_audio_format(&self, pref: &AudioFormatPreference) -> Option<AudioFormat>;
+
+	/// See [`SpeechSynthesiser::capabilities`].
+	fn capabilities(&self) -> SynthesiserCapabilities;
+
+	/// See [`SpeechSynthesiser::validate_ssml`].
+	fn validate_ssml(&self, input: &ssml::Speak<'_>) -> Result<(), Vec<SsmlValidationError>>;
+
+	/// See [`SpeechSynthesiser::estimate_duration`].
+	fn estimate_duration(&self, text: &str, config: &UtteranceConfig) -> Option<Duration>;
+
+	/// See [`SpeechSynthesiser::supported_sample_rates`].
+	fn supported_sample_rates(&self) -> &[u32];
+
+	/// See [`SpeechSynthesiser::supported_containers`].
+	fn supported_containers(&self) -> &[AudioContainer];
+
+	/// See [`SpeechSynthesiser::synthesise_ssml_stream`].
+	fn synthesise_ssml_stream<'a>(
+		&'a self,
+		input: &'a ssml::Speak<'_>,
+		audio_format: &'a AudioFormat,
+		config: &'a UtteranceConfig
+	) -> BoxFuture<'a, Result<BoxUtteranceEventStream, DynError>>;
+
+	/// See [`SpeechSynthesiser::synthesise_text_stream`].
+	fn synthesise_text_stream<'a>(
+		&'a self,
+		input: &'a str,
+		audio_format: &'a AudioFormat,
+		config: &'a UtteranceConfig
+	) -> BoxFuture<'a, Result<BoxUtteranceEventStream, DynError>>;
+
+	/// See [`SpeechSynthesiser::list_voices`].
+	fn list_voices(&self) -> BoxFuture<'_, Result<Vec<VoiceInfo>, DynError>>;
+
+	/// See [`SpeechSynthesiser::supported_languages`].
+	fn supported_languages(&self) -> BoxFuture<'_, Result<Vec<LanguageInfo>, DynError>>;
+
+	/// See [`SpeechSynthesiser::health_check`].
+	fn health_check(&self) -> BoxFuture<'_, Result<(), DynError>>;
+}
+
+/// Wraps a [`SpeechSynthesiser`] to implement the object-safe [`DynSpeechSynthesiserTrait`].
+#[derive(Debug, Clone)]
+pub struct DynSpeechSynthesiser<S>(S);
+
+impl<S: SpeechSynthesiser> DynSpeechSynthesiser<S> {
+	pub fn new(synthesiser: S) -> Self {
+		DynSpeechSynthesiser(synthesiser)
+	}
+
+	pub fn inner(&self) -> &S {
+		&self.0
+	}
+
+	pub fn into_inner(self) -> S {
+		self.0
+	}
+}
+
+impl<S: SpeechSynthesiser + Send + Sync> DynSpeechSynthesiserTrait for DynSpeechSynthesiser<S> {
+	fn negotiate_audio_format(&self, pref: &AudioFormatPreference) -> Option<AudioFormat> {
+		self.0.negotiate_audio_format(pref)
+	}
+
+	fn capabilities(&self) -> SynthesiserCapabilities {
+		self.0.capabilities()
+	}
+
+	fn validate_ssml(&self, input: &ssml::Speak<'_>) -> Result<(), Vec<SsmlValidationError>> {
+		self.0.validate_ssml(input)
+	}
+
+	fn estimate_duration(&self, text: &str, config: &UtteranceConfig) -> Option<Duration> {
+		self.0.estimate_duration(text, config)
+	}
+
+	fn supported_sample_rates(&self) -> &[u32] {
+		self.0.supported_sample_rates()
+	}
+
+	fn supported_containers(&self) -> &[AudioContainer] {
+		self.0.supported_containers()
+	}
+
+	fn synthesise_ssml_stream<'a>(
+		&'a self,
+		input: &'a ssml::Speak<'_>,
+		audio_format: &'a AudioFormat,
+		config: &'a UtteranceConfig
+	) -> BoxFuture<'a, Result<BoxUtteranceEventStream, DynError>> {
+		let fut = self.0.synthesise_ssml_stream(input, audio_format, config);
+		Box::pin(async move {
+			let stream = fut.await.map_err(DynError::new)?;
+			Ok(Box::pin(ErasedStream { inner: stream }) as BoxUtteranceEventStream)
+		})
+	}
+
+	fn synthesise_text_stream<'a>(
+		&'a self,
+		input: &'a str,
+		audio_format: &'a AudioFormat,
+		config: &'a UtteranceConfig
+	) -> BoxFuture<'a, Result<BoxUtteranceEventStream, DynError>> {
+		let fut = self.0.synthesise_text_stream(input, audio_format, config);
+		Box::pin(async move {
+			let stream = fut.await.map_err(DynError::new)?;
+			Ok(Box::pin(ErasedStream { inner: stream }) as BoxUtteranceEventStream)
+		})
+	}
+
+	fn list_voices(&self) -> BoxFuture<'_, Result<Vec<VoiceInfo>, DynError>> {
+		let fut = self.0.list_voices();
+		Box::pin(async move { fut.await.map_err(DynError::new) })
+	}
+
+	fn supported_languages(&self) -> BoxFuture<'_, Result<Vec<LanguageInfo>, DynError>> {
+		let fut = self.0.supported_languages();
+		Box::pin(async move { fut.await.map_err(DynError::new) })
+	}
+
+	fn health_check(&self) -> BoxFuture<'_, Result<(), DynError>> {
+		let fut = self.0.health_check();
+		Box::pin(async move { fut.await.map_err(DynError::new) })
+	}
+}
+
+/// Converts a [`SpeechSynthesiser`] into a boxed, object-safe [`DynSpeechSynthesiserTrait`].
+///
+/// This allows for selecting a synthesiser at runtime, or storing synthesisers of different types in a collection.
+pub fn into_dyn<S: SpeechSynthesiser + Send + Sync + 'static>(synthesiser: S) -> Box<dyn DynSpeechSynthesiserTrait> {
+	Box::new(DynSpeechSynthesiser::new(synthesiser))
+}
+
+impl SpeechSynthesiser for Box<dyn DynSpeechSynthesiserTrait> {
+	type Error = DynError;
+
+	fn negotiate_audio_format(&self, pref: &AudioFormatPreference) -> Option<AudioFormat> {
+		(**self).negotiate_audio_format(pref)
+	}
+
+	fn capabilities(&self) -> SynthesiserCapabilities {
+		(**self).capabilities()
+	}
+
+	fn validate_ssml(&self, input: &ssml::Speak<'_>) -> Result<(), Vec<SsmlValidationError>> {
+		(**self).validate_ssml(input)
+	}
+
+	fn estimate_duration(&self, text: &str, config: &UtteranceConfig) -> Option<Duration> {
+		(**self).estimate_duration(text, config)
+	}
+
+	fn supported_sample_rates(&self) -> &[u32] {
+		(**self).supported_sample_rates()
+	}
+
+	fn supported_containers(&self) -> &[AudioContainer] {
+		(**self).supported_containers()
+	}
+
+	async fn synthesise_ssml_stream(
+		&self,
+		input: &ssml::Speak<'_>,
+		audio_format: &AudioFormat,
+		config: &UtteranceConfig
+	) -> Result<impl UtteranceEventStream<Self::Error> + 'static, Self::Error> {
+		(**self).synthesise_ssml_stream(input, audio_format, config).await
+	}
+
+	async fn synthesise_text_stream(
+		&self,
+		input: &str,
+		audio_format: &AudioFormat,
+		config: &UtteranceConfig
+	) -> Result<impl UtteranceEventStream<Self::Error> + 'static, Self::Error> {
+		(**self).synthesise_text_stream(input, audio_format, config).await
+	}
+
+	fn list_voices(&self) -> impl Future<Output = Result<Vec<VoiceInfo>, Self::Error>> + Send {
+		(**self).list_voices()
+	}
+
+	fn supported_languages(&self) -> impl Future<Output = Result<Vec<LanguageInfo>, Self::Error>> + Send {
+		(**self).supported_languages()
+	}
+
+	fn health_check(&self) -> impl Future<Output = Result<(), Self::Error>> + Send {
+		(**self).health_check()
+	}
+}
+
+pin_project! {
+	struct ErasedStream<S> {
+		#[pin]
+		inner: S
+	}
+}
+
+impl<E: std::error::Error + Send + Sync + 'static, S: Stream<Item = Result<UtteranceEvent, E>>> Stream for ErasedStream<S> {
+	type Item = Result<UtteranceEvent, DynError>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		self.project().inner.poll_next(cx).map(|item| item.map(|res| res.map_err(DynError::new)))
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.inner.size_hint()
+	}
+}
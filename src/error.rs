@@ -0,0 +1,148 @@
+use core::fmt;
+
+use crate::AudioFormat;
+
+/// An error returned by one of this crate's synthesiser adapters, wrapping the inner synthesiser's error type `E`.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SynthesisError<E> {
+	/// Synthesis was cancelled via a [`CancellationToken`](crate::CancellationToken).
+	Cancelled,
+	/// An error returned by the underlying synthesiser.
+	Synthesiser(E),
+	/// The operation did not complete within the configured timeout.
+	Timeout,
+	/// An I/O error encountered while reading input or writing output, i.e. in
+	/// [`SpeechSynthesiserExt::synthesise_text_reader_stream`](crate::SpeechSynthesiserExt::synthesise_text_reader_stream)
+	/// or [`SpeechSynthesiserExt::synthesise_ssml_to_writer`](crate::SpeechSynthesiserExt::synthesise_ssml_to_writer).
+	Io(std::io::Error),
+	/// The audio format cannot be handled by the operation, i.e. in
+	/// [`SpeechSynthesiserExt::pipe_to_rodio`](crate::SpeechSynthesiserExt::pipe_to_rodio), which only accepts 16-bit
+	/// PCM.
+	UnsupportedFormat(AudioFormat),
+	/// The configuration failed [validation](crate::UtteranceConfig::validate), so synthesis was not requested.
+	InvalidConfig(Vec<ConfigValidationError>)
+}
+
+impl<E> SynthesisError<E> {
+	/// Returns the underlying synthesiser's error, if this error originated from the synthesiser.
+	pub fn into_synthesiser_error(self) -> Option<E> {
+		match self {
+			SynthesisError::Synthesiser(e) => Some(e),
+			_ => None
+		}
+	}
+}
+
+impl<E: fmt::Display> fmt::Display for SynthesisError<E> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			SynthesisError::Cancelled => f.write_str("synthesis was cancelled"),
+			SynthesisError::Timeout => f.write_str("operation timed out"),
+			SynthesisError::Synthesiser(e) => e.fmt(f),
+			SynthesisError::Io(e) => e.fmt(f),
+			SynthesisError::UnsupportedFormat(format) => write!(f, "unsupported audio format {:?}", format.container()),
+			SynthesisError::InvalidConfig(errors) => {
+				f.write_str("invalid configuration: ")?;
+				for (i, error) in errors.iter().enumerate() {
+					if i > 0 {
+						f.write_str("; ")?;
+					}
+					error.fmt(f)?;
+				}
+				Ok(())
+			}
+		}
+	}
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for SynthesisError<E> {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			SynthesisError::Synthesiser(e) => Some(e),
+			SynthesisError::Io(e) => Some(e),
+			_ => None
+		}
+	}
+}
+
+/// An error describing an invalid [`UtteranceConfig`](crate::UtteranceConfig), as returned by
+/// [`UtteranceConfig::validate`](crate::UtteranceConfig::validate).
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum ConfigValidationError {
+	/// The configured speaking rate is not a positive, finite number.
+	SpeakingRateOutOfRange(f32),
+	/// The configured pitch shift is not a finite number.
+	PitchShiftOutOfRange(f32),
+	/// The configured volume is not a finite number.
+	VolumeOutOfRange(f32),
+	/// The configured volume is below [`MIN_VOLUME_DB`](crate::UtteranceConfig::MIN_VOLUME_DB), at which the audio
+	/// would be inaudible.
+	VolumeTooLow(f32),
+	/// The configured voice style degree is not within `0.01..=2.0`.
+	SpeakingStyleDegreeOutOfRange(f32),
+	/// The configured silence threshold is not a non-negative, finite number of milliseconds.
+	SilenceThresholdOutOfRange(f32),
+	/// The configured [`SentenceDetectionMode::Regex`](crate::SentenceDetectionMode::Regex) pattern is invalid or uses
+	/// unsupported syntax.
+	InvalidSentencePattern(Box<str>),
+	/// The configured [custom endpoint](crate::UtteranceConfig::custom_endpoint) is not a valid URL.
+	InvalidEndpoint(Box<str>),
+	/// The configuration requests a feature which is not supported by the synthesiser, as determined by
+	/// [`UtteranceConfig::validated_for`](crate::UtteranceConfig::validated_for).
+	Unsupported(&'static str),
+	/// A synthesiser-specific constraint on the configuration was violated, with a description of the problem.
+	Custom(Box<str>)
+}
+
+impl fmt::Display for ConfigValidationError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			ConfigValidationError::SpeakingRateOutOfRange(x) => write!(f, "speaking rate must be a positive number, got {x}"),
+			ConfigValidationError::PitchShiftOutOfRange(x) => write!(f, "pitch shift must be a finite number of semitones, got {x}"),
+			ConfigValidationError::VolumeOutOfRange(x) => write!(f, "volume must be a finite number of decibels, got {x}"),
+			ConfigValidationError::VolumeTooLow(x) => write!(f, "volume must be at least {} dB, got {x}", crate::UtteranceConfig::MIN_VOLUME_DB),
+			ConfigValidationError::SpeakingStyleDegreeOutOfRange(x) => write!(f, "voice style degree must be between 0.01 and 2.0, got {x}"),
+			ConfigValidationError::InvalidSentencePattern(pattern) => write!(f, "invalid or unsupported sentence pattern `{pattern}`"),
+			ConfigValidationError::InvalidEndpoint(url) => write!(f, "invalid custom endpoint URL `{url}`"),
+			ConfigValidationError::SilenceThresholdOutOfRange(x) => write!(f, "silence threshold must be a non-negative number of milliseconds, got {x}"),
+			ConfigValidationError::Unsupported(feature) => write!(f, "synthesiser does not support {feature}"),
+			ConfigValidationError::Custom(message) => f.write_str(message)
+		}
+	}
+}
+
+impl std::error::Error for ConfigValidationError {}
+
+/// An error parsing a RIFF WAVE header, as returned by
+/// [`AudioFormat::from_riff_header`](crate::AudioFormat::from_riff_header).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RiffParseError {
+	/// The header ended before the `fmt ` chunk was fully read.
+	InsufficientBytes,
+	/// The bytes are not a RIFF WAVE header, or the `fmt ` chunk is inconsistent.
+	Malformed,
+	/// The `fmt ` chunk uses a format tag other than PCM (`1`), IEEE float (`3`), A-law (`6`), or μ-law (`7`).
+	UnsupportedFormatTag(u16),
+	/// The `fmt ` chunk specifies a channel count with no corresponding [`AudioChannels`](crate::AudioChannels).
+	UnsupportedChannels(u16),
+	/// The `fmt ` chunk specifies a bit depth with no corresponding [`AudioEncoding`](crate::AudioEncoding) for its
+	/// format tag.
+	UnsupportedBitDepth(u16)
+}
+
+impl fmt::Display for RiffParseError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			RiffParseError::InsufficientBytes => f.write_str("RIFF header is truncated"),
+			RiffParseError::Malformed => f.write_str("malformed RIFF WAVE header"),
+			RiffParseError::UnsupportedFormatTag(x) => write!(f, "unsupported RIFF format tag {x}"),
+			RiffParseError::UnsupportedChannels(x) => write!(f, "unsupported channel count {x}"),
+			RiffParseError::UnsupportedBitDepth(x) => write!(f, "unsupported bit depth {x}")
+		}
+	}
+}
+
+impl std::error::Error for RiffParseError {}
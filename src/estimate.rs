@@ -0,0 +1,99 @@
+use core::time::Duration;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The speaking rate assumed for languages which are not in a [`SpeechRateTable`], in words per minute.
+const DEFAULT_WORDS_PER_MINUTE: f32 = 150.0;
+
+/// Approximate average speaking rates of common languages, in words per minute. Chinese & Japanese are measured in
+/// characters per minute, since each ideograph is counted as a word.
+const RATES: &[(&str, f32)] = &[
+	("ar", 130.0),
+	("de", 130.0),
+	("en", 160.0),
+	("es", 160.0),
+	("fr", 150.0),
+	("hi", 140.0),
+	("it", 150.0),
+	("ja", 300.0),
+	("ko", 120.0),
+	("nl", 140.0),
+	("pl", 120.0),
+	("pt", 150.0),
+	("ru", 120.0),
+	("tr", 120.0),
+	("zh", 250.0)
+];
+
+/// A table of average speaking rates by language, used to roughly estimate how long text will take to speak before it
+/// is synthesised; see [`SpeechSynthesiser::estimate_duration`](crate::SpeechSynthesiser::estimate_duration).
+///
+/// Words are counted according to the Unicode word boundary rules, under which each Chinese or Japanese ideograph is
+/// a separate word. Estimates are only accurate to within roughly ±30%, since actual speaking rates vary greatly
+/// between voices and texts; the true timing of synthesised speech is reported by
+/// [`UtteranceEvent::SentenceBoundary`](crate::UtteranceEvent::SentenceBoundary) events.
+#[derive(Debug, Clone)]
+pub struct SpeechRateTable {
+	rates: Vec<(Box<str>, f32)>,
+	default_rate: f32
+}
+
+impl Default for SpeechRateTable {
+	/// Creates a table of the built-in speaking rates of common languages.
+	fn default() -> Self {
+		SpeechRateTable {
+			rates: RATES.iter().map(|&(language, rate)| (Box::from(language), rate)).collect(),
+			default_rate: DEFAULT_WORDS_PER_MINUTE
+		}
+	}
+}
+
+impl SpeechRateTable {
+	/// Creates an empty table, in which every language is assumed to be spoken at 150 words per minute.
+	pub fn new() -> Self {
+		SpeechRateTable {
+			rates: Vec::new(),
+			default_rate: DEFAULT_WORDS_PER_MINUTE
+		}
+	}
+
+	/// Configures the speaking rate of a language, in words per minute. `language` is a BCP-47 primary language subtag,
+	/// i.e. `en`.
+	pub fn with_rate(mut self, language: impl Into<Box<str>>, words_per_minute: f32) -> Self {
+		let language = language.into();
+		match self.rates.iter_mut().find(|(l, _)| l.eq_ignore_ascii_case(&language)) {
+			Some((_, rate)) => *rate = words_per_minute,
+			None => self.rates.push((language, words_per_minute))
+		}
+		self
+	}
+
+	/// Configures the speaking rate assumed for languages not in the table, in words per minute.
+	pub fn with_default_rate(mut self, words_per_minute: f32) -> Self {
+		self.default_rate = words_per_minute;
+		self
+	}
+
+	/// Returns the speaking rate of the language of a BCP-47 locale (i.e. `en-US`) in words per minute, or the default
+	/// rate if the language is not in the table or no locale is given.
+	pub fn words_per_minute(&self, locale: Option<&str>) -> f32 {
+		let Some(locale) = locale else {
+			return self.default_rate;
+		};
+		let language = locale.split(['-', '_']).next().unwrap_or(locale);
+		self.rates
+			.iter()
+			.find(|(l, _)| l.eq_ignore_ascii_case(language))
+			.map_or(self.default_rate, |(_, rate)| *rate)
+	}
+
+	/// Estimates how long `text` would take to speak in the language of the given BCP-47 locale.
+	pub fn estimate(&self, text: &str, locale: Option<&str>) -> Duration {
+		let words = text.unicode_words().count();
+		let rate = self.words_per_minute(locale);
+		if words == 0 || !(rate.is_finite() && rate > 0.0) {
+			return Duration::ZERO;
+		}
+		Duration::from_secs_f32(words as f32 * 60.0 / rate)
+	}
+}
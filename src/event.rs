@@ -1,7 +1,12 @@
+use std::time::Instant;
+
 use futures_core::Stream;
 
+use crate::AudioFormat;
+
 /// Key & weight information for a single blend shape as part of a [`BlendShapeVisemeFrame`].
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BlendShape {
 	/// Blend shape key, typically as an [ARKit](https://developer.apple.com/documentation/arkit/arblendshapelocation?language=objc) blend shape.
 	pub key: Box<str>,
@@ -9,34 +14,137 @@ pub struct BlendShape {
 	pub weight: f32
 }
 
+impl BlendShape {
+	/// Linearly interpolates between the weights of `self` (at `t = 0.0`) and `other` (at `t = 1.0`).
+	///
+	/// # Panics
+	/// Panics if `self` and `other` do not have the same key.
+	pub fn lerp(&self, other: &BlendShape, t: f32) -> BlendShape {
+		assert_eq!(self.key, other.key, "cannot interpolate between different blend shapes");
+		BlendShape {
+			key: self.key.clone(),
+			weight: lerp(self.weight, other.weight, t)
+		}
+	}
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+	a + (b - a) * t
+}
+
 /// A single frame for visemes in blend shape format.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BlendShapeVisemeFrame {
 	pub blendshapes: Box<[BlendShape]>,
 	/// Offset of this blendshape frame relative to the beginning of the audio stream.
 	pub frame_offset: f32
 }
 
+impl BlendShapeVisemeFrame {
+	/// Linearly interpolates each blend shape & the frame offset between `self` (at `t = 0.0`) and `other` (at
+	/// `t = 1.0`).
+	///
+	/// # Panics
+	/// Panics if `self` and `other` do not contain the same blend shapes in the same order.
+	pub fn lerp(&self, other: &BlendShapeVisemeFrame, t: f32) -> BlendShapeVisemeFrame {
+		assert_eq!(self.blendshapes.len(), other.blendshapes.len(), "cannot interpolate between frames with different blend shapes");
+		BlendShapeVisemeFrame {
+			blendshapes: self.blendshapes.iter().zip(other.blendshapes.iter()).map(|(a, b)| a.lerp(b, t)).collect(),
+			frame_offset: lerp(self.frame_offset, other.frame_offset, t)
+		}
+	}
+
+	/// Returns the duration in milliseconds for which this frame is shown, i.e. until the start of `next`, or until
+	/// `stream_end_millis` if this is the last frame of the stream.
+	pub fn duration_until(&self, next: Option<&BlendShapeVisemeFrame>, stream_end_millis: f32) -> f32 {
+		next.map_or(stream_end_millis, |next| next.frame_offset) - self.frame_offset
+	}
+}
+
+/// Interpolates between frames `a` and `b` to compute the frame at `at_offset`, i.e. for rendering viseme animation
+/// at a higher frame rate than it was synthesised at.
+///
+/// `at_offset` is clamped to the range between the two frames' offsets. See [`BlendShapeVisemeFrame::lerp`].
+pub fn interpolate_frames(a: &BlendShapeVisemeFrame, b: &BlendShapeVisemeFrame, at_offset: f32) -> BlendShapeVisemeFrame {
+	let span = b.frame_offset - a.frame_offset;
+	let t = if span == 0.0 { 0.0 } else { ((at_offset - a.frame_offset) / span).clamp(0.0, 1.0) };
+	a.lerp(b, t)
+}
+
 /// A 'basic' viseme.
 ///
-/// The format for basic visemes is not currently defined due to conflicts between Azure Cognitive Speech Services &
-/// Amazon Polly's viseme mappings.
+/// The format for basic visemes is not strictly defined due to conflicts between Azure Cognitive Speech Services &
+/// Amazon Polly's viseme mappings. Use [`BasicViseme::to_standard`] to map visemes to a well-known
+/// [`StandardViseme`](crate::StandardViseme).
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BasicViseme(pub char);
 
 /// A single frame of 'basic' visemes.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BasicVisemeFrame {
 	pub viseme: BasicViseme,
 	/// Offset of this viseme frame relative to the beginning of the audio stream.
 	pub frame_offset: f32
 }
 
+impl BasicVisemeFrame {
+	/// Returns the duration in milliseconds for which this frame is shown, i.e. until the start of `next`.
+	pub fn duration_until(&self, next: &BasicVisemeFrame) -> f32 {
+		next.frame_offset - self.frame_offset
+	}
+}
+
+/// Pairs each frame with its duration in milliseconds (see [`BasicVisemeFrame::duration_until`]), i.e. for rendering
+/// lip sync animation.
+///
+/// Since the duration of the last frame is unknown, it is estimated as the mean duration of the other frames (or `0.0`
+/// if there is only one frame).
+pub fn pair_with_durations(frames: &[BasicVisemeFrame]) -> Vec<(BasicVisemeFrame, f32)> {
+	let durations: Vec<f32> = frames.windows(2).map(|pair| pair[0].duration_until(&pair[1])).collect();
+	let last = if durations.is_empty() { 0.0 } else { durations.iter().sum::<f32>() / durations.len() as f32 };
+	frames.iter().cloned().zip(durations.into_iter().chain(core::iter::once(last))).collect()
+}
+
 /// An event emitted by a speech synthesiser's [`UtteranceEventStream`].
-#[derive(Debug)]
+///
+/// With the `serde` feature enabled, an event variant which is unknown to this version of the crate is deserialized as
+/// [`UtteranceEvent::Unknown`], discarding its content, so that streams serialized by newer versions can still be
+/// read.
+#[derive(Debug, Clone)]
 #[non_exhaustive]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(remote = "Self"))]
 pub enum UtteranceEvent {
+	/// Emitted as the first item of a synthesis stream.
+	///
+	/// Together with [`UtteranceEvent::SynthesisCompleted`], this brackets every successful synthesis stream, i.e. to
+	/// measure the latency to the first [`UtteranceEvent::AudioChunk`]. Synthesisers can use
+	/// [`stream::with_synthesis_events`](crate::stream::with_synthesis_events) to emit both events.
+	SynthesisStarted {
+		/// The time at which synthesis was requested.
+		///
+		/// Since an [`Instant`] is only meaningful within the current process, this is not serialized with the `serde`
+		/// feature, and deserializes to the time of deserialization.
+		#[cfg_attr(feature = "serde", serde(skip, default = "Instant::now"))]
+		requested_at: Instant,
+		/// The [request ID](crate::UtteranceConfig::request_id) of this synthesis, if one was configured.
+		#[cfg_attr(feature = "serde", serde(default))]
+		request_id: Option<Box<str>>
+	},
+	/// Emitted as the last item of a synthesis stream which completed successfully. Streams which end due to an error
+	/// do not emit this event.
+	SynthesisCompleted {
+		/// The time in milliseconds taken to synthesise the full utterance, measured from when synthesis was requested.
+		duration_millis: f32,
+		/// The total size in bytes of all [`UtteranceEvent::AudioChunk`]s emitted by the stream.
+		total_audio_bytes: u64
+	},
 	/// Marks the audio offset of an [`ssml::Mark`].
+	///
+	/// Only emitted if enabled with
+	/// [`UtteranceConfig::with_emit_ssml_marks`](crate::UtteranceConfig::with_emit_ssml_marks).
 	SsmlMark {
 		/// The position in milliseconds the mark occurred, relative to the beginning of the audio stream.
 		at_millis: f32,
@@ -50,7 +158,14 @@ pub enum UtteranceEvent {
 		/// The position in milliseconds the spoken word ended, relative to the beginning of the audio stream.
 		to_millis: f32,
 		/// The text of the single word spoken between this boundary.
-		text: Box<str>
+		text: Box<str>,
+		/// The synthesiser's confidence in the quality of the synthesised word, from `0.0` (lowest) to `1.0` (highest),
+		/// if reported by the synthesiser.
+		#[cfg_attr(feature = "serde", serde(default))]
+		confidence: Option<f32>,
+		/// The phonetic transcription of the word, in standard Unicode IPA notation, if reported by the synthesiser.
+		#[cfg_attr(feature = "serde", serde(default))]
+		phonetic: Option<Box<str>>
 	},
 	/// Marks the time boundary of a sentence in the audio.
 	SentenceBoundary {
@@ -61,12 +176,163 @@ pub enum UtteranceEvent {
 		/// The text of the sentence spoken between this boundary.
 		text: Box<str>
 	},
+	/// Marks the time boundary of a paragraph in the audio.
+	ParagraphBoundary {
+		/// The position in milliseconds the paragraph begun, relative to the beginning of the audio stream.
+		from_millis: f32,
+		/// The position in milliseconds the paragraph ended, relative to the beginning of the audio stream.
+		to_millis: f32,
+		/// The full text of the paragraph spoken between this boundary, as it appears in the source document.
+		text: Box<str>
+	},
+	/// Marks the time boundary of a single spoken phoneme in the audio.
+	Phoneme {
+		/// The phoneme, in standard Unicode IPA notation.
+		ipa: Box<str>,
+		/// The position in milliseconds the phoneme begun, relative to the beginning of the audio stream.
+		from_millis: f32,
+		/// The position in milliseconds the phoneme ended, relative to the beginning of the audio stream.
+		to_millis: f32,
+		/// The index of the word this phoneme belongs to, matching the order in which
+		/// [`UtteranceEvent::WordBoundary`] events are emitted.
+		word_index: u32
+	},
+	/// Marks a change in the voice speaking, i.e. between multiple [`ssml::Voice`] elements.
+	///
+	/// This event is emitted immediately before the first [`UtteranceEvent::AudioChunk`] spoken by the new voice. It
+	/// is also always emitted at the start of synthesis for the initial voice.
+	SpeakerChange {
+		/// The position in milliseconds the new voice begun speaking, relative to the beginning of the audio stream.
+		at_millis: f32,
+		/// The name of the voice now speaking.
+		voice_name: Box<str>
+	},
+	/// Marks the beginning of a chapter, when synthesising multiple documents with
+	/// [`SpeechSynthesiserExt::synthesise_ssml_document`](crate::SpeechSynthesiserExt::synthesise_ssml_document).
+	ChapterBoundary {
+		/// The index of the chapter beginning, in the order the chapters were given.
+		chapter_index: u32,
+		/// The position in milliseconds the chapter begun, relative to the beginning of the audio stream.
+		at_millis: f32
+	},
+	/// Marks a pause in speech, i.e. from a [`ssml::Break`] or between sentences, during which a character's mouth
+	/// should be closed.
+	///
+	/// Only emitted if enabled with
+	/// [`UtteranceConfig::with_emit_pause_events`](crate::UtteranceConfig::with_emit_pause_events). Synthesisers which
+	/// do not report pauses themselves can use [`stream::detect_pauses`](crate::stream::detect_pauses) to detect them
+	/// from the audio.
+	PauseDetected {
+		/// The position in milliseconds the pause begun, relative to the beginning of the audio stream.
+		at_millis: f32,
+		/// The length of the pause in milliseconds.
+		duration_millis: f32
+	},
+	/// Marks a run of silence in the audio which does not coincide with a pause or sentence boundary, possibly
+	/// indicating dropped words or a backend error.
+	///
+	/// Emitted by [`stream::with_silence_detection`](crate::stream::with_silence_detection).
+	UnexpectedSilence {
+		/// The position in milliseconds the silence begun, relative to the beginning of the audio stream.
+		at_millis: f32,
+		/// The length of the silence in milliseconds.
+		duration_millis: f32
+	},
+	/// Reports the language detected for a segment of text, when enabled with
+	/// [`UtteranceConfig::with_auto_detect_language`](crate::UtteranceConfig::with_auto_detect_language).
+	LanguageDetected {
+		/// The segment of input text the language was detected for.
+		segment_text: Box<str>,
+		/// The detected language, as a BCP-47 locale (i.e. `es-ES`).
+		detected_language: Box<str>,
+		/// The synthesiser's confidence in the detected language, from `0.0` (lowest) to `1.0` (highest).
+		confidence: f32
+	},
+	/// The format of the audio in the stream, emitted as the first event by
+	/// [`stream::with_audio_format_tag`](crate::stream::with_audio_format_tag), i.e. so that a serialized stream can be
+	/// decoded without knowing its format out-of-band.
+	AudioFormat(AudioFormat),
 	/// A chunk of viseme frames in blend shape format.
 	BlendShapeVisemesChunk(Box<[BlendShapeVisemeFrame]>),
 	/// A chunk of frames of 'basic' visemes.
 	VisemesChunk(Box<[BasicVisemeFrame]>),
 	/// A chunk of synthesised speech audio in the requested format.
-	AudioChunk(Box<[u8]>)
+	///
+	/// When serialized with the `serde` feature, the audio is encoded as a base64 string in human-readable formats
+	/// (i.e. JSON), and as raw bytes otherwise.
+	AudioChunk(#[cfg_attr(feature = "serde", serde(with = "crate::serde_base64"))] Box<[u8]>),
+	/// An event of a kind which is unknown to this version of the crate, i.e. one deserialized (with the `serde`
+	/// feature) from a stream serialized by a newer version. Synthesisers never emit this event, and consumers should
+	/// ignore it.
+	Unknown
+}
+
+impl UtteranceEvent {
+	/// Returns the position in milliseconds this event occurred or begun, relative to the beginning of the audio
+	/// stream.
+	///
+	/// Returns `None` for [`UtteranceEvent::AudioChunk`] and viseme chunks, whose frames are timestamped individually,
+	/// for [`UtteranceEvent::LanguageDetected`] & [`UtteranceEvent::AudioFormat`], and for the
+	/// [`UtteranceEvent::SynthesisStarted`] & [`UtteranceEvent::SynthesisCompleted`] sentinels.
+	pub fn timestamp_millis(&self) -> Option<f32> {
+		match self {
+			UtteranceEvent::SsmlMark { at_millis, .. }
+			| UtteranceEvent::SpeakerChange { at_millis, .. }
+			| UtteranceEvent::ChapterBoundary { at_millis, .. }
+			| UtteranceEvent::PauseDetected { at_millis, .. }
+			| UtteranceEvent::UnexpectedSilence { at_millis, .. } => Some(*at_millis),
+			UtteranceEvent::WordBoundary { from_millis, .. }
+			| UtteranceEvent::SentenceBoundary { from_millis, .. }
+			| UtteranceEvent::ParagraphBoundary { from_millis, .. }
+			| UtteranceEvent::Phoneme { from_millis, .. } => Some(*from_millis),
+			UtteranceEvent::BlendShapeVisemesChunk(_)
+			| UtteranceEvent::VisemesChunk(_)
+			| UtteranceEvent::AudioChunk(_)
+			| UtteranceEvent::AudioFormat(_)
+			| UtteranceEvent::LanguageDetected { .. }
+			| UtteranceEvent::SynthesisStarted { .. }
+			| UtteranceEvent::SynthesisCompleted { .. }
+			| UtteranceEvent::Unknown => None
+		}
+	}
+
+	/// Shifts all timestamps in this event by `offset_millis`, i.e. so that the event is relative to the beginning of a
+	/// longer stream its audio has been concatenated into. Frames in viseme chunks are shifted individually.
+	pub fn offset_timestamps(&mut self, offset_millis: f32) {
+		match self {
+			UtteranceEvent::SsmlMark { at_millis, .. }
+			| UtteranceEvent::SpeakerChange { at_millis, .. }
+			| UtteranceEvent::ChapterBoundary { at_millis, .. }
+			| UtteranceEvent::PauseDetected { at_millis, .. }
+			| UtteranceEvent::UnexpectedSilence { at_millis, .. } => *at_millis += offset_millis,
+			UtteranceEvent::WordBoundary { from_millis, to_millis, .. }
+			| UtteranceEvent::SentenceBoundary { from_millis, to_millis, .. }
+			| UtteranceEvent::ParagraphBoundary { from_millis, to_millis, .. }
+			| UtteranceEvent::Phoneme { from_millis, to_millis, .. } => {
+				*from_millis += offset_millis;
+				*to_millis += offset_millis;
+			}
+			UtteranceEvent::BlendShapeVisemesChunk(frames) => frames.iter_mut().for_each(|frame| frame.frame_offset += offset_millis),
+			UtteranceEvent::VisemesChunk(frames) => frames.iter_mut().for_each(|frame| frame.frame_offset += offset_millis),
+			UtteranceEvent::AudioChunk(_)
+			| UtteranceEvent::AudioFormat(_)
+			| UtteranceEvent::LanguageDetected { .. }
+			| UtteranceEvent::SynthesisStarted { .. }
+			| UtteranceEvent::SynthesisCompleted { .. }
+			| UtteranceEvent::Unknown => {}
+		}
+	}
+
+	/// Returns `true` if this event is an [`UtteranceEvent::AudioChunk`].
+	pub fn is_audio(&self) -> bool {
+		matches!(self, UtteranceEvent::AudioChunk(_))
+	}
+
+	/// Returns `true` if this event is a [`UtteranceEvent::WordBoundary`], [`UtteranceEvent::SentenceBoundary`], or
+	/// [`UtteranceEvent::ParagraphBoundary`].
+	pub fn is_boundary(&self) -> bool {
+		matches!(self, UtteranceEvent::WordBoundary { .. } | UtteranceEvent::SentenceBoundary { .. } | UtteranceEvent::ParagraphBoundary { .. })
+	}
 }
 
 /// A stream of [`UtteranceEvent`]s returned by the synthesiser.
@@ -75,3 +341,47 @@ pub enum UtteranceEvent {
 pub trait UtteranceEventStream<E>: Stream<Item = Result<UtteranceEvent, E>> + Send {}
 
 impl<E, T: Stream<Item = Result<UtteranceEvent, E>> + Send> UtteranceEventStream<E> for T {}
+
+/// The time boundary of a single spoken word, as collected by
+/// [`UtteranceEventStreamExt::collect_word_boundaries`](crate::UtteranceEventStreamExt::collect_word_boundaries).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WordBoundary {
+	/// The position in milliseconds the spoken word begun, relative to the beginning of the audio stream.
+	pub from_millis: f32,
+	/// The position in milliseconds the spoken word ended, relative to the beginning of the audio stream.
+	pub to_millis: f32,
+	/// The text of the single word spoken between this boundary.
+	pub text: Box<str>,
+	/// The synthesiser's confidence in the quality of the synthesised word, from `0.0` (lowest) to `1.0` (highest),
+	/// if reported by the synthesiser.
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub confidence: Option<f32>,
+	/// The phonetic transcription of the word, in standard Unicode IPA notation, if reported by the synthesiser.
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub phonetic: Option<Box<str>>
+}
+
+/// The time boundary of a sentence, as collected by
+/// [`UtteranceEventStreamExt::collect_sentence_boundaries`](crate::UtteranceEventStreamExt::collect_sentence_boundaries).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SentenceBoundary {
+	/// The position in milliseconds the sentence begun, relative to the beginning of the audio stream.
+	pub from_millis: f32,
+	/// The position in milliseconds the sentence ended, relative to the beginning of the audio stream.
+	pub to_millis: f32,
+	/// The text of the sentence spoken between this boundary.
+	pub text: Box<str>
+}
+
+/// The position of an [`ssml::Mark`], as collected by
+/// [`UtteranceEventStreamExt::collect_ssml_marks`](crate::UtteranceEventStreamExt::collect_ssml_marks).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SsmlMarkEvent {
+	/// The position in milliseconds the mark occurred, relative to the beginning of the audio stream.
+	pub at_millis: f32,
+	/// The name of the mark in SSML.
+	pub mark: Box<str>
+}
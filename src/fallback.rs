@@ -0,0 +1,228 @@
+use core::{
+	fmt,
+	pin::Pin,
+	task::{Context, Poll},
+	time::Duration
+};
+
+use futures_core::Stream;
+use pin_project_lite::pin_project;
+
+use crate::{
+	AudioContainer, AudioFormat, AudioFormatPreference, LanguageInfo, SpeechSynthesiser, SsmlValidationError, SynthesiserCapabilities, UtteranceConfig,
+	UtteranceEvent, UtteranceEventStream, VoiceInfo
+};
+
+/// An error returned by a [`FallbackSpeechSynthesiser`].
+#[derive(Debug)]
+pub enum FallbackError<A, B> {
+	/// An error yielded by the primary synthesiser's stream.
+	Primary(A),
+	/// An error yielded by the fallback synthesiser's stream.
+	Fallback(B),
+	/// Both the primary and fallback synthesisers failed to begin synthesis.
+	Both { primary: A, fallback: B }
+}
+
+impl<A: fmt::Display, B: fmt::Display> fmt::Display for FallbackError<A, B> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			FallbackError::Primary(e) => e.fmt(f),
+			FallbackError::Fallback(e) => e.fmt(f),
+			FallbackError::Both { primary, fallback } => write!(f, "primary synthesiser failed: {primary}; fallback synthesiser failed: {fallback}")
+		}
+	}
+}
+
+impl<A: std::error::Error + 'static, B: std::error::Error + 'static> std::error::Error for FallbackError<A, B> {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			FallbackError::Primary(e) => Some(e),
+			FallbackError::Fallback(e) | FallbackError::Both { fallback: e, .. } => Some(e)
+		}
+	}
+}
+
+/// A [`SpeechSynthesiser`] which attempts synthesis with a primary synthesiser `A`, falling back to `B` if `A` fails.
+///
+/// The fallback synthesiser is only used if the primary synthesiser fails to *begin* synthesis, i.e. its
+/// `synthesise_*_stream` future resolves to an error. Errors yielded mid-stream are passed through as-is, since audio
+/// may have already been emitted by then.
+///
+/// Fallback synthesisers can be nested to try any number of synthesisers in order, e.g.
+/// `FallbackSpeechSynthesiser::from((a, FallbackSpeechSynthesiser::from((b, c))))`.
+#[derive(Debug, Clone)]
+pub struct FallbackSpeechSynthesiser<A, B> {
+	primary: A,
+	fallback: B
+}
+
+impl<A: SpeechSynthesiser, B: SpeechSynthesiser> FallbackSpeechSynthesiser<A, B> {
+	pub fn new(primary: A, fallback: B) -> Self {
+		FallbackSpeechSynthesiser { primary, fallback }
+	}
+
+	pub fn primary(&self) -> &A {
+		&self.primary
+	}
+
+	pub fn fallback(&self) -> &B {
+		&self.fallback
+	}
+
+	pub fn into_inner(self) -> (A, B) {
+		(self.primary, self.fallback)
+	}
+}
+
+impl<A: SpeechSynthesiser, B: SpeechSynthesiser> From<(A, B)> for FallbackSpeechSynthesiser<A, B> {
+	fn from((primary, fallback): (A, B)) -> Self {
+		FallbackSpeechSynthesiser::new(primary, fallback)
+	}
+}
+
+fn format_matches(a: &AudioFormat, b: &AudioFormat) -> bool {
+	a.sample_rate() == b.sample_rate() && a.channels() == b.channels() && a.container() == b.container()
+}
+
+/// Negotiates a format with `first`, then checks that `second` supports the same format.
+fn negotiate_with_both(first: &impl SpeechSynthesiser, second: &impl SpeechSynthesiser, pref: &AudioFormatPreference) -> Option<AudioFormat> {
+	let format = first.negotiate_audio_format(pref)?;
+	let exact = AudioFormatPreference::default()
+		.with_prefer_sample_rates([format.sample_rate()])
+		.with_prefer_channels([format.channels()])
+		.with_prefer_bitrates(format.bitrate())
+		.with_prefer_containers([format.container()]);
+	let other = second.negotiate_audio_format(&exact)?;
+	format_matches(&format, &other).then_some(format)
+}
+
+impl<A: SpeechSynthesiser + Sync, B: SpeechSynthesiser + Sync> SpeechSynthesiser for FallbackSpeechSynthesiser<A, B> {
+	type Error = FallbackError<A::Error, B::Error>;
+
+	/// Negotiates an audio format supported by *both* synthesisers.
+	fn negotiate_audio_format(&self, pref: &AudioFormatPreference) -> Option<AudioFormat> {
+		negotiate_with_both(&self.primary, &self.fallback, pref).or_else(|| negotiate_with_both(&self.fallback, &self.primary, pref))
+	}
+
+	/// Returns the features supported by *both* synthesisers, since either may be used for synthesis.
+	fn capabilities(&self) -> SynthesiserCapabilities {
+		self.primary.capabilities().intersect(&self.fallback.capabilities())
+	}
+
+	/// Accepts the document if *either* synthesiser accepts it, returning the primary synthesiser's problems otherwise.
+	fn validate_ssml(&self, input: &ssml::Speak<'_>) -> Result<(), Vec<SsmlValidationError>> {
+		match self.primary.validate_ssml(input) {
+			Ok(()) => Ok(()),
+			Err(e) => self.fallback.validate_ssml(input).or(Err(e))
+		}
+	}
+
+	fn estimate_duration(&self, text: &str, config: &UtteranceConfig) -> Option<Duration> {
+		self.primary
+			.estimate_duration(text, config)
+			.or_else(|| self.fallback.estimate_duration(text, config))
+	}
+
+	/// Reports the primary synthesiser's sample rates.
+	fn supported_sample_rates(&self) -> &[u32] {
+		self.primary.supported_sample_rates()
+	}
+
+	/// Reports the primary synthesiser's containers.
+	fn supported_containers(&self) -> &[AudioContainer] {
+		self.primary.supported_containers()
+	}
+
+	async fn synthesise_ssml_stream(
+		&self,
+		input: &ssml::Speak<'_>,
+		audio_format: &AudioFormat,
+		config: &UtteranceConfig
+	) -> Result<impl UtteranceEventStream<Self::Error> + 'static, Self::Error> {
+		match self.primary.synthesise_ssml_stream(input, audio_format, config).await {
+			Ok(stream) => Ok(FallbackStream::Primary { inner: stream }),
+			Err(primary) => match self.fallback.synthesise_ssml_stream(input, audio_format, config).await {
+				Ok(stream) => Ok(FallbackStream::Fallback { inner: stream }),
+				Err(fallback) => Err(FallbackError::Both { primary, fallback })
+			}
+		}
+	}
+
+	async fn synthesise_text_stream(
+		&self,
+		input: &str,
+		audio_format: &AudioFormat,
+		config: &UtteranceConfig
+	) -> Result<impl UtteranceEventStream<Self::Error> + 'static, Self::Error> {
+		match self.primary.synthesise_text_stream(input, audio_format, config).await {
+			Ok(stream) => Ok(FallbackStream::Primary { inner: stream }),
+			Err(primary) => match self.fallback.synthesise_text_stream(input, audio_format, config).await {
+				Ok(stream) => Ok(FallbackStream::Fallback { inner: stream }),
+				Err(fallback) => Err(FallbackError::Both { primary, fallback })
+			}
+		}
+	}
+
+	/// Lists the voices of the primary synthesiser, or the fallback synthesiser if the primary fails.
+	async fn list_voices(&self) -> Result<Vec<VoiceInfo>, Self::Error> {
+		match self.primary.list_voices().await {
+			Ok(voices) => Ok(voices),
+			Err(primary) => self
+				.fallback
+				.list_voices()
+				.await
+				.map_err(|fallback| FallbackError::Both { primary, fallback })
+		}
+	}
+
+	/// Lists the languages of the primary synthesiser, or the fallback synthesiser if the primary fails.
+	async fn supported_languages(&self) -> Result<Vec<LanguageInfo>, Self::Error> {
+		match self.primary.supported_languages().await {
+			Ok(languages) => Ok(languages),
+			Err(primary) => self
+				.fallback
+				.supported_languages()
+				.await
+				.map_err(|fallback| FallbackError::Both { primary, fallback })
+		}
+	}
+
+	/// Succeeds if *either* synthesiser is healthy, since either may be used for synthesis.
+	async fn health_check(&self) -> Result<(), Self::Error> {
+		match self.primary.health_check().await {
+			Ok(()) => Ok(()),
+			Err(primary) => self
+				.fallback
+				.health_check()
+				.await
+				.map_err(|fallback| FallbackError::Both { primary, fallback })
+		}
+	}
+}
+
+pin_project! {
+	#[project = FallbackStreamProj]
+	enum FallbackStream<A, B> {
+		Primary { #[pin] inner: A },
+		Fallback { #[pin] inner: B }
+	}
+}
+
+impl<EA, EB, A: Stream<Item = Result<UtteranceEvent, EA>>, B: Stream<Item = Result<UtteranceEvent, EB>>> Stream for FallbackStream<A, B> {
+	type Item = Result<UtteranceEvent, FallbackError<EA, EB>>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		match self.project() {
+			FallbackStreamProj::Primary { inner } => inner.poll_next(cx).map(|item| item.map(|res| res.map_err(FallbackError::Primary))),
+			FallbackStreamProj::Fallback { inner } => inner.poll_next(cx).map(|item| item.map(|res| res.map_err(FallbackError::Fallback)))
+		}
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		match self {
+			FallbackStream::Primary { inner } => inner.size_hint(),
+			FallbackStream::Fallback { inner } => inner.size_hint()
+		}
+	}
+}
@@ -0,0 +1,82 @@
+use core::{
+	pin::Pin,
+	task::{Context, Poll}
+};
+
+use futures_core::Stream;
+
+use crate::{
+	AudioFormat, SpeechSynthesiser, UtteranceConfig, UtteranceEvent,
+	sequential::{SegmentEventStream, SequentialSynthesis, Step}
+};
+
+/// Stream returned by
+/// [`SpeechSynthesiserExt::synthesise_ssml_streamed_chunks`](crate::SpeechSynthesiserExt::synthesise_ssml_streamed_chunks).
+pub(crate) struct FragmentStream<'a, S: SpeechSynthesiser + ?Sized, F> {
+	synthesiser: &'a S,
+	audio_format: &'a AudioFormat,
+	config: &'a UtteranceConfig,
+	fragments: Pin<Box<F>>,
+	fragments_ended: bool,
+	sequence: SequentialSynthesis<'a, S::Error>
+}
+
+impl<'a, S: SpeechSynthesiser + Sync + ?Sized, F: Stream<Item = ssml::Speak<'a>> + Send + 'a> FragmentStream<'a, S, F> {
+	pub(crate) fn new(synthesiser: &'a S, fragments: F, audio_format: &'a AudioFormat, config: &'a UtteranceConfig) -> Self {
+		FragmentStream {
+			synthesiser,
+			audio_format,
+			config,
+			fragments: Box::pin(fragments),
+			fragments_ended: false,
+			sequence: SequentialSynthesis::new(audio_format)
+		}
+	}
+
+	/// Requests synthesis of the next fragment as soon as it arrives, if one has not already been requested.
+	fn poll_start_next(&mut self, cx: &mut Context<'_>) {
+		if self.sequence.has_next() || self.fragments_ended {
+			return;
+		}
+		let fragment = match self.fragments.as_mut().poll_next(cx) {
+			Poll::Ready(Some(fragment)) => fragment,
+			Poll::Ready(None) => {
+				self.fragments_ended = true;
+				return;
+			}
+			Poll::Pending => return
+		};
+		let (synthesiser, audio_format, config) = (self.synthesiser, self.audio_format, self.config);
+		self.sequence.start(async move {
+			let stream = synthesiser.synthesise_ssml_stream(&fragment, audio_format, config).await?;
+			Ok(Box::pin(stream) as SegmentEventStream<'a, S::Error>)
+		});
+	}
+}
+
+impl<'a, S: SpeechSynthesiser + Sync + ?Sized, F: Stream<Item = ssml::Speak<'a>> + Send + 'a> Stream for FragmentStream<'a, S, F> {
+	type Item = Result<UtteranceEvent, S::Error>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let this = self.get_mut();
+		loop {
+			if this.sequence.is_finished() {
+				return Poll::Ready(None);
+			}
+
+			// The next fragment is requested as soon as it arrives, even while the current one is streaming.
+			this.poll_start_next(cx);
+
+			match this.sequence.poll_step(cx) {
+				Poll::Ready(Some(Ok(Step::Event(event)))) => return Poll::Ready(Some(Ok(event))),
+				Poll::Ready(Some(Ok(Step::Started { .. }))) => continue,
+				Poll::Ready(Some(Ok(Step::Idle))) if this.fragments_ended => this.sequence.finish(),
+				// Waiting on the next fragment to arrive; `poll_start_next` has registered the waker.
+				Poll::Ready(Some(Ok(Step::Idle))) => return Poll::Pending,
+				Poll::Ready(Some(Err(e))) => return Poll::Ready(Some(Err(e))),
+				Poll::Ready(None) => return Poll::Ready(None),
+				Poll::Pending => return Poll::Pending
+			}
+		}
+	}
+}
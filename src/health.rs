@@ -0,0 +1,111 @@
+use core::{
+	future::{Future, poll_fn},
+	pin::{Pin, pin},
+	task::Poll,
+	time::Duration
+};
+use std::{sync::Mutex, time::Instant};
+
+use futures_timer::Delay;
+
+use crate::{SpeechSynthesiser, SynthesisError};
+
+/// Configuration for a [`HealthChecker`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct HealthCheckConfig {
+	/// The maximum time to wait for a health check to complete before it is considered failed. Defaults to 10 seconds.
+	pub timeout: Duration,
+	/// The minimum time between health checks. Defaults to 30 seconds.
+	pub min_interval: Duration
+}
+
+impl Default for HealthCheckConfig {
+	fn default() -> Self {
+		HealthCheckConfig {
+			timeout: Duration::from_secs(10),
+			min_interval: Duration::from_secs(30)
+		}
+	}
+}
+
+impl HealthCheckConfig {
+	/// Configures the maximum time to wait for a health check to complete.
+	pub fn with_timeout(mut self, x: Duration) -> Self {
+		self.timeout = x;
+		self
+	}
+
+	/// Configures the minimum time between health checks.
+	pub fn with_min_interval(mut self, x: Duration) -> Self {
+		self.min_interval = x;
+		self
+	}
+}
+
+/// Latency measurements of a single synthesis, as returned by
+/// [`SpeechSynthesiserExt::measure_latency`](crate::SpeechSynthesiserExt::measure_latency). All times are measured
+/// from when synthesis was requested.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SynthesisLatency {
+	/// The time in milliseconds taken for the synthesis stream to open, i.e. to connect to a remote service.
+	pub connection_ms: u64,
+	/// The time in milliseconds until the first [`UtteranceEvent::AudioChunk`](crate::UtteranceEvent::AudioChunk) was
+	/// received. If the stream emitted no audio, this is the same as [`SynthesisLatency::total_ms`].
+	pub time_to_first_chunk_ms: u64,
+	/// The time in milliseconds until the stream completed.
+	pub total_ms: u64
+}
+
+/// Gates calls to [`SpeechSynthesiser::health_check`], i.e. for use by a connection pool or keep-alive task.
+///
+/// After a successful health check, further checks within [`HealthCheckConfig::min_interval`] succeed immediately
+/// without calling the synthesiser. Failed checks are not cached, so the next check will call the synthesiser again.
+#[derive(Debug)]
+pub struct HealthChecker<S> {
+	synthesiser: S,
+	config: HealthCheckConfig,
+	last_success: Mutex<Option<Instant>>
+}
+
+impl<S: SpeechSynthesiser + Sync> HealthChecker<S> {
+	pub fn new(synthesiser: S, config: HealthCheckConfig) -> Self {
+		HealthChecker {
+			synthesiser,
+			config,
+			last_success: Mutex::new(None)
+		}
+	}
+
+	pub fn inner(&self) -> &S {
+		&self.synthesiser
+	}
+
+	pub fn into_inner(self) -> S {
+		self.synthesiser
+	}
+
+	/// Checks the synthesiser's health, unless it was last found healthy within [`HealthCheckConfig::min_interval`].
+	///
+	/// Resolves to [`SynthesisError::Timeout`] if the check does not complete within [`HealthCheckConfig::timeout`].
+	pub async fn check(&self) -> Result<(), SynthesisError<S::Error>> {
+		let last_success = *self.last_success.lock().unwrap_or_else(|e| e.into_inner());
+		if last_success.is_some_and(|at| at.elapsed() < self.config.min_interval) {
+			return Ok(());
+		}
+
+		let mut check = pin!(self.synthesiser.health_check());
+		let mut timeout = Delay::new(self.config.timeout);
+		poll_fn(|cx| {
+			if let Poll::Ready(res) = check.as_mut().poll(cx) {
+				return Poll::Ready(res.map_err(SynthesisError::Synthesiser));
+			}
+			Pin::new(&mut timeout).poll(cx).map(|()| Err(SynthesisError::Timeout))
+		})
+		.await?;
+
+		*self.last_success.lock().unwrap_or_else(|e| e.into_inner()) = Some(Instant::now());
+		Ok(())
+	}
+}
@@ -1,29 +1,253 @@
-use core::future::Future;
+use core::{future::Future, pin::Pin, time::Duration};
 
 pub use ::ssml;
+use futures_core::Stream;
 
 mod audio;
-pub use self::audio::{AudioChannels, AudioCodec, AudioContainer, AudioEncoding, AudioFormat, AudioFormatPreference};
+pub use self::audio::{
+	AudioChannels, AudioCodec, AudioContainer, AudioEncoding, AudioFormat, AudioFormatPreference, Endianness, QualityPreset, TimedTextFormat
+};
+pub mod backends;
+mod batch;
+pub use self::batch::BatchConfig;
+pub mod builder;
+pub use self::builder::UtteranceConfigBuilder;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+mod cancel;
+mod capabilities;
+mod chapters;
+mod chunk;
+mod configured;
+pub use self::{
+	cancel::{Cancellable, CancellableSpeechSynthesiser, CancellationDropGuard, CancellationToken, WaitForCancellation, cancellable},
+	capabilities::SynthesiserCapabilities,
+	chunk::ChunkLimitingSpeechSynthesiser,
+	configured::ConfiguredSpeechSynthesiser
+};
+mod dynamic;
+pub use self::dynamic::{BoxFuture, BoxUtteranceEventStream, DynError, DynSpeechSynthesiser, DynSpeechSynthesiserTrait, into_dyn};
+mod error;
+pub use self::error::{ConfigValidationError, RiffParseError, SynthesisError};
+mod estimate;
+pub use self::estimate::SpeechRateTable;
 mod event;
-pub use self::event::{BasicViseme, BasicVisemeFrame, BlendShape, BlendShapeVisemeFrame, UtteranceEvent, UtteranceEventStream};
+mod fallback;
+mod fragment;
+mod health;
+pub use self::{
+	event::{
+		BasicViseme, BasicVisemeFrame, BlendShape, BlendShapeVisemeFrame, SentenceBoundary, SsmlMarkEvent, UtteranceEvent, UtteranceEventStream, WordBoundary,
+		interpolate_frames, pair_with_durations
+	},
+	fallback::{FallbackError, FallbackSpeechSynthesiser},
+	health::{HealthCheckConfig, HealthChecker, SynthesisLatency}
+};
+mod middleware;
+pub use self::middleware::{MiddlewareSpeechSynthesiser, SpeechSynthesiserMiddleware};
+mod ogg;
+mod padding;
+mod pause;
+#[cfg(feature = "rodio")]
+mod playback;
+mod pool;
+pub use self::{padding::PaddingSpeechSynthesiser, pause::SentencePauseSpeechSynthesiser, pool::SpeechSynthesiserPool};
+#[cfg(feature = "tokio")]
+mod reader;
+mod retry;
+pub use self::retry::{RetryConfig, RetryingSpeechSynthesiser};
+mod select;
+pub use self::select::VoiceSelectingSpeechSynthesiser;
+mod sequential;
+#[cfg(feature = "serde")]
+mod serde_base64;
+#[cfg(feature = "serde")]
+mod serde_event;
+pub mod stream;
+pub use self::stream::UtteranceEventStreamExt;
+mod style;
+pub use self::style::VoiceStyleSpeechSynthesiser;
+pub mod subtitles;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+mod text;
+pub use self::text::SentenceDetectionMode;
+mod text_chunk;
+mod timeout;
+pub use self::{text_chunk::TextChunkingSpeechSynthesiser, timeout::TimedSpeechSynthesiser};
+#[cfg(feature = "tracing")]
+mod trace;
+#[cfg(feature = "tracing")]
+pub use self::trace::TracedSpeechSynthesiser;
+mod validate;
+pub use self::validate::{SsmlValidationError, validate_ssml_structure};
+mod viseme;
+pub use self::viseme::StandardViseme;
+mod voice;
+pub use self::voice::{LanguageInfo, VoiceAge, VoiceGender, VoiceInfo, VoiceRole};
 
 /// Configuration for a single speech synthesis utterance.
+///
+/// # Honouring options
+/// Synthesisers may observe any option natively, but some options can also be applied on top of a synthesiser which
+/// does not. Wrap a synthesiser in the corresponding wrapper to guarantee such an option is honoured:
+///
+/// | Option | Wrapper |
+/// |--------|---------|
+/// | [`inter_sentence_pause`](UtteranceConfig::inter_sentence_pause) | [`SentencePauseSpeechSynthesiser`] |
+/// | [`break_before`](UtteranceConfig::break_before), [`break_after`](UtteranceConfig::break_after) | [`PaddingSpeechSynthesiser`] |
+/// | [`voice_style`](UtteranceConfig::voice_style), [`voice_style_degree`](UtteranceConfig::voice_style_degree), [`voice_role`](UtteranceConfig::voice_role) | [`VoiceStyleSpeechSynthesiser`] (text input only) |
+/// | [`synthesis_timeout`](UtteranceConfig::synthesis_timeout), [`chunk_timeout`](UtteranceConfig::chunk_timeout) | [`TimedSpeechSynthesiser`] |
+/// | [`preferred_voice_gender`](UtteranceConfig::preferred_voice_gender), [`preferred_voice_age`](UtteranceConfig::preferred_voice_age) | [`VoiceSelectingSpeechSynthesiser`] (text input only) |
+/// | [`max_audio_chunk_size`](UtteranceConfig::max_audio_chunk_size) | [`ChunkLimitingSpeechSynthesiser`] |
+/// | [`max_chars_per_request`](UtteranceConfig::max_chars_per_request) | [`TextChunkingSpeechSynthesiser`] |
+/// | [`cancellation_token`](UtteranceConfig::cancellation_token) | [`CancellableSpeechSynthesiser`] |
+///
+/// [`ConfiguredSpeechSynthesiser`] combines the first four wrappers, which need nothing besides the synthesiser.
 #[derive(Debug, Default, Clone)]
 #[non_exhaustive]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default))]
 pub struct UtteranceConfig {
 	/// Whether to emit [`UtteranceEvent::WordBoundary`] events.
 	pub emit_word_boundary_events: bool,
 	/// Whether to emit [`UtteranceEvent::SentenceBoundary`] events.
 	pub emit_sentence_boundary_events: bool,
+	/// Whether to emit [`UtteranceEvent::ParagraphBoundary`] events.
+	pub emit_paragraph_boundary_events: bool,
 	/// Whether to emit [`UtteranceEvent::VisemesChunk`]/[`UtteranceEvent::BlendShapeVisemesChunk`] events.
 	pub emit_visemes: bool,
+	/// Whether to emit [`UtteranceEvent::Phoneme`] events.
+	pub emit_phoneme_events: bool,
+	/// Whether to emit [`UtteranceEvent::SpeakerChange`] events.
+	pub emit_speaker_change_events: bool,
+	/// Whether to emit [`UtteranceEvent::PauseDetected`] events.
+	///
+	/// Synthesisers which do not report pauses themselves should detect them from the audio with
+	/// [`stream::detect_pauses`].
+	pub emit_pause_events: bool,
+	/// The minimum length in milliseconds of silence to report as an [`UtteranceEvent::PauseDetected`]. Defaults to
+	/// [`stream::DEFAULT_SILENCE_THRESHOLD_MILLIS`].
+	pub silence_threshold_millis: Option<f32>,
+	/// Whether to emit [`UtteranceEvent::SsmlMark`] events.
+	///
+	/// When `false`, synthesisers must not emit any [`UtteranceEvent::SsmlMark`] events. They may either strip
+	/// [`ssml::Mark`] elements from the document before synthesis, or discard the resulting events; the two must be
+	/// indistinguishable to the caller.
+	pub emit_ssml_marks: bool,
 	/// The name of the voice to use for synthesis.
 	pub voice: Option<Box<str>>,
+	/// The preferred gender of the voice, used to select a voice when [`UtteranceConfig::voice`] is not set. See
+	/// [`UtteranceConfig::select_voice`].
+	pub preferred_voice_gender: Option<VoiceGender>,
+	/// The preferred apparent age of the voice, used to select a voice when [`UtteranceConfig::voice`] is not set. See
+	/// [`UtteranceConfig::select_voice`].
+	pub preferred_voice_age: Option<VoiceAge>,
+	/// Whether to prefer a [gender-neutral](VoiceGender::Neutral) voice, i.e. for accessibility guidelines which
+	/// require offering one. This overrides [`UtteranceConfig::preferred_voice_gender`].
+	///
+	/// This is a soft preference: if no gender-neutral voice exists for the requested language, the other preferences
+	/// still apply, and otherwise the synthesiser's default voice is used. A male or female voice is never selected
+	/// in its place. See [`UtteranceConfig::select_voice`].
+	pub prefer_gender_neutral: bool,
+	/// Whether to only ever select a [gender-neutral](VoiceGender::Neutral) voice, for stricter compliance needs than
+	/// [`UtteranceConfig::prefer_gender_neutral`]. See [`UtteranceConfig::select_voice`].
+	pub require_gender_neutral: bool,
 	/// The language to use for raw text synthesis.
-	pub language: Option<Box<str>>
+	pub language: Option<Box<str>>,
+	/// Whether the synthesiser should detect the language of each segment of raw text input, i.e. for text mixing
+	/// multiple languages, rather than speaking all of it in [`UtteranceConfig::language`].
+	///
+	/// This is only a hint. Synthesisers which support language detection should report the language of each segment
+	/// with an [`UtteranceEvent::LanguageDetected`] event; synthesisers which do not should ignore this, and use
+	/// [`UtteranceConfig::language`] as usual. To reject the configuration for such synthesisers instead, check it with
+	/// [`UtteranceConfig::validated_for`].
+	pub auto_detect_language: bool,
+	/// The speaking rate, relative to the voice's default rate; `1.0` is normal speed, `0.5` is half speed, and `2.0`
+	/// is double speed.
+	pub speaking_rate: Option<f32>,
+	/// The pitch shift in semitones, relative to the voice's default pitch.
+	pub pitch_shift_semitones: Option<f32>,
+	/// The volume in decibels, relative to the voice's default volume. Typically in the range `-20.0` to `+6.0`.
+	pub volume_db: Option<f32>,
+	/// How raw text input is split into sentences, i.e. to insert [pauses](UtteranceConfig::inter_sentence_pause)
+	/// between them. Defaults to [`SentenceDetectionMode::Unicode`].
+	pub sentence_detection: SentenceDetectionMode,
+	/// The length of the pause to insert between sentences of raw text input.
+	pub inter_sentence_pause: Option<Duration>,
+	/// The length of silence to insert before the first word, i.e. for headroom when editing the audio.
+	pub break_before: Option<Duration>,
+	/// The length of silence to insert after the last word.
+	pub break_after: Option<Duration>,
+	/// The speaking style of the voice, i.e. `cheerful` or `sad`, for voices which support multiple styles.
+	pub voice_style: Option<Box<str>>,
+	/// The intensity of the [voice style](UtteranceConfig::voice_style), from `0.01` to `2.0`; `1.0` is the default
+	/// intensity, and `2.0` is double intensity.
+	pub voice_style_degree: Option<f32>,
+	/// The character role the voice should play, for voices which support roles (see [`VoiceInfo::supported_roles`]).
+	/// Synthesisers which do not support roles ignore it.
+	pub voice_role: Option<VoiceRole>,
+	/// Pronunciation lexicons to use for synthesis, as URIs of [PLS](https://www.w3.org/TR/pronunciation-lexicon/)
+	/// documents. Synthesisers which support inline lexicons may also accept a PLS document itself.
+	///
+	/// For SSML synthesis, synthesisers should insert a `<lexicon>` element for each lexicon at the top of the
+	/// document; see [`UtteranceConfig::insert_pronunciation_lexicons`]. For text synthesis, lexicons should be passed
+	/// to the backend by other means if it supports them. Synthesisers which do not support lexicons should ignore
+	/// them.
+	///
+	/// The number of lexicons supported varies by backend; for instance, Amazon Polly supports at most 5 lexicons per
+	/// request.
+	pub pronunciation_lexicons: Vec<Box<str>>,
+	/// The approximate maximum size in bytes of each [`UtteranceEvent::AudioChunk`], i.e. to begin playback sooner in
+	/// low-latency applications.
+	///
+	/// This is only a hint.
+	pub max_audio_chunk_size: Option<usize>,
+	/// The maximum number of characters of raw text to send to the synthesiser in a single request, for backends which
+	/// limit the length of their input (i.e. Amazon Polly accepts at most 3,000 characters).
+	pub max_chars_per_request: Option<usize>,
+	/// Whether [`SpeechSynthesiserExt::synthesise_ssml_document`] should request synthesis of the next chapter while
+	/// the current chapter is still streaming, to avoid a gap between chapters.
+	pub prefetch_chapters: bool,
+	/// A token which can be used to cancel synthesis.
+	#[cfg_attr(feature = "serde", serde(skip))]
+	pub cancellation_token: Option<CancellationToken>,
+	/// The maximum time to wait for synthesis to begin, i.e. for the `synthesise_*_stream` future to resolve.
+	pub synthesis_timeout: Option<Duration>,
+	/// The maximum time to wait between items of a synthesis stream.
+	pub chunk_timeout: Option<Duration>,
+	/// The ID of the distributed trace this synthesis is part of, i.e. one shared by every operation performed on a
+	/// user's behalf across services. Unlike [`UtteranceConfig::request_id`], it need not be unique to this synthesis,
+	/// and is not sent to the backend or echoed in events. Wrap a synthesiser in a `TracedSpeechSynthesiser` (with the
+	/// `tracing` feature) to attach it to the synthesis span.
+	pub trace_id: Option<Box<str>>,
+	/// An identifier unique to this synthesis request, i.e. to correlate requests with their responses in multi-tenant
+	/// services.
+	///
+	/// Synthesisers should send this to their backend (i.e. as a request header) where supported, and echo it back in
+	/// [`UtteranceEvent::SynthesisStarted`]; see [`stream::with_synthesis_events_for_request`]. Wrap a synthesiser in a
+	/// `TracedSpeechSynthesiser` (with the `tracing` feature) to attach it to the synthesis span.
+	///
+	/// With the `uuid` feature, a random UUID v4 is generated for requests that don't configure one; see
+	/// [`UtteranceConfig::request_id_or_generate`].
+	pub request_id: Option<Box<str>>,
+	/// A URL overriding the synthesiser's default API endpoint for this request, i.e. to route requests through a proxy
+	/// or private endpoint, or to send some requests to a canary deployment.
+	///
+	/// This is only meaningful to synthesisers which contact a remote HTTP API; local synthesisers ignore it.
+	pub custom_endpoint: Option<Box<str>>
 }
 
 impl UtteranceConfig {
+	/// The lowest [volume](UtteranceConfig::volume_db) accepted by [`UtteranceConfig::validate`], i.e. the noise floor
+	/// of 16-bit audio; any quieter and the audio would be inaudible.
+	pub const MIN_VOLUME_DB: f32 = -96.0;
+
+	/// Creates an [`UtteranceConfigBuilder`], which checks the configuration for some invalid combinations of options
+	/// at compile time.
+	pub fn builder() -> UtteranceConfigBuilder {
+		UtteranceConfigBuilder::new()
+	}
+
 	/// Configures whether to emit [`UtteranceEvent::WordBoundary`] events.
 	pub fn with_emit_word_boundary_events(mut self, x: bool) -> Self {
 		self.emit_word_boundary_events = x;
@@ -36,12 +260,74 @@ impl UtteranceConfig {
 		self
 	}
 
+	/// Configures whether to emit [`UtteranceEvent::ParagraphBoundary`] events.
+	pub fn with_emit_paragraph_boundary_events(mut self, x: bool) -> Self {
+		self.emit_paragraph_boundary_events = x;
+		self
+	}
+
 	/// Configures whether to emit [`UtteranceEvent::VisemesChunk`]/[`UtteranceEvent::BlendShapeVisemesChunk`] events.
 	pub fn with_emit_visemes(mut self, x: bool) -> Self {
 		self.emit_visemes = x;
 		self
 	}
 
+	/// Configures whether to emit [`UtteranceEvent::Phoneme`] events.
+	pub fn with_emit_phoneme_events(mut self, x: bool) -> Self {
+		self.emit_phoneme_events = x;
+		self
+	}
+
+	/// Configures whether to emit [`UtteranceEvent::SpeakerChange`] events.
+	pub fn with_emit_speaker_change_events(mut self, x: bool) -> Self {
+		self.emit_speaker_change_events = x;
+		self
+	}
+
+	/// Configures whether to emit [`UtteranceEvent::PauseDetected`] events.
+	pub fn with_emit_pause_events(mut self, x: bool) -> Self {
+		self.emit_pause_events = x;
+		self
+	}
+
+	/// Configures the minimum length in milliseconds of silence to report as an [`UtteranceEvent::PauseDetected`].
+	pub fn with_silence_threshold_millis(mut self, x: f32) -> Self {
+		self.silence_threshold_millis = Some(x);
+		self
+	}
+
+	/// Configures whether to emit [`UtteranceEvent::SsmlMark`] events.
+	pub fn with_emit_ssml_marks(mut self, x: bool) -> Self {
+		self.emit_ssml_marks = x;
+		self
+	}
+
+	/// Enables every kind of event, i.e. to observe the full event stream while debugging. Events added in future
+	/// versions will also be enabled.
+	///
+	/// Note that synthesisers may reject events they do not support; see [`UtteranceConfig::validated_for`].
+	pub fn with_emit_all_events(mut self) -> Self {
+		self.set_emit_all_events(true);
+		self
+	}
+
+	/// Disables every kind of event, so only [`UtteranceEvent::AudioChunk`]s are emitted. This is the default.
+	pub fn with_emit_no_events(mut self) -> Self {
+		self.set_emit_all_events(false);
+		self
+	}
+
+	fn set_emit_all_events(&mut self, x: bool) {
+		self.emit_word_boundary_events = x;
+		self.emit_sentence_boundary_events = x;
+		self.emit_paragraph_boundary_events = x;
+		self.emit_visemes = x;
+		self.emit_phoneme_events = x;
+		self.emit_speaker_change_events = x;
+		self.emit_pause_events = x;
+		self.emit_ssml_marks = x;
+	}
+
 	/// Configures the name of the voice to use for synthesis.
 	///
 	/// This is generally only used for [text synthesis](SpeechSynthesiser::synthesise_text_stream) and will be ignored
@@ -51,6 +337,39 @@ impl UtteranceConfig {
 		self
 	}
 
+	/// Configures the preferred gender of the voice. See [`UtteranceConfig::preferred_voice_gender`].
+	pub fn with_preferred_voice_gender(mut self, x: VoiceGender) -> Self {
+		self.preferred_voice_gender = Some(x);
+		self
+	}
+
+	/// Configures the preferred apparent age of the voice. See [`UtteranceConfig::preferred_voice_age`].
+	pub fn with_preferred_voice_age(mut self, x: VoiceAge) -> Self {
+		self.preferred_voice_age = Some(x);
+		self
+	}
+
+	/// Configures whether to prefer a gender-neutral voice. See [`UtteranceConfig::prefer_gender_neutral`].
+	pub fn with_voice_gender_neutral(mut self, x: bool) -> Self {
+		self.prefer_gender_neutral = x;
+		self
+	}
+
+	/// Configures whether to only select a gender-neutral voice. See [`UtteranceConfig::require_gender_neutral`].
+	pub fn with_require_gender_neutral(mut self, x: bool) -> Self {
+		self.require_gender_neutral = x;
+		self
+	}
+
+	/// Returns the preferred voice gender, taking [`UtteranceConfig::prefer_gender_neutral`] &
+	/// [`UtteranceConfig::require_gender_neutral`] into account.
+	pub(crate) fn preferred_gender(&self) -> Option<VoiceGender> {
+		match self.prefer_gender_neutral || self.require_gender_neutral {
+			true => Some(VoiceGender::Neutral),
+			false => self.preferred_voice_gender
+		}
+	}
+
 	/// Configures the language to use for raw text synthesis.
 	///
 	/// This is generally only used for [text synthesis](SpeechSynthesiser::synthesise_text_stream) and will be ignored
@@ -59,6 +378,327 @@ impl UtteranceConfig {
 		self.language = Some(x.into());
 		self
 	}
+
+	/// Configures whether the synthesiser should detect the language of each segment of raw text input. See
+	/// [`UtteranceConfig::auto_detect_language`].
+	pub fn with_auto_detect_language(mut self, x: bool) -> Self {
+		self.auto_detect_language = x;
+		self
+	}
+
+	/// Configures the speaking rate, relative to the voice's default rate; `1.0` is normal speed, `0.5` is half speed,
+	/// and `2.0` is double speed.
+	///
+	/// For [SSML synthesis](SpeechSynthesiser::synthesise_ssml_stream), this is only a hint; [`ssml::Prosody`]
+	/// elements in the document take precedence.
+	pub fn with_speaking_rate(mut self, x: f32) -> Self {
+		self.speaking_rate = Some(x);
+		self
+	}
+
+	/// Configures the pitch shift in semitones, relative to the voice's default pitch.
+	///
+	/// For [SSML synthesis](SpeechSynthesiser::synthesise_ssml_stream), this is only a hint; [`ssml::Prosody`]
+	/// elements in the document take precedence.
+	pub fn with_pitch_shift_semitones(mut self, x: f32) -> Self {
+		self.pitch_shift_semitones = Some(x);
+		self
+	}
+
+	/// Configures the volume in decibels, relative to the voice's default volume. Typically in the range `-20.0` to
+	/// `+6.0`.
+	///
+	/// For [SSML synthesis](SpeechSynthesiser::synthesise_ssml_stream), this is only a hint; [`ssml::Prosody`]
+	/// elements in the document take precedence.
+	pub fn with_volume_db(mut self, x: f32) -> Self {
+		self.volume_db = Some(x);
+		self
+	}
+
+	/// Configures how raw text input is split into sentences. See [`SentenceDetectionMode`].
+	///
+	/// This is only used for [text synthesis](SpeechSynthesiser::synthesise_text_stream).
+	pub fn with_sentence_detection(mut self, mode: SentenceDetectionMode) -> Self {
+		self.sentence_detection = mode;
+		self
+	}
+
+	/// Configures the length of the pause to insert between sentences of raw text input.
+	///
+	/// This is only used for [text synthesis](SpeechSynthesiser::synthesise_text_stream) and will be ignored with
+	/// [SSML synthesis](SpeechSynthesiser::synthesise_ssml_stream), where pauses can be inserted explicitly with
+	/// [`ssml::Break`] elements. See [`SentencePauseSpeechSynthesiser`].
+	pub fn with_inter_sentence_pause(mut self, x: Duration) -> Self {
+		self.inter_sentence_pause = Some(x);
+		self
+	}
+
+	/// Configures the length of silence to insert before the first word. See [`PaddingSpeechSynthesiser`].
+	pub fn with_break_before(mut self, x: Duration) -> Self {
+		self.break_before = Some(x);
+		self
+	}
+
+	/// Configures the length of silence to insert after the last word. See [`PaddingSpeechSynthesiser`].
+	pub fn with_break_after(mut self, x: Duration) -> Self {
+		self.break_after = Some(x);
+		self
+	}
+
+	/// Configures the speaking style of the voice, i.e. `cheerful` or `sad`, for voices which support multiple styles.
+	/// See [`VoiceInfo::list_styles`] for a voice's supported styles.
+	///
+	/// This is only used for [text synthesis](SpeechSynthesiser::synthesise_text_stream) and will be ignored with
+	/// [SSML synthesis](SpeechSynthesiser::synthesise_ssml_stream), where styles can be specified with
+	/// [`ssml::mstts::Express`] elements. See [`VoiceStyleSpeechSynthesiser`].
+	pub fn with_voice_style(mut self, x: impl Into<Box<str>>) -> Self {
+		self.voice_style = Some(x.into());
+		self
+	}
+
+	/// Configures the intensity of the [voice style](UtteranceConfig::with_voice_style), from `0.01` to `2.0`; `1.0`
+	/// is the default intensity, and `2.0` is double intensity.
+	pub fn with_voice_style_degree(mut self, x: f32) -> Self {
+		self.voice_style_degree = Some(x);
+		self
+	}
+
+	/// Configures the character role the voice should play, i.e. [`VoiceRole::Girl`]. See
+	/// [`UtteranceConfig::voice_role`].
+	pub fn with_role(mut self, x: VoiceRole) -> Self {
+		self.voice_role = Some(x);
+		self
+	}
+
+	/// Adds a pronunciation lexicon to use for synthesis, as the URI of a PLS document. Lexicons are applied in the
+	/// order they are added.
+	///
+	/// See [`UtteranceConfig::pronunciation_lexicons`].
+	pub fn with_pronunciation_lexicon(mut self, x: impl Into<Box<str>>) -> Self {
+		self.pronunciation_lexicons.push(x.into());
+		self
+	}
+
+	/// Configures the approximate maximum size in bytes of each [`UtteranceEvent::AudioChunk`].
+	///
+	/// See [`ChunkLimitingSpeechSynthesiser`] & [`stream::limit_audio_chunk_size`].
+	pub fn with_max_audio_chunk_size(mut self, x: usize) -> Self {
+		self.max_audio_chunk_size = Some(x);
+		self
+	}
+
+	/// Configures the maximum number of characters of raw text to send to the synthesiser in a single request.
+	///
+	/// See [`TextChunkingSpeechSynthesiser`].
+	pub fn with_max_chars_per_request(mut self, x: usize) -> Self {
+		self.max_chars_per_request = Some(x);
+		self
+	}
+
+	/// Configures whether to request the next chapter while the current chapter is streaming. See
+	/// [`SpeechSynthesiserExt::synthesise_ssml_document`].
+	pub fn with_prefetch_chapters(mut self, x: bool) -> Self {
+		self.prefetch_chapters = x;
+		self
+	}
+
+	/// Configures a token which can be used to cancel synthesis.
+	///
+	/// See [`CancellableSpeechSynthesiser`] & [`cancellable`].
+	pub fn with_cancellation_token(mut self, x: CancellationToken) -> Self {
+		self.cancellation_token = Some(x);
+		self
+	}
+
+	/// Configures the maximum time to wait for synthesis to begin. See [`TimedSpeechSynthesiser`].
+	pub fn with_timeout(mut self, x: Duration) -> Self {
+		self.synthesis_timeout = Some(x);
+		self
+	}
+
+	/// Configures the maximum time to wait between items of a synthesis stream. See [`TimedSpeechSynthesiser`].
+	pub fn with_chunk_timeout(mut self, x: Duration) -> Self {
+		self.chunk_timeout = Some(x);
+		self
+	}
+
+	/// Configures an identifier used to correlate this synthesis with the request which triggered it. See
+	/// [`UtteranceConfig::trace_id`].
+	pub fn with_trace_id(mut self, x: impl Into<Box<str>>) -> Self {
+		self.trace_id = Some(x.into());
+		self
+	}
+
+	/// Configures an identifier for this synthesis request. See [`UtteranceConfig::request_id`].
+	pub fn with_request_id(mut self, x: impl Into<Box<str>>) -> Self {
+		self.request_id = Some(x.into());
+		self
+	}
+
+	/// Returns the configured [request ID](UtteranceConfig::request_id), or, with the `uuid` feature, a newly generated
+	/// UUID v4 if none is configured.
+	///
+	/// Each call generates a new ID, so synthesisers should call this once per request and use the result both for the
+	/// backend request and for [`UtteranceEvent::SynthesisStarted`].
+	pub fn request_id_or_generate(&self) -> Option<Box<str>> {
+		#[cfg(feature = "uuid")]
+		if self.request_id.is_none() {
+			return Some(uuid::Uuid::new_v4().to_string().into_boxed_str());
+		}
+		self.request_id.clone()
+	}
+
+	/// Configures a URL overriding the synthesiser's default API endpoint for this request. See
+	/// [`UtteranceConfig::custom_endpoint`].
+	pub fn with_custom_endpoint(mut self, url: impl Into<Box<str>>) -> Self {
+		self.custom_endpoint = Some(url.into());
+		self
+	}
+
+	/// Selects the voice best matching this configuration from a synthesiser's voices, as listed by
+	/// [`SpeechSynthesiser::list_voices`].
+	///
+	/// If [`UtteranceConfig::voice`] is set, it takes absolute priority, and the voice with that name (if any) is
+	/// returned. Otherwise, voices are filtered by [`UtteranceConfig::language`] (see [`VoiceInfo::matches_locale`]),
+	/// and the first voice matching both [`UtteranceConfig::preferred_voice_gender`] and
+	/// [`UtteranceConfig::preferred_voice_age`] is returned. If no voice matches both, a voice matching the preferred
+	/// gender is preferred over one matching the preferred age. Returns `None` if no voice matches any preference.
+	///
+	/// If [`UtteranceConfig::prefer_gender_neutral`] is set, the preferred gender is [`VoiceGender::Neutral`], and
+	/// voices of any other known gender are not considered. If [`UtteranceConfig::require_gender_neutral`] is set, only
+	/// gender-neutral voices are considered at all, including when selecting a [`UtteranceConfig::voice`] by name.
+	pub fn select_voice<'v>(&self, voices: &'v [VoiceInfo]) -> Option<&'v VoiceInfo> {
+		let preferred_gender = self.preferred_gender();
+		let allowed = |voice: &&VoiceInfo| match voice.gender {
+			_ if self.require_gender_neutral => voice.gender == Some(VoiceGender::Neutral),
+			Some(VoiceGender::Male | VoiceGender::Female) => !self.prefer_gender_neutral,
+			_ => true
+		};
+		if let Some(name) = self.voice.as_deref() {
+			return voices.iter().filter(allowed).find(|voice| voice.name.eq_ignore_ascii_case(name));
+		}
+		if preferred_gender.is_none() && self.preferred_voice_age.is_none() {
+			return None;
+		}
+		voices
+			.iter()
+			.filter(|voice| self.language.as_deref().map_or(true, |language| voice.matches_locale(language)))
+			.filter(allowed)
+			.map(|voice| {
+				let gender = preferred_gender.is_some() && voice.gender == preferred_gender;
+				let age = self.preferred_voice_age.is_some() && voice.age == self.preferred_voice_age;
+				(voice, gender as u8 * 2 + age as u8)
+			})
+			.filter(|(_, score)| *score > 0)
+			// `max_by_key` returns the last maximum; reverse so earlier voices win ties.
+			.rev()
+			.max_by_key(|(_, score)| *score)
+			.map(|(voice, _)| voice)
+	}
+
+	/// Inserts a `<lexicon>` element for each configured [pronunciation
+	/// lexicon](UtteranceConfig::pronunciation_lexicons) URI at the top of an SSML document. Inline PLS documents are
+	/// skipped, since they cannot be referenced from SSML.
+	pub fn insert_pronunciation_lexicons(&self, document: &mut ssml::Speak<'_>) {
+		let lexicons = self
+			.pronunciation_lexicons
+			.iter()
+			.filter(|lexicon| !lexicon.trim_start().starts_with('<'))
+			.enumerate()
+			.map(|(i, uri)| {
+				ssml::Element::Custom(
+					ssml::CustomElement::new("lexicon")
+						.with_attr("xml:id", format!("lexicon{i}"))
+						.with_attr("uri", uri.to_string())
+				)
+			});
+		document.children_mut().splice(0..0, lexicons);
+	}
+
+	/// Checks that all configured values are within their valid ranges, returning every invalid value rather than just
+	/// the first, i.e. so that all problems can be reported to the user at once.
+	///
+	/// Synthesisers should call this before synthesis and return an error if the configuration is invalid, rather than
+	/// passing invalid values on to their backend; wrap a synthesiser in a [`ConfiguredSpeechSynthesiser`] to guarantee
+	/// this, failing with [`SynthesisError::InvalidConfig`]. Synthesisers with additional constraints can append their
+	/// own [`ConfigValidationError::Custom`] errors to the result.
+	pub fn validate(&self) -> Result<(), Vec<ConfigValidationError>> {
+		let mut errors = Vec::new();
+		if let Some(x) = self.speaking_rate {
+			if !(x.is_finite() && x > 0.0) {
+				errors.push(ConfigValidationError::SpeakingRateOutOfRange(x));
+			}
+		}
+		if let Some(x) = self.pitch_shift_semitones {
+			if !x.is_finite() {
+				errors.push(ConfigValidationError::PitchShiftOutOfRange(x));
+			}
+		}
+		if let Some(x) = self.volume_db {
+			if !x.is_finite() {
+				errors.push(ConfigValidationError::VolumeOutOfRange(x));
+			} else if x < Self::MIN_VOLUME_DB {
+				errors.push(ConfigValidationError::VolumeTooLow(x));
+			}
+		}
+		if let Some(x) = self.voice_style_degree {
+			if !(0.01..=2.0).contains(&x) {
+				errors.push(ConfigValidationError::SpeakingStyleDegreeOutOfRange(x));
+			}
+		}
+		if let Some(x) = self.silence_threshold_millis {
+			if !(x.is_finite() && x >= 0.0) {
+				errors.push(ConfigValidationError::SilenceThresholdOutOfRange(x));
+			}
+		}
+		if let SentenceDetectionMode::Regex(pattern) = &self.sentence_detection {
+			if !self.sentence_detection.is_valid() {
+				errors.push(ConfigValidationError::InvalidSentencePattern(pattern.clone()));
+			}
+		}
+		if let Some(url) = &self.custom_endpoint {
+			if !is_valid_endpoint(url) {
+				errors.push(ConfigValidationError::InvalidEndpoint(url.clone()));
+			}
+		}
+		if errors.is_empty() { Ok(()) } else { Err(errors) }
+	}
+
+	/// Checks that no events or features are requested which are not supported by a synthesiser with the given
+	/// capabilities, returning an [`ConfigValidationError::Unsupported`] for each unsupported feature.
+	///
+	/// Visemes are considered supported if the synthesiser supports either basic or blend shape visemes.
+	pub fn validated_for(&self, capabilities: &SynthesiserCapabilities) -> Result<(), Vec<ConfigValidationError>> {
+		let unsupported = [
+			(self.emit_word_boundary_events && !capabilities.supports_word_boundary, "word boundary events"),
+			(self.emit_sentence_boundary_events && !capabilities.supports_sentence_boundary, "sentence boundary events"),
+			(self.emit_paragraph_boundary_events && !capabilities.supports_paragraph_boundary, "paragraph boundary events"),
+			(self.emit_visemes && !(capabilities.supports_visemes || capabilities.supports_blend_shape_visemes), "visemes"),
+			(self.emit_phoneme_events && !capabilities.supports_phoneme_events, "phoneme events"),
+			(self.emit_speaker_change_events && !capabilities.supports_speaker_change, "speaker change events"),
+			(self.emit_pause_events && !capabilities.supports_pause_events, "pause events"),
+			(self.emit_ssml_marks && !capabilities.supports_ssml_marks, "SSML mark events"),
+			(self.auto_detect_language && !capabilities.supports_language_detection, "language detection")
+		];
+		let errors: Vec<_> = unsupported
+			.into_iter()
+			.filter(|(unsupported, _)| *unsupported)
+			.map(|(_, feature)| ConfigValidationError::Unsupported(feature))
+			.collect();
+		if errors.is_empty() { Ok(()) } else { Err(errors) }
+	}
+}
+
+/// Returns `false` for clearly malformed endpoint URLs, i.e. those without a scheme or host, or containing whitespace.
+fn is_valid_endpoint(url: &str) -> bool {
+	let Some((scheme, rest)) = url.split_once("://") else {
+		return false;
+	};
+	let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+	scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+		&& scheme.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+		&& !host.is_empty()
+		&& !url.chars().any(|c| c.is_whitespace() || c.is_control())
 }
 
 /// Common trait for a speech synthesiser.
@@ -70,10 +710,16 @@ pub trait SpeechSynthesiser {
 	/// - Any requested sample rate is not supported.
 	/// - Any requested container is not supported.
 	/// - Any requested channel count is not supported.
+	/// - No supported format meets the preference's hard requirements, such as
+	///   [`AudioFormatPreference::require_sample_rate`]. Requirements must be checked before any other preference; see
+	///   [`AudioFormatPreference::meets_requirements`]. This includes the container
+	///   [blacklist](AudioFormatPreference::with_blacklist) & [whitelist](AudioFormatPreference::with_whitelist).
+	/// - The preference's blacklist & whitelist [conflict](AudioFormatPreference::has_conflicting_filters).
 	///
 	/// If multiple values are provided for a preference by the application, the synthesiser should prioritise the
-	/// highest quality configuration. For optional properties (such as bitrate), this should **not** fail, and instead
-	/// return the highest quality bitrate closest to the user's preference.
+	/// highest quality configuration, as determined by [`AudioFormat::quality_score`]. For optional properties (such as
+	/// bitrate), this should **not** fail, and instead return the highest quality bitrate closest to the user's
+	/// preference.
 	///
 	/// i.e., for a synthesiser that only supports 44100 Hz, stereo MP3 at either 128 or 192 Kbps:
 	/// - requesting a sample rate of `48000` or `22050` should return `None`,
@@ -81,8 +727,86 @@ pub trait SpeechSynthesiser {
 	/// - requesting OGG format should return `None`,
 	/// - and requesting 44100 Hz stereo MP3 at 160 Kbps should return an audio format of 44100 Hz stereo MP3 **at 192
 	///   Kbps**.
+	///
+	/// Similarly, for a synthesiser that only supports 48000 Hz mono FLAC:
+	/// - requesting [`AudioContainer::Flac`] with a sample rate of `48000` and [`AudioChannels::Mono`] (or no
+	///   preference for sample rate/channels) should return an audio format of 48000 Hz mono FLAC,
+	/// - requesting [`AudioContainer::Flac`] with [`AudioChannels::Stereo`] should return `None`,
+	/// - and requesting *either* [`AudioContainer::Flac`] or [`AudioContainer::Mp3`] should return FLAC, since it is
+	///   the only supported container of the two. Note that bitrate preferences do not apply to lossless containers.
+	///
+	/// Synthesisers should never return a codec & container combination which is not valid, such as
+	/// `AudioContainer::Mp4(AudioCodec::Vorbis)`, even if it is requested. For a synthesiser that supports AAC in
+	/// either ADTS framing or an MP4 container:
+	/// - requesting [`AudioContainer::Mp4`] with [`AudioCodec::Opus`] should return `None`,
+	/// - requesting *either* `AudioContainer::Mp4(AudioCodec::Opus)` or `AudioContainer::Mp4(AudioCodec::Aac)` should
+	///   return AAC in MP4,
+	/// - and requesting [`AudioContainer::Aac`] should return AAC with ADTS framing, *not* MP4, since the two are not
+	///   interchangeable.
+	///
+	/// [`AudioContainer::Caf`] & [`AudioContainer::Aiff`] are specific to Apple platforms, and most server-side
+	/// synthesisers will not support them; applications requesting CAF or AIFF should typically also accept
+	/// [`AudioContainer::Riff`] as a fallback.
+	///
+	/// Synthesisers which can enumerate their supported formats can implement this with
+	/// [`AudioFormatPreference::select_best`].
 	fn negotiate_audio_format(&self, pref: &AudioFormatPreference) -> Option<AudioFormat>;
 
+	/// Returns the features supported by this synthesiser.
+	///
+	/// The default implementation reports every feature as supported; synthesisers should override this to accurately
+	/// describe their capabilities. See [`UtteranceConfig::validated_for`].
+	fn capabilities(&self) -> SynthesiserCapabilities {
+		SynthesiserCapabilities::default()
+	}
+
+	/// Checks an [`ssml`] document for problems before it is sent for synthesis, returning every problem found.
+	///
+	/// The default implementation performs the generic SSML 1.1 checks of [`validate_ssml_structure`]. Synthesisers
+	/// should override this to also reject elements or values they do not support, i.e. unsupported voice styles.
+	fn validate_ssml(&self, input: &ssml::Speak<'_>) -> Result<(), Vec<SsmlValidationError>> {
+		validate_ssml_structure(input)
+	}
+
+	/// Roughly estimates how long `text` will take to speak with the given configuration, i.e. for timeline layout or
+	/// progress bars, without synthesising it. Returns `None` if no estimate can be made.
+	///
+	/// The default implementation uses the average speaking rate of [`UtteranceConfig::language`] from the default
+	/// [`SpeechRateTable`], adjusted by [`UtteranceConfig::speaking_rate`]; synthesisers should override this with a
+	/// backend-specific estimate if they can provide a better one. Estimates are only accurate to within roughly ±30%;
+	/// the actual timing of synthesised speech is reported by [`UtteranceEvent::SentenceBoundary`] events.
+	fn estimate_duration(&self, text: &str, config: &UtteranceConfig) -> Option<Duration> {
+		let estimate = SpeechRateTable::default().estimate(text, config.language.as_deref());
+		match config.speaking_rate {
+			Some(rate) if rate.is_finite() && rate > 0.0 => Some(estimate.div_f32(rate)),
+			_ => Some(estimate)
+		}
+	}
+
+	/// Returns the sample rates (in Hz) this synthesiser can produce, i.e. to populate a format picker without
+	/// probing [`SpeechSynthesiser::negotiate_audio_format`] with every rate, which may require a network round-trip
+	/// for each.
+	///
+	/// The default implementation returns the rates most commonly supported by TTS backends (8, 16, 22.05, 24, 44.1,
+	/// and 48 kHz); synthesisers should override this with the rates they actually support.
+	fn supported_sample_rates(&self) -> &[u32] {
+		&[8000, 16000, 22050, 24000, 44100, 48000]
+	}
+
+	/// Returns the containers (and their encodings or codecs) this synthesiser can produce. See
+	/// [`SpeechSynthesiser::supported_sample_rates`].
+	///
+	/// The default implementation returns the containers most commonly supported by TTS backends: 16-bit PCM (raw &
+	/// in RIFF), MP3, and Opus in OGG. Synthesisers should override this with the containers they actually support.
+	fn supported_containers(&self) -> &[AudioContainer] {
+		&[
+			AudioContainer::Raw(AudioEncoding::PcmI16Le),
+			AudioContainer::Riff(AudioEncoding::PcmI16Le),
+			AudioContainer::Mp3,
+			AudioContainer::Ogg(AudioCodec::Opus)
+		]
+	}
+
 	/// Stream the synthesis of an [`ssml`] document.
 	///
 	/// Audio will be streamed in chunks, in the format specified by the given [`AudioFormat`]. You can negotiate an
@@ -116,4 +840,632 @@ pub trait SpeechSynthesiser {
 		audio_format: &AudioFormat,
 		config: &UtteranceConfig
 	) -> impl Future<Output = Result<impl UtteranceEventStream<Self::Error> + 'static, Self::Error>> + Send;
+
+	/// Synthesise an [`ssml`] document, collecting all audio into a single buffer.
+	///
+	/// This uses the default [`UtteranceConfig`], under which no events besides audio are emitted. See
+	/// [`SpeechSynthesiser::synthesise_ssml_to_bytes_with_config`] to use a custom configuration.
+	fn synthesise_ssml_to_bytes(&self, input: &ssml::Speak<'_>, audio_format: &AudioFormat) -> impl Future<Output = Result<Vec<u8>, Self::Error>> + Send
+	where
+		Self: Sync
+	{
+		async move {
+			let config = UtteranceConfig::default();
+			self.synthesise_ssml_to_bytes_with_config(input, audio_format, &config).await
+		}
+	}
+
+	/// Synthesise an [`ssml`] document with the given [`UtteranceConfig`], collecting all audio into a single buffer.
+	///
+	/// Any events besides [`UtteranceEvent::AudioChunk`] are discarded.
+	fn synthesise_ssml_to_bytes_with_config(
+		&self,
+		input: &ssml::Speak<'_>,
+		audio_format: &AudioFormat,
+		config: &UtteranceConfig
+	) -> impl Future<Output = Result<Vec<u8>, Self::Error>> + Send {
+		let stream = self.synthesise_ssml_stream(input, audio_format, config);
+		async move { stream.await?.collect_audio().await }
+	}
+
+	/// Synthesise **raw text**, collecting all audio into a single buffer.
+	///
+	/// This uses the default [`UtteranceConfig`], under which no events besides audio are emitted. See
+	/// [`SpeechSynthesiser::synthesise_text_to_bytes_with_config`] to use a custom configuration.
+	fn synthesise_text_to_bytes(&self, input: &str, audio_format: &AudioFormat) -> impl Future<Output = Result<Vec<u8>, Self::Error>> + Send
+	where
+		Self: Sync
+	{
+		async move {
+			let config = UtteranceConfig::default();
+			self.synthesise_text_to_bytes_with_config(input, audio_format, &config).await
+		}
+	}
+
+	/// Synthesise **raw text** with the given [`UtteranceConfig`], collecting all audio into a single buffer.
+	///
+	/// Any events besides [`UtteranceEvent::AudioChunk`] are discarded.
+	fn synthesise_text_to_bytes_with_config(
+		&self,
+		input: &str,
+		audio_format: &AudioFormat,
+		config: &UtteranceConfig
+	) -> impl Future<Output = Result<Vec<u8>, Self::Error>> + Send {
+		let stream = self.synthesise_text_stream(input, audio_format, config);
+		async move { stream.await?.collect_audio().await }
+	}
+
+	/// List the voices supported by this synthesiser.
+	///
+	/// The [`VoiceInfo::name`] of each returned voice can be passed to [`UtteranceConfig::with_voice`].
+	///
+	/// The default implementation returns an empty list; synthesisers which can enumerate their voices should override
+	/// this method.
+	fn list_voices(&self) -> impl Future<Output = Result<Vec<VoiceInfo>, Self::Error>> + Send {
+		async { Ok(Vec::new()) }
+	}
+
+	/// List the languages supported by this synthesiser, i.e. to populate a locale picker.
+	///
+	/// The default implementation returns an empty list; synthesisers which can enumerate their languages should
+	/// override this method.
+	fn supported_languages(&self) -> impl Future<Output = Result<Vec<LanguageInfo>, Self::Error>> + Send {
+		async { Ok(Vec::new()) }
+	}
+
+	/// Checks that the synthesiser is able to synthesise speech, i.e. that its connection to a remote service has not
+	/// expired. This is intended to be called periodically by a connection pool or keep-alive task; see
+	/// [`HealthChecker`] to limit how often the check is performed.
+	///
+	/// The default implementation synthesises a single space in the lowest quality format the synthesiser supports, and
+	/// discards the result. Synthesisers should override this with a cheaper request if their backend supports one.
+	fn health_check(&self) -> impl Future<Output = Result<(), Self::Error>> + Send
+	where
+		Self: Sync
+	{
+		let format = self
+			.negotiate_audio_format(&AudioFormatPreference::default().with_prefer_quality(QualityPreset::Low))
+			.or_else(|| self.negotiate_audio_format(&AudioFormatPreference::default()));
+		async move {
+			// A synthesiser which supports no formats can't synthesise anything; there's nothing to check.
+			let Some(format) = format else {
+				return Ok(());
+			};
+			let config = UtteranceConfig::default();
+			self.synthesise_text_stream(" ", &format, &config).await?.collect_audio().await?;
+			Ok(())
+		}
+	}
+}
+
+/// Extension methods for [`SpeechSynthesiser`]s.
+pub trait SpeechSynthesiserExt: SpeechSynthesiser {
+	/// Find the first voice (as listed by [`SpeechSynthesiser::list_voices`]) matching the given BCP-47 locale.
+	///
+	/// See [`VoiceInfo::matches_locale`] for how locales are matched. An exact match is preferred over a match by
+	/// language only.
+	fn find_voice_by_locale(&self, locale: &str) -> impl Future<Output = Result<Option<VoiceInfo>, Self::Error>> + Send {
+		let voices = self.list_voices();
+		async move {
+			let mut voices = voices.await?;
+			let index = voices
+				.iter()
+				.position(|voice| voice.locale.eq_ignore_ascii_case(locale))
+				.or_else(|| voices.iter().position(|voice| voice.matches_locale(locale)));
+			Ok(index.map(|index| voices.swap_remove(index)))
+		}
+	}
+
+	/// Find the highest quality voice (as listed by [`SpeechSynthesiser::list_voices`]) for the given BCP-47 locale.
+	///
+	/// Neural voices are preferred over standard voices, and an exact match is preferred over a match by language
+	/// only; see [`VoiceInfo::matches_locale`]. If the synthesiser reports its
+	/// [supported languages](SpeechSynthesiser::supported_languages) and none match `locale`, no voice is returned.
+	fn best_voice_for_locale(&self, locale: &str) -> impl Future<Output = Result<Option<VoiceInfo>, Self::Error>> + Send {
+		let languages = self.supported_languages();
+		let voices = self.list_voices();
+		async move {
+			let languages = languages.await?;
+			if !languages.is_empty() && !languages.iter().any(|language| language.matches_locale(locale)) {
+				return Ok(None);
+			}
+			let voices = voices.await?;
+			// Reversed so that the first of equally good voices is chosen.
+			Ok(voices
+				.into_iter()
+				.rev()
+				.filter(|voice| voice.matches_locale(locale))
+				.max_by_key(|voice| (voice.neural, voice.locale.eq_ignore_ascii_case(locale))))
+		}
+	}
+
+	/// Synthesise raw text with a `<mark>` inserted at each of the given `(mark_name, char_offset)` positions, i.e. to
+	/// synchronise audio with slideshow transitions or UI highlights. Offsets are counted in characters (not bytes)
+	/// from the beginning of `text`.
+	///
+	/// The text is split into sentences (each wrapped in an `<s>` element), and the resulting document is synthesised
+	/// via [`SpeechSynthesiser::synthesise_ssml_stream`]; the text-only options of `config` (such as
+	/// [`UtteranceConfig::voice`]) are applied to the document. [`UtteranceConfig::emit_ssml_marks`] must be enabled
+	/// to receive the resulting [`UtteranceEvent::SsmlMark`] events.
+	fn synthesise_with_marks<'a>(
+		&'a self,
+		text: &'a str,
+		marks: &'a [(&'a str, usize)],
+		audio_format: &'a AudioFormat,
+		config: &'a UtteranceConfig
+	) -> impl Future<Output = Result<impl UtteranceEventStream<Self::Error> + 'static, Self::Error>> + Send + 'a
+	where
+		Self: Sync
+	{
+		async move {
+			let document = text::marked_text_to_ssml(text, marks, config);
+			let stream = self.synthesise_ssml_stream(&document, audio_format, config).await?;
+			Ok(Box::pin(stream) as Pin<Box<dyn UtteranceEventStream<Self::Error>>>)
+		}
+	}
+
+	/// Synthesise a document made up of multiple [`ssml`] documents (i.e. the chapters of an audiobook) into a single
+	/// contiguous stream.
+	///
+	/// Each chapter is synthesised in order with a separate call to [`SpeechSynthesiser::synthesise_ssml_stream`].
+	/// An [`UtteranceEvent::ChapterBoundary`] is emitted before the events of each chapter (including the first), and
+	/// if the byte rate of `audio_format` is known (see [`AudioFormat::bytes_per_second`]), event timestamps are
+	/// adjusted to be relative to the beginning of the combined stream.
+	///
+	/// By default, each chapter is only requested once the previous chapter's stream has ended. With
+	/// [`UtteranceConfig::prefetch_chapters`], the next chapter is requested as soon as the current chapter begins
+	/// streaming, though its events are still only yielded once the current chapter ends.
+	///
+	/// With [`AudioContainer::Riff`], only the first chapter's header is kept, with its lengths replaced by a
+	/// placeholder since the length of the combined audio isn't known in advance. Other containers with headers (e.g.
+	/// [`AudioContainer::Aiff`]) will include a header in each chapter's audio.
+	fn synthesise_ssml_document<'a>(
+		&'a self,
+		chapters: Vec<ssml::Speak<'a>>,
+		audio_format: &'a AudioFormat,
+		config: &'a UtteranceConfig
+	) -> impl UtteranceEventStream<Self::Error> + 'a
+	where
+		Self: Sync
+	{
+		stream::with_synthesis_events_for_request(
+			chapters::ChapterStream::new(self, chapters, audio_format, config),
+			std::time::Instant::now(),
+			config.request_id_or_generate()
+		)
+	}
+
+	/// Synthesise a stream of [`ssml`] fragments (i.e. the sentences of a response as they are generated by a language
+	/// model) into a single contiguous stream, beginning synthesis of each fragment as soon as it arrives.
+	///
+	/// Each fragment is synthesised with a separate call to [`SpeechSynthesiser::synthesise_ssml_stream`], and their
+	/// events are yielded in series, as if the concatenation of all fragments were synthesised in a single call: each
+	/// fragment's [`UtteranceEvent::SynthesisStarted`] & [`UtteranceEvent::SynthesisCompleted`] events are replaced by
+	/// a single pair bracketing the combined stream, and if the byte rate of `audio_format` is known (see
+	/// [`AudioFormat::bytes_per_second`]), event timestamps are adjusted to be relative to the beginning of the
+	/// combined stream.
+	///
+	/// The next fragment is requested as soon as it arrives, even while the current fragment is still streaming, though
+	/// its events are only yielded once the current fragment ends.
+	///
+	/// With [`AudioContainer::Riff`], only the first fragment's header is kept, with its lengths replaced by a
+	/// placeholder since the length of the combined audio isn't known in advance. Other containers with headers (e.g.
+	/// [`AudioContainer::Aiff`]) will include a header in each fragment's audio.
+	fn synthesise_ssml_streamed_chunks<'a>(
+		&'a self,
+		fragments: impl Stream<Item = ssml::Speak<'a>> + Send + 'a,
+		audio_format: &'a AudioFormat,
+		config: &'a UtteranceConfig
+	) -> impl UtteranceEventStream<Self::Error> + 'a
+	where
+		Self: Sync
+	{
+		stream::with_synthesis_events_for_request(
+			fragment::FragmentStream::new(self, fragments, audio_format, config),
+			std::time::Instant::now(),
+			config.request_id_or_generate()
+		)
+	}
+
+	/// Synthesise an [`ssml`] document as OGG Opus audio, yielding the audio one page at a time, i.e. for streaming to
+	/// an HTTP client with chunked transfer encoding (with a `Content-Type` of `audio/ogg; codecs=opus`). Since each
+	/// OGG page is self-contained, a client can begin decoding as soon as it receives the first item.
+	///
+	/// The audio format is negotiated with [`SpeechSynthesiser::negotiate_audio_format`], requiring
+	/// [`AudioContainer::Ogg`]`(`[`AudioCodec::Opus`]`)`. Events other than audio are discarded; use
+	/// [`SpeechSynthesiser::synthesise_ssml_stream`] directly to receive them.
+	///
+	/// By default, each item is exactly one page. With [`UtteranceConfig::max_audio_chunk_size`], consecutive pages are
+	/// combined into items of up to that many bytes, though an item always contains at least one whole page. Pages are
+	/// reassembled regardless of how the synthesiser chunks its audio. If the audio ends partway through a page, the
+	/// incomplete page is yielded as the final item.
+	#[allow(clippy::type_complexity)]
+	fn synthesise_to_ogg_page_stream(
+		&self,
+		input: &ssml::Speak<'_>,
+		config: &UtteranceConfig
+	) -> impl Future<Output = Result<impl Stream<Item = Result<Box<[u8]>, Self::Error>> + Send + 'static, Self::Error>> + Send
+	where
+		Self: Sync
+	{
+		let container = AudioContainer::Ogg(AudioCodec::Opus);
+		// If the synthesiser cannot produce OGG Opus, let it report its own error; Opus always decodes at 48 kHz.
+		let audio_format = self
+			.negotiate_audio_format(&AudioFormatPreference::default().require_container(container))
+			.unwrap_or(AudioFormat::new(48000, AudioChannels::Mono, None, container));
+		async move {
+			let stream = self.synthesise_ssml_stream(input, &audio_format, config).await?;
+			let stream = Box::pin(stream) as Pin<Box<dyn UtteranceEventStream<Self::Error>>>;
+			Ok(ogg::OggPageStream::new(stream, config.max_audio_chunk_size))
+		}
+	}
+
+	/// Measure the latency of this synthesiser by synthesising `warmup_text` with the default configuration, i.e. for
+	/// monitoring a provider's latency over time. The audio is discarded.
+	///
+	/// Each call performs a single synthesis; to compute percentiles, call this repeatedly and aggregate the results.
+	fn measure_latency(&self, warmup_text: &str, format: &AudioFormat) -> impl Future<Output = Result<SynthesisLatency, Self::Error>> + Send
+	where
+		Self: Sync
+	{
+		async move {
+			let config = UtteranceConfig::default();
+			let requested_at = std::time::Instant::now();
+			let stream = self.synthesise_text_stream(warmup_text, format, &config).await?;
+			let connection = requested_at.elapsed();
+			let mut stream = core::pin::pin!(stream);
+			let mut first_chunk = None;
+			while let Some(event) = stream::next(stream.as_mut()).await {
+				if event?.is_audio() && first_chunk.is_none() {
+					first_chunk = Some(requested_at.elapsed());
+				}
+			}
+			let total = requested_at.elapsed();
+			Ok(SynthesisLatency {
+				connection_ms: connection.as_millis() as u64,
+				time_to_first_chunk_ms: first_chunk.unwrap_or(total).as_millis() as u64,
+				total_ms: total.as_millis() as u64
+			})
+		}
+	}
+
+	/// Synthesise a batch of independent text chunks (i.e. all of an application's UI strings) concurrently, with up to
+	/// [`BatchConfig::max_concurrent`] requests in flight at once via [`SpeechSynthesiser::synthesise_text_stream`].
+	///
+	/// Each item is the index of a chunk in `chunks` paired with its stream (or the error requesting it), in the order
+	/// synthesis began rather than the order of `chunks`. A failed request does not affect the others; the stream ends
+	/// once every chunk has been yielded. Requests only progress while the returned stream is polled, so it should be
+	/// polled alongside the chunk streams it yields.
+	fn synthesise_text_chunks<'a>(
+		&'a self,
+		chunks: Vec<String>,
+		audio_format: &'a AudioFormat,
+		config: &'a UtteranceConfig,
+		batch: &BatchConfig
+	) -> impl Stream<Item = (usize, Result<impl UtteranceEventStream<Self::Error> + 'static, Self::Error>)> + Send + 'a
+	where
+		Self: Sync
+	{
+		batch::BatchStream::new(self, chunks, audio_format, config, batch)
+	}
+
+	/// Synthesise an [`ssml`] document, writing the audio directly to an [`AsyncWrite`](tokio::io::AsyncWrite)
+	/// destination (i.e. a file or HTTP response body) as it is streamed. Returns the total number of bytes written.
+	///
+	/// All events besides [`UtteranceEvent::AudioChunk`]s are discarded. The writer is flushed once synthesis
+	/// completes. See [`SpeechSynthesiser::synthesise_ssml_stream`].
+	#[cfg(feature = "tokio")]
+	fn synthesise_ssml_to_writer<W: tokio::io::AsyncWrite + Unpin + Send>(
+		&self,
+		input: &ssml::Speak<'_>,
+		audio_format: &AudioFormat,
+		config: &UtteranceConfig,
+		writer: W
+	) -> impl Future<Output = Result<u64, SynthesisError<Self::Error>>> + Send {
+		let fut = self.synthesise_ssml_stream(input, audio_format, config);
+		async move { stream::write_audio(fut.await.map_err(SynthesisError::Synthesiser)?, writer).await }
+	}
+
+	/// Synthesise raw text, writing the audio directly to an [`AsyncWrite`](tokio::io::AsyncWrite) destination as it
+	/// is streamed. Returns the total number of bytes written.
+	///
+	/// See [`SpeechSynthesiserExt::synthesise_ssml_to_writer`] & [`SpeechSynthesiser::synthesise_text_stream`].
+	#[cfg(feature = "tokio")]
+	fn synthesise_text_to_writer<W: tokio::io::AsyncWrite + Unpin + Send>(
+		&self,
+		input: &str,
+		audio_format: &AudioFormat,
+		config: &UtteranceConfig,
+		writer: W
+	) -> impl Future<Output = Result<u64, SynthesisError<Self::Error>>> + Send {
+		let fut = self.synthesise_text_stream(input, audio_format, config);
+		async move { stream::write_audio(fut.await.map_err(SynthesisError::Synthesiser)?, writer).await }
+	}
+
+	/// Stream text from an [`AsyncRead`](tokio::io::AsyncRead) source into synthesised speech, without buffering the
+	/// entire input in memory.
+	///
+	/// Input is split into sentences according to [`UtteranceConfig::sentence_detection`], and each sentence is
+	/// synthesised with a separate call to [`SpeechSynthesiser::synthesise_text_stream`] as soon as it has been read.
+	/// The events of each sentence are yielded in order; if the byte rate of `audio_format` is known (see
+	/// [`AudioFormat::bytes_per_second`]), event timestamps are adjusted to be relative to the beginning of the
+	/// combined stream.
+	///
+	/// Text which runs on for more than 4 KiB without a sentence boundary is split at whitespace, so that input
+	/// without sentence boundaries can still be streamed.
+	///
+	/// The input must be valid UTF-8; otherwise, the stream yields a [`SynthesisError::Io`] error.
+	///
+	/// With [`AudioContainer::Riff`], only the first sentence's header is kept, with its lengths replaced by a
+	/// placeholder since the length of the combined audio isn't known in advance. Other containers with headers (e.g.
+	/// [`AudioContainer::Aiff`]) will include a header in each sentence's audio.
+	#[cfg(feature = "tokio")]
+	fn synthesise_text_reader_stream<'a, R: tokio::io::AsyncRead + Unpin + Send + 'a>(
+		&'a self,
+		input: R,
+		audio_format: &'a AudioFormat,
+		config: &'a UtteranceConfig
+	) -> impl UtteranceEventStream<SynthesisError<Self::Error>> + 'a
+	where
+		Self: Sync
+	{
+		stream::with_synthesis_events_for_request(
+			reader::TextReaderStream::new(self, input, audio_format, config),
+			std::time::Instant::now(),
+			config.request_id_or_generate()
+		)
+	}
+
+	/// Play the audio of an [`UtteranceEventStream`] on a [`rodio::Sink`] as it is streamed, i.e. for immediate
+	/// playback on desktop.
+	///
+	/// `audio_format` must be the format the stream was synthesised in, which must be 16-bit PCM in either
+	/// [`AudioContainer::Raw`] or [`AudioContainer::Riff`]; other formats fail with
+	/// [`SynthesisError::UnsupportedFormat`] before the stream is polled. The text & start time of each
+	/// [`UtteranceEvent::WordBoundary`] are passed to `on_word` (if provided) as they are received, i.e. for
+	/// karaoke-style highlighting; since audio is queued ahead of playback, the callback should schedule its highlight
+	/// relative to when playback began, rather than act immediately. Pass `None::<fn(f32, &str)>` to ignore word
+	/// boundaries.
+	///
+	/// The returned future completes once all audio has been queued on the sink, which may be well before it has
+	/// finished playing; use [`rodio::Sink::sleep_until_end`] to wait for playback to finish.
+	///
+	/// Requires the `rodio` feature. Note that rodio always depends on `cpal` for audio output, which on Linux requires
+	/// the ALSA development files (i.e. the `libasound2-dev` package on Debian & Ubuntu) to build.
+	#[cfg(feature = "rodio")]
+	fn pipe_to_rodio<'a, F: FnMut(f32, &str) + Send + 'a>(
+		&'a self,
+		stream: impl UtteranceEventStream<Self::Error> + 'a,
+		audio_format: &'a AudioFormat,
+		sink: &'a rodio::Sink,
+		on_word: Option<F>
+	) -> impl Future<Output = Result<(), SynthesisError<Self::Error>>> + Send + 'a
+	where
+		Self: Sync
+	{
+		playback::pipe_to_rodio(stream, audio_format, sink, on_word)
+	}
+}
+
+impl<T: SpeechSynthesiser + ?Sized> SpeechSynthesiserExt for T {}
+
+#[cfg(test)]
+mod tests {
+	use crate::{ConfigValidationError, SynthesiserCapabilities, UtteranceConfig};
+
+	#[test]
+	fn validate_reports_every_invalid_value() {
+		let config = UtteranceConfig::default()
+			.with_voice_style_degree(2.5)
+			.with_volume_db(-120.0)
+			.with_speaking_rate(0.0);
+		assert_eq!(
+			config.validate(),
+			Err(vec![
+				ConfigValidationError::SpeakingRateOutOfRange(0.0),
+				ConfigValidationError::VolumeTooLow(-120.0),
+				ConfigValidationError::SpeakingStyleDegreeOutOfRange(2.5)
+			])
+		);
+		assert_eq!(
+			UtteranceConfig::default()
+				.with_volume_db(f32::NAN)
+				.validate()
+				.map_err(|errors| errors.len()),
+			Err(1)
+		);
+		assert_eq!(UtteranceConfig::default().with_volume_db(-20.0).validate(), Ok(()));
+	}
+
+	#[test]
+	fn validated_for_reports_every_unsupported_feature() {
+		let capabilities = SynthesiserCapabilities::default()
+			.with_supports_word_boundary(false)
+			.with_supports_pause_events(false)
+			.with_supports_ssml_marks(false)
+			.with_supports_language_detection(false);
+		let config = UtteranceConfig::default().with_emit_all_events().with_auto_detect_language(true);
+		assert_eq!(
+			config.validated_for(&capabilities),
+			Err(vec![
+				ConfigValidationError::Unsupported("word boundary events"),
+				ConfigValidationError::Unsupported("pause events"),
+				ConfigValidationError::Unsupported("SSML mark events"),
+				ConfigValidationError::Unsupported("language detection")
+			])
+		);
+		assert_eq!(config.validated_for(&SynthesiserCapabilities::default()), Ok(()));
+	}
+
+	#[test]
+	fn validated_for_ignores_unrequested_features() {
+		let capabilities = SynthesiserCapabilities::default()
+			.with_supports_pause_events(false)
+			.with_supports_ssml_marks(false)
+			.with_supports_language_detection(false);
+		assert_eq!(UtteranceConfig::default().validated_for(&capabilities), Ok(()));
+		assert_eq!(
+			UtteranceConfig::default()
+				.with_emit_word_boundary_events(true)
+				.validated_for(&capabilities),
+			Ok(())
+		);
+	}
+
+	#[test]
+	fn visemes_supported_by_either_kind() {
+		let config = UtteranceConfig::default().with_emit_visemes(true);
+		let basic_only = SynthesiserCapabilities::default().with_supports_blend_shape_visemes(false);
+		assert_eq!(config.validated_for(&basic_only), Ok(()));
+		let neither = basic_only.with_supports_visemes(false);
+		assert_eq!(config.validated_for(&neither), Err(vec![ConfigValidationError::Unsupported("visemes")]));
+	}
+
+	#[test]
+	#[cfg(feature = "uuid")]
+	fn generates_request_id_if_unset() {
+		let config = UtteranceConfig::default();
+		let first = config.request_id_or_generate().unwrap();
+		assert_eq!(uuid::Uuid::parse_str(&first).unwrap().get_version_num(), 4);
+		assert_ne!(config.request_id_or_generate().unwrap(), first);
+
+		let config = config.with_request_id("request");
+		assert_eq!(config.request_id_or_generate().as_deref(), Some("request"));
+	}
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod serde_tests {
+	use core::{fmt::Debug, time::Duration};
+	use std::time::Instant;
+
+	use serde::{Serialize, de::DeserializeOwned};
+
+	use crate::{
+		AudioChannels, AudioCodec, AudioContainer, AudioEncoding, AudioFormat, AudioFormatPreference, BasicViseme, BasicVisemeFrame, BlendShape,
+		BlendShapeVisemeFrame, UtteranceConfig, UtteranceEvent, VoiceGender
+	};
+
+	/// Asserts that `value` survives a roundtrip through JSON & MessagePack (in both its named & compact forms), by
+	/// comparing the serialized forms before & after deserialization.
+	fn assert_roundtrip<T: Serialize + DeserializeOwned + Debug>(value: &T) {
+		let json = serde_json::to_string(value).unwrap();
+		let decoded: T = serde_json::from_str(&json).unwrap_or_else(|e| panic!("failed to decode {json}: {e}"));
+		assert_eq!(serde_json::to_string(&decoded).unwrap(), json);
+
+		for encode in [rmp_serde::to_vec_named::<T>, rmp_serde::to_vec::<T>] {
+			let msgpack = encode(value).unwrap();
+			let decoded: T = rmp_serde::from_slice(&msgpack).unwrap_or_else(|e| panic!("failed to decode {value:?} from MessagePack: {e}"));
+			assert_eq!(encode(&decoded).unwrap(), msgpack);
+		}
+	}
+
+	fn frame() -> BlendShapeVisemeFrame {
+		BlendShapeVisemeFrame {
+			blendshapes: vec![BlendShape { key: "jawOpen".into(), weight: 0.5 }].into_boxed_slice(),
+			frame_offset: 16.6
+		}
+	}
+
+	#[test]
+	fn audio_types_roundtrip() {
+		let format = AudioFormat::new(48000, AudioChannels::Stereo, Some(128), AudioContainer::Ogg(AudioCodec::Opus));
+		assert_roundtrip(&format);
+		assert_roundtrip(&AudioFormat::new(16000, AudioChannels::Mono, None, AudioContainer::Riff(AudioEncoding::PcmI16Le)));
+		assert_roundtrip(&AudioContainer::Mp4(AudioCodec::Aac));
+		assert_roundtrip(&AudioEncoding::MuLaw);
+		assert_roundtrip(&AudioCodec::Vorbis);
+		assert_roundtrip(&AudioChannels::Stereo);
+		assert_roundtrip(
+			&AudioFormatPreference::from_audio_format(&format)
+				.require_sample_rate(48000)
+				.with_blacklist(vec![AudioContainer::Raw(AudioEncoding::ALaw)])
+		);
+	}
+
+	#[test]
+	fn viseme_types_roundtrip() {
+		assert_roundtrip(&BlendShape {
+			key: "mouthSmile".into(),
+			weight: 1.0
+		});
+		assert_roundtrip(&frame());
+		assert_roundtrip(&BasicViseme('a'));
+		assert_roundtrip(&BasicVisemeFrame {
+			viseme: BasicViseme('o'),
+			frame_offset: 120.0
+		});
+	}
+
+	#[test]
+	fn config_roundtrips() {
+		let config = UtteranceConfig::default()
+			.with_emit_all_events()
+			.with_voice("en-US-JennyNeural")
+			.with_preferred_voice_gender(VoiceGender::Female)
+			.with_speaking_rate(1.25)
+			.with_inter_sentence_pause(Duration::from_millis(300))
+			.with_pronunciation_lexicon("https://example.com/lexicon.pls")
+			.with_request_id("request");
+		assert_roundtrip(&config);
+		assert_roundtrip(&UtteranceConfig::default());
+	}
+
+	#[test]
+	fn events_roundtrip() {
+		let events = [
+			UtteranceEvent::SynthesisStarted {
+				requested_at: Instant::now(),
+				request_id: Some("request".into())
+			},
+			UtteranceEvent::WordBoundary {
+				from_millis: 0.0,
+				to_millis: 250.0,
+				text: "hello".into(),
+				confidence: Some(0.9),
+				phonetic: None
+			},
+			UtteranceEvent::AudioFormat(AudioFormat::new(24000, AudioChannels::Mono, None, AudioContainer::Raw(AudioEncoding::PcmI16Le))),
+			UtteranceEvent::BlendShapeVisemesChunk(vec![frame()].into_boxed_slice()),
+			UtteranceEvent::VisemesChunk(
+				vec![BasicVisemeFrame {
+					viseme: BasicViseme('e'),
+					frame_offset: 5.0
+				}]
+				.into_boxed_slice()
+			),
+			UtteranceEvent::AudioChunk(vec![0, 1, 2, 255].into_boxed_slice()),
+			UtteranceEvent::Unknown
+		];
+		for event in &events {
+			assert_roundtrip(event);
+		}
+	}
+
+	#[test]
+	fn audio_chunks_are_base64_in_json() {
+		let json = serde_json::to_string(&UtteranceEvent::AudioChunk(vec![0, 1, 2, 255].into_boxed_slice())).unwrap();
+		assert_eq!(json, r#"{"AudioChunk":"AAEC/w=="}"#);
+	}
+
+	#[test]
+	fn unknown_events_are_deserialized_as_unknown() {
+		let events: Vec<UtteranceEvent> =
+			serde_json::from_str(r#"[{"FutureEvent":{"at_millis":1.0,"extra":[1,2,3]}},{"AudioChunk":"AAEC/w=="},{"OtherEvent":[1,2]}]"#).unwrap();
+		assert!(matches!(events[..], [UtteranceEvent::Unknown, UtteranceEvent::AudioChunk(ref audio), UtteranceEvent::Unknown] if audio[..] == [0, 1, 2, 255]));
+
+		#[derive(Serialize)]
+		enum FutureEvent {
+			FutureEvent { at_millis: f32 }
+		}
+		for encode in [rmp_serde::to_vec_named::<FutureEvent>, rmp_serde::to_vec::<FutureEvent>] {
+			let msgpack = encode(&FutureEvent::FutureEvent { at_millis: 1.0 }).unwrap();
+			assert!(matches!(rmp_serde::from_slice(&msgpack), Ok(UtteranceEvent::Unknown)));
+		}
+	}
+
+	#[test]
+	fn malformed_known_events_are_errors() {
+		assert!(serde_json::from_str::<UtteranceEvent>(r#"{"WordBoundary":{"from_millis":"soon"}}"#).is_err());
+	}
 }
@@ -0,0 +1,198 @@
+use core::{future::Future, pin::Pin, time::Duration};
+
+use crate::{
+	AudioContainer, AudioFormat, AudioFormatPreference, LanguageInfo, SpeechSynthesiser, SsmlValidationError, SynthesiserCapabilities, UtteranceConfig,
+	UtteranceEventStream, VoiceInfo
+};
+
+/// Hooks for intercepting the requests & responses of a synthesiser, i.e. for refreshing authentication, logging
+/// requests, or translating events. Apply a middleware to a synthesiser with [`MiddlewareSpeechSynthesiser`].
+///
+/// Both hooks default to passing the request or response through unchanged. Middlewares are stacked by wrapping a
+/// `MiddlewareSpeechSynthesiser` in another; the outermost middleware's `on_before_synthesise` is called first, and its
+/// `on_after_synthesise` last.
+///
+/// # Example
+/// A middleware authenticating requests with an expiring access token, here passed to the backend as a query parameter
+/// of the [custom endpoint](UtteranceConfig::custom_endpoint). The token is injected into each request in
+/// `on_before_synthesise`, and `on_after_synthesise` wraps the stream with an adapter which re-authenticates as soon
+/// as the token is rejected mid-stream. The failed stream still yields its error, since the audio already received
+/// can't be requested again, but the next request is made with the new token.
+///
+/// ```
+/// # use core::{pin::Pin, task::{Context, Poll}};
+/// # use std::sync::{Arc, Mutex};
+/// # use futures_core::Stream;
+/// # use speech_synthesis::{
+/// #     MiddlewareSpeechSynthesiser, SpeechSynthesiser, SpeechSynthesiserMiddleware, UtteranceConfig, UtteranceEvent, UtteranceEventStream
+/// # };
+/// #[derive(Debug)]
+/// enum ApiError {
+///     Unauthorized,
+///     Other(String)
+/// }
+///
+/// /// Exchanges the service's long-lived credentials for a new access token.
+/// fn authenticate() -> Result<String, ApiError> {
+///     // ...
+///     # Ok("token".to_string())
+/// }
+///
+/// #[derive(Debug, Clone, Default)]
+/// struct AuthRefreshMiddleware {
+///     token: Arc<Mutex<Option<String>>>
+/// }
+///
+/// impl AuthRefreshMiddleware {
+///     fn reauthenticate(&self) -> Result<String, ApiError> {
+///         let token = authenticate();
+///         *self.token.lock().unwrap() = token.as_ref().ok().cloned();
+///         token
+///     }
+/// }
+///
+/// impl SpeechSynthesiserMiddleware<ApiError> for AuthRefreshMiddleware {
+///     fn on_before_synthesise(&self, config: &mut UtteranceConfig) -> Result<(), ApiError> {
+///         let cached = self.token.lock().unwrap().clone();
+///         let token = match cached {
+///             Some(token) => token,
+///             None => self.reauthenticate()?
+///         };
+///         config.custom_endpoint = Some(format!("https://tts.example.com/v1/synthesize?access_token={token}").into());
+///         Ok(())
+///     }
+///
+///     fn on_after_synthesise(&self, stream: Pin<Box<dyn UtteranceEventStream<ApiError>>>) -> Pin<Box<dyn UtteranceEventStream<ApiError>>> {
+///         Box::pin(ReauthenticateOnError { inner: stream, middleware: self.clone() })
+///     }
+/// }
+///
+/// struct ReauthenticateOnError {
+///     inner: Pin<Box<dyn UtteranceEventStream<ApiError>>>,
+///     middleware: AuthRefreshMiddleware
+/// }
+///
+/// impl Stream for ReauthenticateOnError {
+///     type Item = Result<UtteranceEvent, ApiError>;
+///
+///     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+///         let item = self.inner.as_mut().poll_next(cx);
+///         if let Poll::Ready(Some(Err(ApiError::Unauthorized))) = item {
+///             // If this fails too, the token is cleared, so the next request tries again in `on_before_synthesise`.
+///             let _ = self.middleware.reauthenticate();
+///         }
+///         item
+///     }
+/// }
+///
+/// fn authenticated<S: SpeechSynthesiser<Error = ApiError>>(synthesiser: S) -> MiddlewareSpeechSynthesiser<S, AuthRefreshMiddleware> {
+///     MiddlewareSpeechSynthesiser::new(synthesiser, AuthRefreshMiddleware::default())
+/// }
+/// # let middleware = AuthRefreshMiddleware::default();
+/// # let mut config = UtteranceConfig::default();
+/// # middleware.on_before_synthesise(&mut config).unwrap();
+/// # assert_eq!(config.custom_endpoint.as_deref(), Some("https://tts.example.com/v1/synthesize?access_token=token"));
+/// ```
+pub trait SpeechSynthesiserMiddleware<E> {
+	/// Called before each synthesis request with a copy of the request's configuration, which may be modified.
+	/// Returning an error fails the request without contacting the synthesiser.
+	fn on_before_synthesise(&self, config: &mut UtteranceConfig) -> Result<(), E> {
+		let _ = config;
+		Ok(())
+	}
+
+	/// Called with the stream of each successful synthesis request, returning the stream to be yielded in its place.
+	fn on_after_synthesise(&self, stream: Pin<Box<dyn UtteranceEventStream<E>>>) -> Pin<Box<dyn UtteranceEventStream<E>>> {
+		stream
+	}
+}
+
+/// Wraps a [`SpeechSynthesiser`] with a [`SpeechSynthesiserMiddleware`], which is called for every
+/// `synthesise_*_stream` request.
+#[derive(Debug, Clone)]
+pub struct MiddlewareSpeechSynthesiser<S, M> {
+	synthesiser: S,
+	middleware: M
+}
+
+impl<S: SpeechSynthesiser, M: SpeechSynthesiserMiddleware<S::Error>> MiddlewareSpeechSynthesiser<S, M> {
+	pub fn new(synthesiser: S, middleware: M) -> Self {
+		MiddlewareSpeechSynthesiser { synthesiser, middleware }
+	}
+
+	pub fn inner(&self) -> &S {
+		&self.synthesiser
+	}
+
+	pub fn middleware(&self) -> &M {
+		&self.middleware
+	}
+
+	pub fn into_inner(self) -> S {
+		self.synthesiser
+	}
+}
+
+impl<S: SpeechSynthesiser + Sync, M: SpeechSynthesiserMiddleware<S::Error> + Sync> SpeechSynthesiser for MiddlewareSpeechSynthesiser<S, M> {
+	type Error = S::Error;
+
+	fn negotiate_audio_format(&self, pref: &AudioFormatPreference) -> Option<AudioFormat> {
+		self.synthesiser.negotiate_audio_format(pref)
+	}
+
+	fn capabilities(&self) -> SynthesiserCapabilities {
+		self.synthesiser.capabilities()
+	}
+
+	fn validate_ssml(&self, input: &ssml::Speak<'_>) -> Result<(), Vec<SsmlValidationError>> {
+		self.synthesiser.validate_ssml(input)
+	}
+
+	fn estimate_duration(&self, text: &str, config: &UtteranceConfig) -> Option<Duration> {
+		self.synthesiser.estimate_duration(text, config)
+	}
+
+	fn supported_sample_rates(&self) -> &[u32] {
+		self.synthesiser.supported_sample_rates()
+	}
+
+	fn supported_containers(&self) -> &[AudioContainer] {
+		self.synthesiser.supported_containers()
+	}
+
+	async fn synthesise_ssml_stream(
+		&self,
+		input: &ssml::Speak<'_>,
+		audio_format: &AudioFormat,
+		config: &UtteranceConfig
+	) -> Result<impl UtteranceEventStream<Self::Error> + 'static, Self::Error> {
+		let mut config = config.clone();
+		self.middleware.on_before_synthesise(&mut config)?;
+		let stream = Box::pin(self.synthesiser.synthesise_ssml_stream(input, audio_format, &config).await?);
+		Ok(self.middleware.on_after_synthesise(stream))
+	}
+
+	async fn synthesise_text_stream(
+		&self,
+		input: &str,
+		audio_format: &AudioFormat,
+		config: &UtteranceConfig
+	) -> Result<impl UtteranceEventStream<Self::Error> + 'static, Self::Error> {
+		let mut config = config.clone();
+		self.middleware.on_before_synthesise(&mut config)?;
+		let stream = Box::pin(self.synthesiser.synthesise_text_stream(input, audio_format, &config).await?);
+		Ok(self.middleware.on_after_synthesise(stream))
+	}
+
+	fn list_voices(&self) -> impl Future<Output = Result<Vec<VoiceInfo>, Self::Error>> + Send {
+		self.synthesiser.list_voices()
+	}
+
+	fn supported_languages(&self) -> impl Future<Output = Result<Vec<LanguageInfo>, Self::Error>> + Send {
+		self.synthesiser.supported_languages()
+	}
+
+	fn health_check(&self) -> impl Future<Output = Result<(), Self::Error>> + Send {
+		self.synthesiser.health_check()
+	}
+}
@@ -0,0 +1,115 @@
+use core::{
+	pin::Pin,
+	task::{Context, Poll}
+};
+use std::collections::VecDeque;
+
+use futures_core::Stream;
+use pin_project_lite::pin_project;
+
+use crate::UtteranceEvent;
+
+const CAPTURE_PATTERN: &[u8; 4] = b"OggS";
+/// The length of a page header, excluding its segment table.
+const HEADER_LEN: usize = 27;
+
+/// Returns the length of the page at the start of `data`, or `None` if the page is incomplete.
+fn page_len(data: &[u8]) -> Option<usize> {
+	let segments = *data.get(HEADER_LEN - 1)? as usize;
+	let table = data.get(HEADER_LEN..HEADER_LEN + segments)?;
+	let len = HEADER_LEN + segments + table.iter().map(|&x| x as usize).sum::<usize>();
+	(data.len() >= len).then_some(len)
+}
+
+pin_project! {
+	/// Reassembles the audio chunks of an [`UtteranceEventStream`](crate::UtteranceEventStream) of OGG audio into
+	/// whole pages. See [`SpeechSynthesiserExt::synthesise_to_ogg_page_stream`](crate::SpeechSynthesiserExt::synthesise_to_ogg_page_stream).
+	pub(crate) struct OggPageStream<S> {
+		#[pin]
+		inner: S,
+		// Audio received which does not yet form a complete page.
+		buffer: Vec<u8>,
+		// Complete pages waiting to be yielded.
+		pages: VecDeque<Vec<u8>>,
+		max_item_size: usize,
+		finished: bool
+	}
+}
+
+impl<S> OggPageStream<S> {
+	pub(crate) fn new(inner: S, max_item_size: Option<usize>) -> Self {
+		OggPageStream {
+			inner,
+			buffer: Vec::new(),
+			pages: VecDeque::new(),
+			max_item_size: max_item_size.unwrap_or(0),
+			finished: false
+		}
+	}
+}
+
+/// Moves each complete page at the start of `buffer` into `pages`. Any data before the first capture pattern is
+/// discarded.
+fn split_pages(buffer: &mut Vec<u8>, pages: &mut VecDeque<Vec<u8>>) {
+	let mut start = 0;
+	loop {
+		match buffer[start..].windows(CAPTURE_PATTERN.len()).position(|x| x == CAPTURE_PATTERN) {
+			Some(offset) => start += offset,
+			// Keep a possible partial capture pattern at the end of the buffer.
+			None => {
+				start = buffer.len().saturating_sub(CAPTURE_PATTERN.len() - 1).max(start);
+				break;
+			}
+		}
+		match page_len(&buffer[start..]) {
+			Some(len) => {
+				pages.push_back(buffer[start..start + len].to_vec());
+				start += len;
+			}
+			None => break
+		}
+	}
+	buffer.drain(..start);
+}
+
+impl<E, S: Stream<Item = Result<UtteranceEvent, E>>> Stream for OggPageStream<S> {
+	type Item = Result<Box<[u8]>, E>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let mut this = self.project();
+		loop {
+			// Pages can only be combined into an item once it is known whether the next page fits.
+			let pending_len: usize = this.pages.iter().map(Vec::len).sum();
+			if !this.pages.is_empty() && (*this.finished || pending_len > *this.max_item_size) {
+				let mut item = this.pages.pop_front().unwrap_or_default();
+				while let Some(next) = this.pages.front() {
+					if item.len() + next.len() > *this.max_item_size {
+						break;
+					}
+					item.extend_from_slice(next);
+					this.pages.pop_front();
+				}
+				return Poll::Ready(Some(Ok(item.into_boxed_slice())));
+			}
+			if *this.finished {
+				return Poll::Ready(None);
+			}
+			match this.inner.as_mut().poll_next(cx) {
+				Poll::Ready(Some(Ok(UtteranceEvent::AudioChunk(chunk)))) => {
+					this.buffer.extend_from_slice(&chunk);
+					split_pages(this.buffer, this.pages);
+				}
+				Poll::Ready(Some(Ok(_))) => {}
+				Poll::Ready(Some(Err(e))) => return Poll::Ready(Some(Err(e))),
+				Poll::Ready(None) => {
+					*this.finished = true;
+					// A trailing incomplete page is yielded as-is rather than discarded.
+					if this.buffer.starts_with(CAPTURE_PATTERN) {
+						this.pages.push_back(core::mem::take(this.buffer));
+					}
+				}
+				Poll::Pending => return Poll::Pending
+			}
+		}
+	}
+}
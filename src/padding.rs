@@ -0,0 +1,330 @@
+use core::{
+	future::Future,
+	pin::Pin,
+	task::{Context, Poll},
+	time::Duration
+};
+
+use futures_core::Stream;
+use pin_project_lite::pin_project;
+use ssml::TimeDesignation;
+
+use crate::{
+	AudioContainer, AudioFormat, AudioFormatPreference, LanguageInfo, SpeechSynthesiser, SsmlValidationError, SynthesiserCapabilities, UtteranceConfig,
+	UtteranceEvent, UtteranceEventStream, VoiceInfo, text::text_to_ssml
+};
+
+/// Wraps a [`SpeechSynthesiser`] to honour [`UtteranceConfig::break_before`] & [`UtteranceConfig::break_after`].
+///
+/// For [containerless](AudioContainer::is_containerless) formats, silence is generated with
+/// [`AudioFormat::silence_bytes`] and emitted as [`UtteranceEvent::AudioChunk`]s at the start & end of the stream, and
+/// the timestamps of all other events are offset by the length of the leading silence. For all other formats, where
+/// silence cannot be inserted without re-encoding the audio, an SSML `<break>` is inserted at the start & end of the
+/// document instead; text input is first converted to SSML and synthesised via
+/// [`SpeechSynthesiser::synthesise_ssml_stream`].
+///
+/// In either case, the breaks are removed from the configuration passed to the inner synthesiser, so that they are not
+/// inserted twice.
+#[derive(Debug, Clone)]
+pub struct PaddingSpeechSynthesiser<S>(S);
+
+impl<S: SpeechSynthesiser> PaddingSpeechSynthesiser<S> {
+	pub fn new(synthesiser: S) -> Self {
+		PaddingSpeechSynthesiser(synthesiser)
+	}
+
+	pub fn inner(&self) -> &S {
+		&self.0
+	}
+
+	pub fn into_inner(self) -> S {
+		self.0
+	}
+}
+
+fn without_breaks(config: &UtteranceConfig) -> UtteranceConfig {
+	let mut config = config.clone();
+	config.break_before = None;
+	config.break_after = None;
+	config
+}
+
+/// Returns the leading & trailing silence for `config`, if it can be inserted directly into audio in `audio_format`.
+fn silence(audio_format: &AudioFormat, config: &UtteranceConfig) -> Option<(Vec<u8>, Vec<u8>)> {
+	if !audio_format.container().is_containerless() {
+		return None;
+	}
+	let before = audio_format.silence_bytes(config.break_before.unwrap_or_default())?;
+	let after = audio_format.silence_bytes(config.break_after.unwrap_or_default())?;
+	Some((before, after))
+}
+
+/// Inserts a `<break>` at the start & end of `document` for the configured breaks.
+fn insert_breaks(document: &mut ssml::Speak<'_>, config: &UtteranceConfig) {
+	let break_element = |duration: Duration| ssml::breaks(TimeDesignation::from_millis(duration.as_secs_f32() * 1000.0));
+	if let Some(before) = config.break_before {
+		document.children_mut().insert(0, break_element(before).into());
+	}
+	if let Some(after) = config.break_after {
+		document.push(break_element(after));
+	}
+}
+
+pin_project! {
+	struct Padded<E, S> {
+		#[pin]
+		inner: S,
+		before: Option<Box<[u8]>>,
+		after: Option<Box<[u8]>>,
+		offset_millis: f32,
+		// The total length of the leading & trailing silence.
+		padding_bytes: u64,
+		// An item received from the inner stream which is held back while silence is emitted.
+		held: Option<Result<UtteranceEvent, E>>,
+		finished: bool
+	}
+}
+
+impl<E, S: Stream<Item = Result<UtteranceEvent, E>>> Stream for Padded<E, S> {
+	type Item = Result<UtteranceEvent, E>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let this = self.project();
+		if let Some(item) = this.held.take() {
+			return Poll::Ready(Some(item));
+		}
+		if *this.finished {
+			return Poll::Ready(None);
+		}
+
+		let item = match this.inner.poll_next(cx) {
+			Poll::Ready(item) => item,
+			Poll::Pending => return Poll::Pending
+		};
+		let item = match item {
+			// The leading silence follows the `SynthesisStarted` sentinel, if there is one.
+			Some(Ok(event @ UtteranceEvent::SynthesisStarted { .. })) => return Poll::Ready(Some(Ok(event))),
+			Some(Ok(UtteranceEvent::SynthesisCompleted { duration_millis, total_audio_bytes })) => {
+				// The trailing silence precedes the `SynthesisCompleted` sentinel, which counts all silence emitted.
+				let completed = UtteranceEvent::SynthesisCompleted {
+					duration_millis,
+					total_audio_bytes: total_audio_bytes + *this.padding_bytes
+				};
+				*this.finished = true;
+				match this.after.take() {
+					Some(after) => {
+						*this.held = Some(Ok(completed));
+						Ok(UtteranceEvent::AudioChunk(after))
+					}
+					None => Ok(completed)
+				}
+			}
+			Some(Ok(mut event)) => {
+				event.offset_timestamps(*this.offset_millis);
+				Ok(event)
+			}
+			Some(Err(e)) => {
+				*this.finished = true;
+				return Poll::Ready(Some(Err(e)));
+			}
+			None => {
+				*this.finished = true;
+				match this.after.take() {
+					Some(after) => Ok(UtteranceEvent::AudioChunk(after)),
+					None => return Poll::Ready(None)
+				}
+			}
+		};
+		match this.before.take() {
+			Some(before) => {
+				*this.held = Some(item);
+				Poll::Ready(Some(Ok(UtteranceEvent::AudioChunk(before))))
+			}
+			None => Poll::Ready(Some(item))
+		}
+	}
+}
+
+fn padded<E>(stream: impl UtteranceEventStream<E>, before: Vec<u8>, after: Vec<u8>, offset_millis: f32) -> impl UtteranceEventStream<E>
+where
+	E: Send
+{
+	Padded {
+		inner: stream,
+		padding_bytes: (before.len() + after.len()) as u64,
+		before: Some(before.into_boxed_slice()).filter(|b| !b.is_empty()),
+		after: Some(after.into_boxed_slice()).filter(|b| !b.is_empty()),
+		offset_millis,
+		held: None,
+		finished: false
+	}
+}
+
+impl<S: SpeechSynthesiser + Sync> SpeechSynthesiser for PaddingSpeechSynthesiser<S> {
+	type Error = S::Error;
+
+	fn negotiate_audio_format(&self, pref: &AudioFormatPreference) -> Option<AudioFormat> {
+		self.0.negotiate_audio_format(pref)
+	}
+
+	fn capabilities(&self) -> SynthesiserCapabilities {
+		self.0.capabilities()
+	}
+
+	fn validate_ssml(&self, input: &ssml::Speak<'_>) -> Result<(), Vec<SsmlValidationError>> {
+		self.0.validate_ssml(input)
+	}
+
+	/// Includes the configured breaks in the inner synthesiser's estimate.
+	fn estimate_duration(&self, text: &str, config: &UtteranceConfig) -> Option<Duration> {
+		let estimate = self.0.estimate_duration(text, config)?;
+		Some(estimate + config.break_before.unwrap_or_default() + config.break_after.unwrap_or_default())
+	}
+
+	fn supported_sample_rates(&self) -> &[u32] {
+		self.0.supported_sample_rates()
+	}
+
+	fn supported_containers(&self) -> &[AudioContainer] {
+		self.0.supported_containers()
+	}
+
+	async fn synthesise_ssml_stream(
+		&self,
+		input: &ssml::Speak<'_>,
+		audio_format: &AudioFormat,
+		config: &UtteranceConfig
+	) -> Result<impl UtteranceEventStream<Self::Error> + 'static, Self::Error> {
+		if config.break_before.is_none() && config.break_after.is_none() {
+			return Ok(Box::pin(self.0.synthesise_ssml_stream(input, audio_format, config).await?) as Pin<Box<dyn UtteranceEventStream<Self::Error>>>);
+		}
+
+		let inner_config = without_breaks(config);
+		if let Some((before, after)) = silence(audio_format, config) {
+			let offset_millis = config.break_before.unwrap_or_default().as_secs_f32() * 1000.0;
+			let stream = self.0.synthesise_ssml_stream(input, audio_format, &inner_config).await?;
+			return Ok(Box::pin(padded(stream, before, after, offset_millis)));
+		}
+
+		let mut document = input.clone();
+		insert_breaks(&mut document, config);
+		Ok(Box::pin(self.0.synthesise_ssml_stream(&document, audio_format, &inner_config).await?))
+	}
+
+	async fn synthesise_text_stream(
+		&self,
+		input: &str,
+		audio_format: &AudioFormat,
+		config: &UtteranceConfig
+	) -> Result<impl UtteranceEventStream<Self::Error> + 'static, Self::Error> {
+		if config.break_before.is_none() && config.break_after.is_none() {
+			return Ok(Box::pin(self.0.synthesise_text_stream(input, audio_format, config).await?) as Pin<Box<dyn UtteranceEventStream<Self::Error>>>);
+		}
+
+		let inner_config = without_breaks(config);
+		if let Some((before, after)) = silence(audio_format, config) {
+			let offset_millis = config.break_before.unwrap_or_default().as_secs_f32() * 1000.0;
+			let stream = self.0.synthesise_text_stream(input, audio_format, &inner_config).await?;
+			return Ok(Box::pin(padded(stream, before, after, offset_millis)));
+		}
+
+		let mut document = text_to_ssml(input, &inner_config);
+		insert_breaks(&mut document, config);
+		Ok(Box::pin(self.0.synthesise_ssml_stream(&document, audio_format, &inner_config).await?))
+	}
+
+	fn list_voices(&self) -> impl Future<Output = Result<Vec<VoiceInfo>, Self::Error>> + Send {
+		self.0.list_voices()
+	}
+
+	fn supported_languages(&self) -> impl Future<Output = Result<Vec<LanguageInfo>, Self::Error>> + Send {
+		self.0.supported_languages()
+	}
+
+	fn health_check(&self) -> impl Future<Output = Result<(), Self::Error>> + Send {
+		self.0.health_check()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use core::{pin::pin, time::Duration};
+
+	use ssml::{Break, Element, TimeDesignation};
+
+	use super::{PaddingSpeechSynthesiser, insert_breaks};
+	use crate::{
+		AudioChannels, AudioContainer, AudioEncoding, AudioFormat, SpeechSynthesiser, UtteranceConfig, UtteranceEvent, stream, testing::MockSpeechSynthesiser
+	};
+
+	fn word(from_millis: f32) -> UtteranceEvent {
+		UtteranceEvent::WordBoundary {
+			from_millis,
+			to_millis: from_millis + 100.0,
+			text: "word".into(),
+			confidence: None,
+			phonetic: None
+		}
+	}
+
+	#[tokio::test]
+	async fn pads_raw_audio_and_offsets_events() {
+		let format = AudioFormat::new(8000, AudioChannels::Mono, None, AudioContainer::Raw(AudioEncoding::PcmI16Le));
+		let mock = MockSpeechSynthesiser::new();
+		mock.push_response(vec![1; 32], vec![word(0.0), word(150.0)]);
+		let synthesiser = PaddingSpeechSynthesiser::new(mock);
+
+		let config = UtteranceConfig::default()
+			.with_break_before(Duration::from_millis(250))
+			.with_break_after(Duration::from_millis(100));
+		let stream = synthesiser.synthesise_text_stream("", &format, &config).await.unwrap();
+		let mut stream = pin!(stream);
+		let mut events = Vec::new();
+		while let Some(event) = stream::next(stream.as_mut()).await {
+			events.push(event.unwrap());
+		}
+
+		let [UtteranceEvent::AudioChunk(before), first, second, UtteranceEvent::AudioChunk(audio), UtteranceEvent::AudioChunk(after)] = &events[..] else {
+			panic!("unexpected events: {events:?}");
+		};
+		assert_eq!(before.len(), 250 * 16);
+		assert!(before.iter().all(|&b| b == 0));
+		assert_eq!(first.timestamp_millis(), Some(250.0));
+		assert_eq!(second.timestamp_millis(), Some(400.0));
+		assert_eq!(&audio[..], [1; 32]);
+		assert_eq!(after.len(), 100 * 16);
+	}
+
+	#[tokio::test]
+	async fn passes_through_without_breaks() {
+		let format = AudioFormat::new(8000, AudioChannels::Mono, None, AudioContainer::Raw(AudioEncoding::PcmI16Le));
+		let mock = MockSpeechSynthesiser::new();
+		mock.push_response(vec![1; 32], vec![word(50.0)]);
+		let synthesiser = PaddingSpeechSynthesiser::new(mock);
+
+		let stream = synthesiser
+			.synthesise_text_stream("", &format, &UtteranceConfig::default())
+			.await
+			.unwrap();
+		let mut stream = pin!(stream);
+		assert_eq!(stream::next(stream.as_mut()).await.unwrap().unwrap().timestamp_millis(), Some(50.0));
+		assert!(matches!(stream::next(stream.as_mut()).await, Some(Ok(UtteranceEvent::AudioChunk(_)))));
+		assert!(stream::next(stream.as_mut()).await.is_none());
+	}
+
+	#[test]
+	fn inserts_ssml_breaks() {
+		let config = UtteranceConfig::default()
+			.with_break_before(Duration::from_millis(250))
+			.with_break_after(Duration::from_millis(500));
+		let mut document = ssml::speak(None, ["Hello"]);
+		insert_breaks(&mut document, &config);
+
+		let is_break = |element: &Element, millis: f32| matches!(element, Element::Break(Break::Time(time)) if *time == TimeDesignation::from_millis(millis));
+		let children = document.children();
+		assert_eq!(children.len(), 3);
+		assert!(is_break(&children[0], 250.0));
+		assert!(matches!(children[1], Element::Text(_)));
+		assert!(is_break(&children[2], 500.0));
+	}
+}
@@ -0,0 +1,108 @@
+use core::{future::Future, pin::Pin, time::Duration};
+
+use crate::{
+	AudioContainer, AudioFormat, AudioFormatPreference, LanguageInfo, SentenceDetectionMode, SpeechSynthesiser, SsmlValidationError, SynthesiserCapabilities,
+	UtteranceConfig, UtteranceEventStream, VoiceInfo,
+	text::{sentence_bounds, text_to_ssml}
+};
+
+/// Wraps a [`SpeechSynthesiser`] to honour [`UtteranceConfig::inter_sentence_pause`].
+///
+/// When a pause is configured, text input is split into sentences according to
+/// [`UtteranceConfig::sentence_detection`].
+/// Each sentence is wrapped in an `<s>` element, an SSML `<break>` of the configured duration is inserted between
+/// sentences, and the resulting document is synthesised via [`SpeechSynthesiser::synthesise_ssml_stream`]. SSML input
+/// is passed through as-is, since pauses can already be expressed explicitly. Text is also passed through as-is if
+/// sentence detection is left to the [backend](SentenceDetectionMode::Backend).
+#[derive(Debug, Clone)]
+pub struct SentencePauseSpeechSynthesiser<S>(S);
+
+impl<S: SpeechSynthesiser> SentencePauseSpeechSynthesiser<S> {
+	pub fn new(synthesiser: S) -> Self {
+		SentencePauseSpeechSynthesiser(synthesiser)
+	}
+
+	pub fn inner(&self) -> &S {
+		&self.0
+	}
+
+	pub fn into_inner(self) -> S {
+		self.0
+	}
+}
+
+impl<S: SpeechSynthesiser + Sync> SpeechSynthesiser for SentencePauseSpeechSynthesiser<S> {
+	type Error = S::Error;
+
+	fn negotiate_audio_format(&self, pref: &AudioFormatPreference) -> Option<AudioFormat> {
+		self.0.negotiate_audio_format(pref)
+	}
+
+	fn capabilities(&self) -> SynthesiserCapabilities {
+		self.0.capabilities()
+	}
+
+	fn validate_ssml(&self, input: &ssml::Speak<'_>) -> Result<(), Vec<SsmlValidationError>> {
+		self.0.validate_ssml(input)
+	}
+
+	/// Includes the configured pauses between sentences in the inner synthesiser's estimate.
+	fn estimate_duration(&self, text: &str, config: &UtteranceConfig) -> Option<Duration> {
+		let estimate = self.0.estimate_duration(text, config)?;
+		let Some(pause) = config
+			.inter_sentence_pause
+			.filter(|_| config.sentence_detection != SentenceDetectionMode::Backend)
+		else {
+			return Some(estimate);
+		};
+		let sentences = sentence_bounds(text, &config.sentence_detection)
+			.into_iter()
+			.filter(|(_, s)| !s.trim().is_empty())
+			.count() as u32;
+		Some(estimate + pause * sentences.saturating_sub(1))
+	}
+
+	fn supported_sample_rates(&self) -> &[u32] {
+		self.0.supported_sample_rates()
+	}
+
+	fn supported_containers(&self) -> &[AudioContainer] {
+		self.0.supported_containers()
+	}
+
+	fn synthesise_ssml_stream(
+		&self,
+		input: &ssml::Speak<'_>,
+		audio_format: &AudioFormat,
+		config: &UtteranceConfig
+	) -> impl Future<Output = Result<impl UtteranceEventStream<Self::Error> + 'static, Self::Error>> + Send {
+		self.0.synthesise_ssml_stream(input, audio_format, config)
+	}
+
+	async fn synthesise_text_stream(
+		&self,
+		input: &str,
+		audio_format: &AudioFormat,
+		config: &UtteranceConfig
+	) -> Result<impl UtteranceEventStream<Self::Error> + 'static, Self::Error> {
+		let stream: Pin<Box<dyn UtteranceEventStream<Self::Error>>> = match (config.inter_sentence_pause, &config.sentence_detection) {
+			(Some(_), mode) if *mode != SentenceDetectionMode::Backend => {
+				Box::pin(self.0.synthesise_ssml_stream(&text_to_ssml(input, config), audio_format, config).await?)
+			}
+			_ => Box::pin(self.0.synthesise_text_stream(input, audio_format, config).await?)
+		};
+		Ok(stream)
+	}
+
+	fn list_voices(&self) -> impl Future<Output = Result<Vec<VoiceInfo>, Self::Error>> + Send {
+		self.0.list_voices()
+	}
+
+	fn supported_languages(&self) -> impl Future<Output = Result<Vec<LanguageInfo>, Self::Error>> + Send {
+		self.0.supported_languages()
+	}
+
+	fn health_check(&self) -> impl Future<Output = Result<(), Self::Error>> + Send {
+		self.0.health_check()
+	}
+}
@@ -0,0 +1,124 @@
+use core::pin::pin;
+
+use rodio::{Sink, buffer::SamplesBuffer};
+
+use crate::{AudioContainer, AudioEncoding, AudioFormat, Endianness, SynthesisError, UtteranceEvent, UtteranceEventStream, audio::riff_data_offset, stream};
+
+/// Implementation of [`SpeechSynthesiserExt::pipe_to_rodio`](crate::SpeechSynthesiserExt::pipe_to_rodio).
+pub(crate) async fn pipe_to_rodio<E, F: FnMut(f32, &str)>(
+	stream: impl UtteranceEventStream<E>,
+	audio_format: &AudioFormat,
+	sink: &Sink,
+	mut on_word: Option<F>
+) -> Result<(), SynthesisError<E>> {
+	let (is_riff, endianness) = match audio_format.container() {
+		AudioContainer::Raw(encoding) if is_pcm_i16(encoding) => (false, encoding.endianness()),
+		AudioContainer::Riff(encoding) if is_pcm_i16(encoding) => (true, encoding.endianness()),
+		_ => return Err(SynthesisError::UnsupportedFormat(audio_format.clone()))
+	};
+	let channels = audio_format.channels().count() as u16;
+	let frame_width = 2 * channels as usize;
+
+	// Audio which has not yet been appended to the sink; this holds the RIFF header until it has been fully received,
+	// and any incomplete frame at the end of a chunk.
+	let mut pending = Vec::new();
+	let mut in_header = is_riff;
+	let mut stream = pin!(stream);
+	while let Some(event) = stream::next(stream.as_mut()).await {
+		match event.map_err(SynthesisError::Synthesiser)? {
+			UtteranceEvent::AudioChunk(chunk) => {
+				pending.extend_from_slice(&chunk);
+				if in_header {
+					let Some(offset) = riff_data_offset(&pending) else {
+						continue;
+					};
+					pending.drain(..offset);
+					in_header = false;
+				}
+
+				let frames = pending.len() / frame_width * frame_width;
+				let samples: Vec<i16> = pending[..frames]
+					.chunks_exact(2)
+					.map(|sample| match endianness {
+						Some(Endianness::Big) => i16::from_be_bytes([sample[0], sample[1]]),
+						_ => i16::from_le_bytes([sample[0], sample[1]])
+					})
+					.collect();
+				pending.drain(..frames);
+				if !samples.is_empty() {
+					sink.append(SamplesBuffer::new(channels, audio_format.sample_rate(), samples));
+				}
+			}
+			UtteranceEvent::WordBoundary { from_millis, text, .. } => {
+				if let Some(on_word) = on_word.as_mut() {
+					on_word(from_millis, &text);
+				}
+			}
+			_ => {}
+		}
+	}
+	Ok(())
+}
+
+#[allow(deprecated)]
+fn is_pcm_i16(encoding: AudioEncoding) -> bool {
+	matches!(encoding, AudioEncoding::PcmI16 | AudioEncoding::PcmI16Le | AudioEncoding::PcmI16Be)
+}
+
+#[cfg(test)]
+mod tests {
+	use rodio::Sink;
+
+	use crate::{
+		AudioChannels, AudioContainer, AudioEncoding, AudioFormat, SpeechSynthesiser, SpeechSynthesiserExt, SynthesisError, UtteranceConfig, UtteranceEvent,
+		testing::MockSpeechSynthesiser
+	};
+
+	fn word(from_millis: f32, text: &str) -> UtteranceEvent {
+		UtteranceEvent::WordBoundary {
+			from_millis,
+			to_millis: from_millis + 100.0,
+			text: text.into(),
+			confidence: None,
+			phonetic: None
+		}
+	}
+
+	#[tokio::test]
+	async fn plays_riff_audio_and_reports_words() {
+		let format = AudioFormat::new(8000, AudioChannels::Mono, None, AudioContainer::Riff(AudioEncoding::PcmI16Le));
+		let mut audio = format.to_wav_header(1600).unwrap();
+		audio.extend(std::iter::repeat(0x7f).take(1600));
+		let synthesiser = MockSpeechSynthesiser::new();
+		synthesiser.push_response(audio, vec![word(0.0, "hello"), word(100.0, "world")]);
+
+		let stream = synthesiser
+			.synthesise_text_stream("", &format, &UtteranceConfig::default())
+			.await
+			.unwrap();
+		let (sink, _output) = Sink::new_idle();
+		let mut words = Vec::new();
+		synthesiser
+			.pipe_to_rodio(stream, &format, &sink, Some(|at_millis: f32, text: &str| words.push((at_millis, text.to_string()))))
+			.await
+			.unwrap();
+		assert_eq!(sink.len(), 1);
+		assert_eq!(words, [(0.0, "hello".to_string()), (100.0, "world".to_string())]);
+	}
+
+	#[tokio::test]
+	async fn rejects_non_pcm_i16_formats() {
+		let format = AudioFormat::new(8000, AudioChannels::Mono, None, AudioContainer::Raw(AudioEncoding::PcmF32Le));
+		let synthesiser = MockSpeechSynthesiser::new();
+		synthesiser.push_response(vec![0; 16], Vec::new());
+
+		let stream = synthesiser
+			.synthesise_text_stream("", &format, &UtteranceConfig::default())
+			.await
+			.unwrap();
+		let (sink, _output) = Sink::new_idle();
+		let result = synthesiser.pipe_to_rodio(stream, &format, &sink, None::<fn(f32, &str)>).await;
+		assert!(matches!(result, Err(SynthesisError::UnsupportedFormat(_))));
+		assert!(sink.empty());
+	}
+}
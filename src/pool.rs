@@ -0,0 +1,238 @@
+use core::{
+	fmt,
+	future::{Future, poll_fn},
+	pin::Pin,
+	sync::atomic::{AtomicUsize, Ordering},
+	task::{Context, Poll, Waker},
+	time::Duration
+};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use futures_core::Stream;
+use pin_project_lite::pin_project;
+
+use crate::{
+	AudioContainer, AudioFormat, AudioFormatPreference, LanguageInfo, SpeechSynthesiser, SsmlValidationError, SynthesiserCapabilities, UtteranceConfig,
+	UtteranceEvent, UtteranceEventStream, VoiceInfo
+};
+
+struct Semaphore {
+	available: usize,
+	waiters: Vec<Waker>
+}
+
+struct PoolShared {
+	semaphore: Mutex<Semaphore>,
+	/// The number of in-flight requests for each instance.
+	in_flight: Box<[AtomicUsize]>,
+	/// The instance to start searching from, so that ties between equally busy instances are broken round-robin.
+	next: AtomicUsize
+}
+
+impl PoolShared {
+	fn new(instances: usize, max_concurrent: usize) -> Self {
+		PoolShared {
+			semaphore: Mutex::new(Semaphore {
+				available: max_concurrent,
+				waiters: Vec::new()
+			}),
+			in_flight: (0..instances).map(|_| AtomicUsize::new(0)).collect(),
+			next: AtomicUsize::new(0)
+		}
+	}
+
+	fn lock(&self) -> MutexGuard<'_, Semaphore> {
+		self.semaphore.lock().unwrap_or_else(|e| e.into_inner())
+	}
+
+	fn poll_acquire(&self, cx: &mut Context<'_>) -> Poll<()> {
+		let mut semaphore = self.lock();
+		if semaphore.available > 0 {
+			semaphore.available -= 1;
+			return Poll::Ready(());
+		}
+		if !semaphore.waiters.iter().any(|w| w.will_wake(cx.waker())) {
+			semaphore.waiters.push(cx.waker().clone());
+		}
+		Poll::Pending
+	}
+
+	fn release(&self) {
+		let waiters = {
+			let mut semaphore = self.lock();
+			semaphore.available += 1;
+			// Wake all waiters rather than just one, since a woken waiter may have been dropped before it could acquire
+			// the permit.
+			core::mem::take(&mut semaphore.waiters)
+		};
+		waiters.into_iter().for_each(Waker::wake);
+	}
+
+	/// Selects the instance with the fewest in-flight requests.
+	fn select(&self) -> usize {
+		let len = self.in_flight.len();
+		let start = self.next.fetch_add(1, Ordering::Relaxed) % len;
+		let index = (start..len)
+			.chain(0..start)
+			.min_by_key(|&i| self.in_flight[i].load(Ordering::Relaxed))
+			.unwrap_or(start);
+		self.in_flight[index].fetch_add(1, Ordering::Relaxed);
+		index
+	}
+}
+
+/// A permit to use one instance of the pool, released when dropped.
+struct PoolPermit {
+	shared: Arc<PoolShared>,
+	index: usize
+}
+
+impl Drop for PoolPermit {
+	fn drop(&mut self) {
+		self.shared.in_flight[self.index].fetch_sub(1, Ordering::Relaxed);
+		self.shared.release();
+	}
+}
+
+pin_project! {
+	struct PooledStream<S> {
+		#[pin]
+		inner: S,
+		permit: PoolPermit
+	}
+}
+
+impl<E, S: Stream<Item = Result<UtteranceEvent, E>>> Stream for PooledStream<S> {
+	type Item = Result<UtteranceEvent, E>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		self.project().inner.poll_next(cx)
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.inner.size_hint()
+	}
+}
+
+/// Dispatches synthesis requests across multiple instances of a synthesiser, i.e. multiple API clients or connections,
+/// for high-throughput batch processing.
+///
+/// Each request is dispatched to the instance with the fewest in-flight requests, with ties broken round-robin. A
+/// request is considered in-flight until its stream is dropped. Optionally, the total number of concurrent requests
+/// across all instances can be limited with [`SpeechSynthesiserPool::with_max_concurrent`]; further requests wait
+/// until an in-flight request completes.
+///
+/// All instances are assumed to be configured identically; properties like the supported audio formats, capabilities,
+/// and voices are taken from the first instance.
+pub struct SpeechSynthesiserPool<S> {
+	instances: Vec<S>,
+	shared: Arc<PoolShared>
+}
+
+impl<S: SpeechSynthesiser> SpeechSynthesiserPool<S> {
+	/// Creates a new pool dispatching requests across `instances`, with no limit on the number of concurrent requests.
+	///
+	/// # Panics
+	/// Panics if `instances` is empty.
+	pub fn new(instances: Vec<S>) -> Self {
+		assert!(!instances.is_empty(), "a synthesiser pool must contain at least one instance");
+		let shared = Arc::new(PoolShared::new(instances.len(), usize::MAX));
+		SpeechSynthesiserPool { instances, shared }
+	}
+
+	/// Limits the total number of concurrent requests across all instances. A limit of `0` is treated as `1`.
+	pub fn with_max_concurrent(mut self, limit: usize) -> Self {
+		self.shared = Arc::new(PoolShared::new(self.instances.len(), limit.max(1)));
+		self
+	}
+
+	pub fn instances(&self) -> &[S] {
+		&self.instances
+	}
+
+	pub fn into_inner(self) -> Vec<S> {
+		self.instances
+	}
+
+	async fn acquire(&self) -> PoolPermit {
+		poll_fn(|cx| self.shared.poll_acquire(cx)).await;
+		PoolPermit {
+			shared: Arc::clone(&self.shared),
+			index: self.shared.select()
+		}
+	}
+}
+
+impl<S: fmt::Debug> fmt::Debug for SpeechSynthesiserPool<S> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("SpeechSynthesiserPool")
+			.field("instances", &self.instances)
+			.finish_non_exhaustive()
+	}
+}
+
+impl<S: SpeechSynthesiser + Sync> SpeechSynthesiser for SpeechSynthesiserPool<S> {
+	type Error = S::Error;
+
+	fn negotiate_audio_format(&self, pref: &AudioFormatPreference) -> Option<AudioFormat> {
+		self.instances[0].negotiate_audio_format(pref)
+	}
+
+	fn capabilities(&self) -> SynthesiserCapabilities {
+		self.instances[0].capabilities()
+	}
+
+	fn validate_ssml(&self, input: &ssml::Speak<'_>) -> Result<(), Vec<SsmlValidationError>> {
+		self.instances[0].validate_ssml(input)
+	}
+
+	fn estimate_duration(&self, text: &str, config: &UtteranceConfig) -> Option<Duration> {
+		self.instances[0].estimate_duration(text, config)
+	}
+
+	fn supported_sample_rates(&self) -> &[u32] {
+		self.instances[0].supported_sample_rates()
+	}
+
+	fn supported_containers(&self) -> &[AudioContainer] {
+		self.instances[0].supported_containers()
+	}
+
+	async fn synthesise_ssml_stream(
+		&self,
+		input: &ssml::Speak<'_>,
+		audio_format: &AudioFormat,
+		config: &UtteranceConfig
+	) -> Result<impl UtteranceEventStream<Self::Error> + 'static, Self::Error> {
+		let permit = self.acquire().await;
+		let stream = self.instances[permit.index].synthesise_ssml_stream(input, audio_format, config).await?;
+		Ok(PooledStream { inner: stream, permit })
+	}
+
+	async fn synthesise_text_stream(
+		&self,
+		input: &str,
+		audio_format: &AudioFormat,
+		config: &UtteranceConfig
+	) -> Result<impl UtteranceEventStream<Self::Error> + 'static, Self::Error> {
+		let permit = self.acquire().await;
+		let stream = self.instances[permit.index].synthesise_text_stream(input, audio_format, config).await?;
+		Ok(PooledStream { inner: stream, permit })
+	}
+
+	fn list_voices(&self) -> impl Future<Output = Result<Vec<VoiceInfo>, Self::Error>> + Send {
+		self.instances[0].list_voices()
+	}
+
+	fn supported_languages(&self) -> impl Future<Output = Result<Vec<LanguageInfo>, Self::Error>> + Send {
+		self.instances[0].supported_languages()
+	}
+
+	/// Checks the health of every instance in turn, returning the first error encountered.
+	async fn health_check(&self) -> Result<(), Self::Error> {
+		for instance in &self.instances {
+			instance.health_check().await?;
+		}
+		Ok(())
+	}
+}
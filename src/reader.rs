@@ -0,0 +1,235 @@
+use core::{
+	pin::Pin,
+	task::{Context, Poll}
+};
+use std::{collections::VecDeque, io};
+
+use futures_core::Stream;
+use tokio::io::{AsyncRead, ReadBuf};
+
+use crate::{
+	AudioFormat, SpeechSynthesiser, SynthesisError, UtteranceConfig, UtteranceEvent,
+	sequential::{SegmentEventStream, SequentialSynthesis, Step},
+	text::sentence_bounds
+};
+
+/// The length in bytes beyond which text without a sentence boundary is split at its last whitespace anyway, so that a
+/// reader without any sentence boundaries can't grow the text buffer indefinitely.
+const MAX_SENTENCE_BYTES: usize = 4096;
+
+/// Stream returned by
+/// [`SpeechSynthesiserExt::synthesise_text_reader_stream`](crate::SpeechSynthesiserExt::synthesise_text_reader_stream).
+pub(crate) struct TextReaderStream<'a, S: SpeechSynthesiser + ?Sized, R> {
+	synthesiser: &'a S,
+	audio_format: &'a AudioFormat,
+	config: &'a UtteranceConfig,
+	reader: R,
+	/// Bytes read which do not yet form a complete UTF-8 character.
+	undecoded: Vec<u8>,
+	/// Text read which has not yet been split into sentences, i.e. the start of an incomplete sentence followed by any
+	/// text read since.
+	text: String,
+	/// Complete sentences which have not yet been dispatched for synthesis.
+	sentences: VecDeque<String>,
+	eof: bool,
+	sequence: SequentialSynthesis<'a, S::Error>
+}
+
+impl<'a, S: SpeechSynthesiser + Sync + ?Sized, R: AsyncRead + Unpin + Send + 'a> TextReaderStream<'a, S, R> {
+	pub(crate) fn new(synthesiser: &'a S, reader: R, audio_format: &'a AudioFormat, config: &'a UtteranceConfig) -> Self {
+		TextReaderStream {
+			synthesiser,
+			audio_format,
+			config,
+			reader,
+			undecoded: Vec::new(),
+			text: String::new(),
+			sentences: VecDeque::new(),
+			eof: false,
+			sequence: SequentialSynthesis::new(audio_format)
+		}
+	}
+
+	/// Takes the next complete sentence, splitting the text buffer into sentences if none are left.
+	fn take_sentence(&mut self) -> Option<String> {
+		if self.sentences.is_empty() {
+			self.split_sentences();
+		}
+		self.sentences.pop_front()
+	}
+
+	/// Moves every complete sentence out of the text buffer.
+	///
+	/// The last sentence in the buffer is only considered complete once the reader has reached EOF, since more text may
+	/// follow it, so it is left in the buffer to be segmented again along with the next text read. Only that sentence
+	/// is segmented twice, and it is force split once it exceeds [`MAX_SENTENCE_BYTES`], so reading is linear in the
+	/// length of the text.
+	fn split_sentences(&mut self) {
+		let bounds = sentence_bounds(&self.text, &self.config.sentence_detection);
+		let complete = if self.eof { bounds.len() } else { bounds.len().saturating_sub(1) };
+		let consumed = bounds.get(complete).map_or(self.text.len(), |&(offset, _)| offset);
+		let sentences = bounds[..complete]
+			.iter()
+			.map(|(_, sentence)| sentence.trim())
+			.filter(|sentence| !sentence.is_empty());
+		self.sentences.extend(sentences.map(str::to_string));
+		self.text.drain(..consumed);
+
+		if !self.eof && self.text.len() > MAX_SENTENCE_BYTES {
+			let split = match self.text.rfind(char::is_whitespace) {
+				Some(index) if index > 0 => index,
+				// Without any whitespace, split at a character boundary instead.
+				_ => (0..=MAX_SENTENCE_BYTES)
+					.rev()
+					.find(|&index| self.text.is_char_boundary(index))
+					.unwrap_or_default()
+			};
+			let sentence: String = self.text.drain(..split).collect();
+			if !sentence.trim().is_empty() {
+				self.sentences.push_back(sentence.trim().to_string());
+			}
+		}
+	}
+
+	fn poll_read_text(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+		let mut buf = [0; 4096];
+		let mut buf = ReadBuf::new(&mut buf);
+		match Pin::new(&mut self.reader).poll_read(cx, &mut buf) {
+			Poll::Ready(Ok(())) => {}
+			Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+			Poll::Pending => return Poll::Pending
+		}
+		if buf.filled().is_empty() {
+			self.eof = true;
+			if !self.undecoded.is_empty() {
+				return Poll::Ready(Err(io::Error::new(io::ErrorKind::InvalidData, "stream did not contain valid UTF-8")));
+			}
+			return Poll::Ready(Ok(()));
+		}
+
+		self.undecoded.extend_from_slice(buf.filled());
+		let valid_up_to = match core::str::from_utf8(&self.undecoded) {
+			Ok(text) => text.len(),
+			// The buffer may end partway through a character, which will be completed by the next read.
+			Err(e) if e.error_len().is_none() => e.valid_up_to(),
+			Err(_) => return Poll::Ready(Err(io::Error::new(io::ErrorKind::InvalidData, "stream did not contain valid UTF-8")))
+		};
+		self.text
+			.push_str(core::str::from_utf8(&self.undecoded[..valid_up_to]).unwrap_or_default());
+		self.undecoded.drain(..valid_up_to);
+		Poll::Ready(Ok(()))
+	}
+}
+
+impl<'a, S: SpeechSynthesiser + Sync + ?Sized, R: AsyncRead + Unpin + Send + 'a> Stream for TextReaderStream<'a, S, R> {
+	type Item = Result<UtteranceEvent, SynthesisError<S::Error>>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let this = self.get_mut();
+		loop {
+			match this.sequence.poll_step(cx) {
+				Poll::Ready(Some(Ok(Step::Event(event)))) => return Poll::Ready(Some(Ok(event))),
+				Poll::Ready(Some(Ok(Step::Started { .. }))) => continue,
+				Poll::Ready(Some(Ok(Step::Idle))) => {}
+				Poll::Ready(Some(Err(e))) => return Poll::Ready(Some(Err(SynthesisError::Synthesiser(e)))),
+				Poll::Ready(None) => return Poll::Ready(None),
+				Poll::Pending => return Poll::Pending
+			}
+
+			if let Some(sentence) = this.take_sentence() {
+				let (synthesiser, audio_format, config) = (this.synthesiser, this.audio_format, this.config);
+				this.sequence.start(async move {
+					let stream = synthesiser.synthesise_text_stream(&sentence, audio_format, config).await?;
+					Ok(Box::pin(stream) as SegmentEventStream<'a, S::Error>)
+				});
+				continue;
+			}
+
+			if this.eof {
+				this.sequence.finish();
+				return Poll::Ready(None);
+			}
+
+			match this.poll_read_text(cx) {
+				Poll::Ready(Ok(())) => continue,
+				Poll::Ready(Err(e)) => {
+					this.sequence.finish();
+					return Poll::Ready(Some(Err(SynthesisError::Io(e))));
+				}
+				Poll::Pending => return Poll::Pending
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use core::pin::pin;
+
+	use super::{MAX_SENTENCE_BYTES, TextReaderStream};
+	use crate::{AudioChannels, AudioContainer, AudioEncoding, AudioFormat, UtteranceConfig, UtteranceEvent, stream, testing::MockSpeechSynthesiser};
+
+	fn format() -> AudioFormat {
+		AudioFormat::new(8000, AudioChannels::Mono, None, AudioContainer::Raw(AudioEncoding::PcmI16Le))
+	}
+
+	#[test]
+	fn last_sentence_waits_for_eof() {
+		let (synthesiser, format, config) = (MockSpeechSynthesiser::new(), format(), UtteranceConfig::default());
+		let mut reader = TextReaderStream::new(&synthesiser, &b""[..], &format, &config);
+		reader.text.push_str("First sentence. Second sentence. Third");
+		assert_eq!(reader.take_sentence().as_deref(), Some("First sentence."));
+		assert_eq!(reader.take_sentence().as_deref(), Some("Second sentence."));
+		assert_eq!(reader.take_sentence(), None);
+		// Only the incomplete sentence is left to be segmented again.
+		assert_eq!(reader.text, "Third");
+
+		reader.text.push_str(" sentence.");
+		reader.eof = true;
+		assert_eq!(reader.take_sentence().as_deref(), Some("Third sentence."));
+		assert_eq!(reader.take_sentence(), None);
+	}
+
+	#[test]
+	fn long_text_is_split_at_whitespace() {
+		let (synthesiser, format, config) = (MockSpeechSynthesiser::new(), format(), UtteranceConfig::default());
+		let mut reader = TextReaderStream::new(&synthesiser, &b""[..], &format, &config);
+		let words = "word ".repeat(MAX_SENTENCE_BYTES / 5 + 10);
+		reader.text.push_str(&words);
+		reader.text.push_str("unfinished");
+
+		let sentence = reader.take_sentence().unwrap();
+		assert!(sentence.len() <= words.len());
+		assert!(sentence.starts_with("word word") && sentence.ends_with("word"));
+		assert_eq!(reader.text.trim(), "unfinished");
+	}
+
+	#[test]
+	fn long_text_without_whitespace_is_split_at_char_boundary() {
+		let (synthesiser, format, config) = (MockSpeechSynthesiser::new(), format(), UtteranceConfig::default());
+		let mut reader = TextReaderStream::new(&synthesiser, &b""[..], &format, &config);
+		reader.text.push_str(&"é".repeat(MAX_SENTENCE_BYTES));
+
+		let sentence = reader.take_sentence().unwrap();
+		assert_eq!(sentence.len(), MAX_SENTENCE_BYTES);
+		assert_eq!(reader.text.len(), MAX_SENTENCE_BYTES);
+	}
+
+	#[tokio::test]
+	async fn synthesises_each_sentence() {
+		let synthesiser = MockSpeechSynthesiser::new();
+		for _ in 0..3 {
+			synthesiser.push_response(vec![0; 1600], Vec::new());
+		}
+		let (format, config) = (format(), UtteranceConfig::default());
+		let stream = TextReaderStream::new(&synthesiser, &b"One. Two. Three."[..], &format, &config);
+		let mut stream = pin!(stream);
+		let mut chunks = 0;
+		while let Some(event) = stream::next(stream.as_mut()).await {
+			assert!(matches!(event.unwrap(), UtteranceEvent::AudioChunk(_)));
+			chunks += 1;
+		}
+		assert_eq!(chunks, 3);
+		assert_eq!(synthesiser.remaining_responses(), 0);
+	}
+}
@@ -0,0 +1,180 @@
+use core::{fmt, future::Future, time::Duration};
+
+use futures_timer::Delay;
+
+use crate::{
+	AudioContainer, AudioFormat, AudioFormatPreference, LanguageInfo, SpeechSynthesiser, SsmlValidationError, SynthesiserCapabilities, UtteranceConfig,
+	UtteranceEventStream, VoiceInfo
+};
+
+/// Configuration for a [`RetryingSpeechSynthesiser`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct RetryConfig {
+	/// The maximum number of times to retry a failed request. Defaults to `3`.
+	pub max_retries: u32,
+	/// The time to wait before the first retry. Defaults to 250 milliseconds.
+	pub initial_backoff: Duration,
+	/// The factor by which the backoff is multiplied after each retry. Defaults to `2.0`.
+	pub backoff_factor: f32
+}
+
+impl Default for RetryConfig {
+	fn default() -> Self {
+		RetryConfig {
+			max_retries: 3,
+			initial_backoff: Duration::from_millis(250),
+			backoff_factor: 2.0
+		}
+	}
+}
+
+impl RetryConfig {
+	/// Configures the maximum number of times to retry a failed request.
+	pub fn with_max_retries(mut self, x: u32) -> Self {
+		self.max_retries = x;
+		self
+	}
+
+	/// Configures the time to wait before the first retry.
+	pub fn with_initial_backoff(mut self, x: Duration) -> Self {
+		self.initial_backoff = x;
+		self
+	}
+
+	/// Configures the factor by which the backoff is multiplied after each retry. A factor of `1.0` waits the same
+	/// amount of time between each retry.
+	pub fn with_backoff_factor(mut self, x: f32) -> Self {
+		self.backoff_factor = x;
+		self
+	}
+
+	fn backoff(&self, attempt: u32) -> Duration {
+		self.initial_backoff.mul_f32(self.backoff_factor.max(0.0).powi(attempt as i32))
+	}
+}
+
+type RetryCallback<E> = Box<dyn Fn(u32, &E) + Send + Sync>;
+
+/// Wraps a [`SpeechSynthesiser`] to retry failed requests with exponential backoff.
+///
+/// Only errors returned by the `synthesise_*_stream` futures are retried; errors yielded by the stream itself are
+/// passed through as-is, since audio may have already been emitted by then.
+pub struct RetryingSpeechSynthesiser<S: SpeechSynthesiser> {
+	inner: S,
+	config: RetryConfig,
+	on_retry: Option<RetryCallback<S::Error>>
+}
+
+impl<S: SpeechSynthesiser> RetryingSpeechSynthesiser<S> {
+	pub fn new(synthesiser: S, config: RetryConfig) -> Self {
+		RetryingSpeechSynthesiser {
+			inner: synthesiser,
+			config,
+			on_retry: None
+		}
+	}
+
+	/// Configures a callback to be called before each retry with the retry attempt (starting from `1`) and the error
+	/// which caused it, i.e. for logging.
+	pub fn with_on_retry(mut self, on_retry: impl Fn(u32, &S::Error) + Send + Sync + 'static) -> Self {
+		self.on_retry = Some(Box::new(on_retry));
+		self
+	}
+
+	pub fn inner(&self) -> &S {
+		&self.inner
+	}
+
+	pub fn into_inner(self) -> S {
+		self.inner
+	}
+
+	pub fn config(&self) -> &RetryConfig {
+		&self.config
+	}
+
+	async fn retry<T, F: Future<Output = Result<T, S::Error>>>(&self, mut f: impl FnMut() -> F) -> Result<T, S::Error> {
+		let mut attempt = 0;
+		loop {
+			match f().await {
+				Ok(x) => return Ok(x),
+				Err(e) if attempt < self.config.max_retries => {
+					if let Some(on_retry) = &self.on_retry {
+						on_retry(attempt + 1, &e);
+					}
+					Delay::new(self.config.backoff(attempt)).await;
+					attempt += 1;
+				}
+				Err(e) => return Err(e)
+			}
+		}
+	}
+}
+
+impl<S: SpeechSynthesiser + fmt::Debug> fmt::Debug for RetryingSpeechSynthesiser<S> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("RetryingSpeechSynthesiser")
+			.field("inner", &self.inner)
+			.field("config", &self.config)
+			.finish_non_exhaustive()
+	}
+}
+
+impl<S: SpeechSynthesiser + Sync> SpeechSynthesiser for RetryingSpeechSynthesiser<S> {
+	type Error = S::Error;
+
+	fn negotiate_audio_format(&self, pref: &AudioFormatPreference) -> Option<AudioFormat> {
+		self.inner.negotiate_audio_format(pref)
+	}
+
+	fn capabilities(&self) -> SynthesiserCapabilities {
+		self.inner.capabilities()
+	}
+
+	fn validate_ssml(&self, input: &ssml::Speak<'_>) -> Result<(), Vec<SsmlValidationError>> {
+		self.inner.validate_ssml(input)
+	}
+
+	fn estimate_duration(&self, text: &str, config: &UtteranceConfig) -> Option<Duration> {
+		self.inner.estimate_duration(text, config)
+	}
+
+	fn supported_sample_rates(&self) -> &[u32] {
+		self.inner.supported_sample_rates()
+	}
+
+	fn supported_containers(&self) -> &[AudioContainer] {
+		self.inner.supported_containers()
+	}
+
+	async fn synthesise_ssml_stream(
+		&self,
+		input: &ssml::Speak<'_>,
+		audio_format: &AudioFormat,
+		config: &UtteranceConfig
+	) -> Result<impl UtteranceEventStream<Self::Error> + 'static, Self::Error> {
+		self.retry(|| self.inner.synthesise_ssml_stream(input, audio_format, config)).await
+	}
+
+	async fn synthesise_text_stream(
+		&self,
+		input: &str,
+		audio_format: &AudioFormat,
+		config: &UtteranceConfig
+	) -> Result<impl UtteranceEventStream<Self::Error> + 'static, Self::Error> {
+		self.retry(|| self.inner.synthesise_text_stream(input, audio_format, config)).await
+	}
+
+	async fn list_voices(&self) -> Result<Vec<VoiceInfo>, Self::Error> {
+		self.retry(|| self.inner.list_voices()).await
+	}
+
+	async fn supported_languages(&self) -> Result<Vec<LanguageInfo>, Self::Error> {
+		self.retry(|| self.inner.supported_languages()).await
+	}
+
+	fn health_check(&self) -> impl Future<Output = Result<(), Self::Error>> + Send {
+		self.inner.health_check()
+	}
+}
@@ -0,0 +1,101 @@
+use core::{future::Future, pin::Pin, time::Duration};
+use std::borrow::Cow;
+
+use crate::{
+	AudioContainer, AudioFormat, AudioFormatPreference, LanguageInfo, SpeechSynthesiser, SsmlValidationError, SynthesiserCapabilities, UtteranceConfig,
+	UtteranceEventStream, VoiceInfo
+};
+
+/// Wraps a [`SpeechSynthesiser`] to honour [`UtteranceConfig::preferred_voice_gender`],
+/// [`UtteranceConfig::preferred_voice_age`], & [`UtteranceConfig::prefer_gender_neutral`] for text input.
+///
+/// When either preference is set and no [voice](UtteranceConfig::voice) is configured, the synthesiser's voices are
+/// listed via [`SpeechSynthesiser::list_voices`], and the best match (per [`UtteranceConfig::select_voice`]) is used
+/// for synthesis. If no voice matches, the synthesiser's default voice is used. SSML input is passed through as-is,
+/// since voices are selected within the document itself.
+///
+/// Note that this includes [`UtteranceConfig::require_gender_neutral`]: the inner synthesiser's error type cannot
+/// express that no gender-neutral voice exists, so applications with strict requirements should check
+/// [`UtteranceConfig::select_voice`] against [`SpeechSynthesiser::list_voices`] before synthesis.
+#[derive(Debug, Clone)]
+pub struct VoiceSelectingSpeechSynthesiser<S>(S);
+
+impl<S: SpeechSynthesiser> VoiceSelectingSpeechSynthesiser<S> {
+	pub fn new(synthesiser: S) -> Self {
+		VoiceSelectingSpeechSynthesiser(synthesiser)
+	}
+
+	pub fn inner(&self) -> &S {
+		&self.0
+	}
+
+	pub fn into_inner(self) -> S {
+		self.0
+	}
+}
+
+impl<S: SpeechSynthesiser + Sync> SpeechSynthesiser for VoiceSelectingSpeechSynthesiser<S> {
+	type Error = S::Error;
+
+	fn negotiate_audio_format(&self, pref: &AudioFormatPreference) -> Option<AudioFormat> {
+		self.0.negotiate_audio_format(pref)
+	}
+
+	fn capabilities(&self) -> SynthesiserCapabilities {
+		self.0.capabilities()
+	}
+
+	fn validate_ssml(&self, input: &ssml::Speak<'_>) -> Result<(), Vec<SsmlValidationError>> {
+		self.0.validate_ssml(input)
+	}
+
+	fn estimate_duration(&self, text: &str, config: &UtteranceConfig) -> Option<Duration> {
+		self.0.estimate_duration(text, config)
+	}
+
+	fn supported_sample_rates(&self) -> &[u32] {
+		self.0.supported_sample_rates()
+	}
+
+	fn supported_containers(&self) -> &[AudioContainer] {
+		self.0.supported_containers()
+	}
+
+	fn synthesise_ssml_stream(
+		&self,
+		input: &ssml::Speak<'_>,
+		audio_format: &AudioFormat,
+		config: &UtteranceConfig
+	) -> impl Future<Output = Result<impl UtteranceEventStream<Self::Error> + 'static, Self::Error>> + Send {
+		self.0.synthesise_ssml_stream(input, audio_format, config)
+	}
+
+	async fn synthesise_text_stream(
+		&self,
+		input: &str,
+		audio_format: &AudioFormat,
+		config: &UtteranceConfig
+	) -> Result<impl UtteranceEventStream<Self::Error> + 'static, Self::Error> {
+		let mut config = Cow::Borrowed(config);
+		if config.voice.is_none() && (config.preferred_gender().is_some() || config.preferred_voice_age.is_some()) {
+			let voices = self.0.list_voices().await?;
+			if let Some(voice) = config.select_voice(&voices) {
+				config.to_mut().voice = Some(voice.name.clone());
+			}
+		}
+		let stream: Pin<Box<dyn UtteranceEventStream<Self::Error>>> = Box::pin(self.0.synthesise_text_stream(input, audio_format, &config).await?);
+		Ok(stream)
+	}
+
+	fn list_voices(&self) -> impl Future<Output = Result<Vec<VoiceInfo>, Self::Error>> + Send {
+		self.0.list_voices()
+	}
+
+	fn supported_languages(&self) -> impl Future<Output = Result<Vec<LanguageInfo>, Self::Error>> + Send {
+		self.0.supported_languages()
+	}
+
+	fn health_check(&self) -> impl Future<Output = Result<(), Self::Error>> + Send {
+		self.0.health_check()
+	}
+}
@@ -0,0 +1,327 @@
+use core::{
+	future::Future,
+	pin::Pin,
+	task::{Context, Poll}
+};
+
+use crate::{AudioContainer, AudioFormat, UtteranceEvent, UtteranceEventStream, audio::riff_data_offset};
+
+pub(crate) type SegmentEventStream<'a, E> = Pin<Box<dyn UtteranceEventStream<E> + 'a>>;
+type StartFuture<'a, E> = Pin<Box<dyn Future<Output = Result<SegmentEventStream<'a, E>, E>> + Send + 'a>>;
+
+/// The next segment, which is either still being requested, ready to stream, or failed to start.
+enum Pending<'a, E> {
+	Starting(StartFuture<'a, E>),
+	Ready(SegmentEventStream<'a, E>),
+	Failed(E)
+}
+
+/// An item yielded by [`SequentialSynthesis::poll_step`].
+pub(crate) enum Step {
+	/// An event from the current segment, with its timestamps relative to the beginning of the combined stream.
+	Event(UtteranceEvent),
+	/// The next segment has begun streaming, `at_millis` into the combined stream.
+	Started { at_millis: f32 },
+	/// No segment is streaming or requested; the next segment should be [started](SequentialSynthesis::start), or the
+	/// combined stream [finished](SequentialSynthesis::finish).
+	Idle
+}
+
+/// Yields the events of a series of separately synthesised segments (the chapters of a document, fragments of a
+/// streamed response, or sentences read from text) one after another, as if they were synthesised in a single call.
+///
+/// Each segment's [`UtteranceEvent::SynthesisStarted`] & [`UtteranceEvent::SynthesisCompleted`] events are discarded,
+/// and if the byte rate of the audio format is known, event timestamps are offset by the duration of the audio of all
+/// previous segments.
+///
+/// For [`AudioContainer::Riff`], only the first segment's header is kept, with its lengths replaced by a placeholder
+/// since the length of the combined audio isn't known in advance; the headers of later segments are stripped.
+pub(crate) struct SequentialSynthesis<'a, E> {
+	audio_format: &'a AudioFormat,
+	next: Option<Pending<'a, E>>,
+	current: Option<SegmentEventStream<'a, E>>,
+	/// The number of audio bytes emitted by previous segments, excluding headers.
+	audio_bytes: u64,
+	/// The number of audio bytes emitted by the current segment, excluding headers.
+	current_audio_bytes: u64,
+	/// The current segment's RIFF header, while it is still being received.
+	header: Option<Vec<u8>>,
+	/// Whether a RIFF header has been emitted, after which the headers of later segments are stripped.
+	emitted_header: bool,
+	finished: bool
+}
+
+// Segment streams are boxed, and an error is never pinned, so the sequence need not be structurally pinned.
+impl<E> Unpin for SequentialSynthesis<'_, E> {}
+
+impl<'a, E> SequentialSynthesis<'a, E> {
+	pub(crate) fn new(audio_format: &'a AudioFormat) -> Self {
+		SequentialSynthesis {
+			audio_format,
+			next: None,
+			current: None,
+			audio_bytes: 0,
+			current_audio_bytes: 0,
+			header: None,
+			emitted_header: false,
+			finished: false
+		}
+	}
+
+	/// Returns `true` if the next segment has already been requested.
+	pub(crate) fn has_next(&self) -> bool {
+		self.next.is_some()
+	}
+
+	/// Returns `true` if a segment is currently streaming.
+	pub(crate) fn is_streaming(&self) -> bool {
+		self.current.is_some()
+	}
+
+	pub(crate) fn is_finished(&self) -> bool {
+		self.finished
+	}
+
+	/// Ends the combined stream; [`SequentialSynthesis::poll_step`] will yield `None` from now on.
+	pub(crate) fn finish(&mut self) {
+		self.finished = true;
+	}
+
+	/// Requests the next segment, which is streamed once the current segment (if any) ends.
+	///
+	/// Only one segment may be requested ahead of the current one; see [`SequentialSynthesis::has_next`].
+	pub(crate) fn start(&mut self, request: impl Future<Output = Result<SegmentEventStream<'a, E>, E>> + Send + 'a) {
+		debug_assert!(self.next.is_none(), "the next segment has already been requested");
+		self.next = Some(Pending::Starting(Box::pin(request)));
+	}
+
+	/// The offset in milliseconds of the current segment from the beginning of the combined stream, or `0.0` if the
+	/// byte rate of the audio format is unknown.
+	pub(crate) fn offset_millis(&self) -> f32 {
+		self.audio_format
+			.duration_from_byte_count(self.audio_bytes)
+			.map_or(0.0, |offset| offset.as_secs_f32() * 1000.0)
+	}
+
+	/// Polls for the next step of the combined stream.
+	///
+	/// The next segment is requested while the current segment is streaming, though its events (and any error
+	/// requesting it) are only yielded once the current segment ends. After an error, the combined stream is finished.
+	pub(crate) fn poll_step(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Step, E>>> {
+		loop {
+			if self.finished {
+				return Poll::Ready(None);
+			}
+
+			if let Some(Pending::Starting(starting)) = self.next.as_mut() {
+				if let Poll::Ready(result) = starting.as_mut().poll(cx) {
+					self.next = Some(match result {
+						Ok(stream) => Pending::Ready(stream),
+						Err(e) => Pending::Failed(e)
+					});
+				}
+			}
+
+			if let Some(current) = self.current.as_mut() {
+				match current.as_mut().poll_next(cx) {
+					Poll::Ready(Some(Ok(UtteranceEvent::SynthesisStarted { .. } | UtteranceEvent::SynthesisCompleted { .. }))) => continue,
+					Poll::Ready(Some(Ok(UtteranceEvent::AudioChunk(chunk)))) => match self.take_audio(chunk) {
+						Some(chunk) => return Poll::Ready(Some(Ok(Step::Event(UtteranceEvent::AudioChunk(chunk))))),
+						None => continue
+					},
+					Poll::Ready(Some(Ok(mut event))) => {
+						event.offset_timestamps(self.offset_millis());
+						return Poll::Ready(Some(Ok(Step::Event(event))));
+					}
+					Poll::Ready(Some(Err(e))) => {
+						self.finished = true;
+						return Poll::Ready(Some(Err(e)));
+					}
+					Poll::Ready(None) => {
+						self.current = None;
+						self.audio_bytes += core::mem::take(&mut self.current_audio_bytes);
+						// If the segment ended before its RIFF header was fully received, pass what was received through
+						// as is rather than silently dropping it.
+						if let Some(partial) = self.header.take().filter(|header| !header.is_empty()) {
+							return Poll::Ready(Some(Ok(Step::Event(UtteranceEvent::AudioChunk(partial.into_boxed_slice())))));
+						}
+						continue;
+					}
+					Poll::Pending => return Poll::Pending
+				}
+			}
+
+			return match self.next.take() {
+				Some(Pending::Ready(stream)) => {
+					self.current = Some(stream);
+					self.header = matches!(self.audio_format.container(), AudioContainer::Riff(_)).then(Vec::new);
+					Poll::Ready(Some(Ok(Step::Started { at_millis: self.offset_millis() })))
+				}
+				Some(Pending::Failed(e)) => {
+					self.finished = true;
+					Poll::Ready(Some(Err(e)))
+				}
+				Some(starting @ Pending::Starting(_)) => {
+					self.next = Some(starting);
+					Poll::Pending
+				}
+				None => Poll::Ready(Some(Ok(Step::Idle)))
+			};
+		}
+	}
+
+	/// Counts a chunk of the current segment's audio, stripping its RIFF header if it isn't the first. Returns `None`
+	/// if there is no audio to emit yet, i.e. the header hasn't been fully received.
+	fn take_audio(&mut self, chunk: Box<[u8]>) -> Option<Box<[u8]>> {
+		let Some(mut audio) = self.header.take() else {
+			self.current_audio_bytes += chunk.len() as u64;
+			return Some(chunk);
+		};
+		audio.extend_from_slice(&chunk);
+		let Some(offset) = riff_data_offset(&audio) else {
+			self.header = Some(audio);
+			return None;
+		};
+		self.current_audio_bytes += (audio.len() - offset) as u64;
+		if self.emitted_header {
+			audio.drain(..offset);
+			return (!audio.is_empty()).then(|| audio.into_boxed_slice());
+		}
+
+		// The RIFF & data chunk lengths only cover the first segment.
+		audio[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+		audio[offset - 4..offset].copy_from_slice(&u32::MAX.to_le_bytes());
+		self.emitted_header = true;
+		Some(audio.into_boxed_slice())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use core::future::poll_fn;
+
+	use super::{SegmentEventStream, SequentialSynthesis, Step};
+	use crate::{
+		AudioChannels, AudioContainer, AudioEncoding, AudioFormat, SpeechSynthesiser, UtteranceConfig, UtteranceEvent,
+		testing::{MockError, MockSpeechSynthesiser}
+	};
+
+	/// Starts a segment for each response, and collects the combined steps.
+	async fn collect(synthesiser: &MockSpeechSynthesiser, format: &AudioFormat, segments: usize) -> Vec<Result<Step, MockError>> {
+		let config = UtteranceConfig::default();
+		let mut sequence = SequentialSynthesis::new(format);
+		let mut remaining = segments;
+		let mut steps = Vec::new();
+		loop {
+			match poll_fn(|cx| sequence.poll_step(cx)).await {
+				Some(Ok(Step::Idle)) if remaining > 0 => {
+					remaining -= 1;
+					let stream = synthesiser.synthesise_text_stream("", format, &config);
+					sequence.start(async move { Ok(Box::pin(stream.await?) as SegmentEventStream<'_, MockError>) });
+				}
+				Some(Ok(Step::Idle)) => sequence.finish(),
+				Some(step) => steps.push(step),
+				None => return steps
+			}
+		}
+	}
+
+	fn audio(steps: &[Result<Step, MockError>]) -> Vec<u8> {
+		steps
+			.iter()
+			.filter_map(|step| match step {
+				Ok(Step::Event(UtteranceEvent::AudioChunk(chunk))) => Some(&chunk[..]),
+				_ => None
+			})
+			.flatten()
+			.copied()
+			.collect()
+	}
+
+	#[tokio::test]
+	async fn offsets_timestamps_and_strips_sentinels() {
+		let format = AudioFormat::new(8000, AudioChannels::Mono, None, AudioContainer::Raw(AudioEncoding::PcmI16Le));
+		let synthesiser = MockSpeechSynthesiser::new();
+		for _ in 0..2 {
+			let events = vec![
+				UtteranceEvent::SynthesisStarted {
+					requested_at: std::time::Instant::now(),
+					request_id: None
+				},
+				UtteranceEvent::SsmlMark { at_millis: 10.0, mark: "mark".into() },
+			];
+			synthesiser.push_response(vec![0; 1600], events);
+		}
+
+		let steps = collect(&synthesiser, &format, 2).await;
+		let at_millis: Vec<f32> = steps
+			.iter()
+			.filter_map(|step| match step {
+				Ok(Step::Started { at_millis }) => Some(*at_millis),
+				Ok(Step::Event(UtteranceEvent::SsmlMark { at_millis, .. })) => Some(*at_millis),
+				Ok(Step::Event(UtteranceEvent::SynthesisStarted { .. })) => panic!("sentinel was not stripped"),
+				_ => None
+			})
+			.collect();
+		assert_eq!(at_millis, [0.0, 10.0, 100.0, 110.0]);
+	}
+
+	#[tokio::test]
+	async fn keeps_only_the_first_riff_header() {
+		let format = AudioFormat::new(8000, AudioChannels::Mono, None, AudioContainer::Riff(AudioEncoding::PcmI16Le));
+		let header = format.to_wav_header(1600).unwrap();
+		let synthesiser = MockSpeechSynthesiser::new();
+		for _ in 0..2 {
+			let mut audio = header.clone();
+			audio.extend(std::iter::repeat(1).take(1600));
+			synthesiser.push_response(audio, vec![UtteranceEvent::SsmlMark { at_millis: 0.0, mark: "mark".into() }]);
+		}
+
+		let steps = collect(&synthesiser, &format, 2).await;
+		let audio = audio(&steps);
+		assert_eq!(audio.len(), header.len() + 3200);
+		assert_eq!(&audio[..4], b"RIFF");
+		assert_eq!(&audio[4..8], &u32::MAX.to_le_bytes());
+		assert_eq!(&audio[header.len() - 4..header.len()], &u32::MAX.to_le_bytes());
+		assert!(audio[header.len()..].iter().all(|&byte| byte == 1));
+
+		// Header bytes don't count towards the offset of the next segment.
+		assert!(
+			steps
+				.iter()
+				.any(|step| matches!(step, Ok(Step::Started { at_millis }) if *at_millis == 100.0))
+		);
+	}
+
+	#[tokio::test]
+	async fn passes_through_truncated_riff_header() {
+		let format = AudioFormat::new(8000, AudioChannels::Mono, None, AudioContainer::Riff(AudioEncoding::PcmI16Le));
+		let header = format.to_wav_header(1600).unwrap();
+		let synthesiser = MockSpeechSynthesiser::new();
+		// The header is split across chunks, and the segment ends before it is complete.
+		synthesiser.push_response(Vec::new(), vec![UtteranceEvent::AudioChunk(header[..12].into()), UtteranceEvent::AudioChunk(header[12..20].into())]);
+		let mut response = header.clone();
+		response.extend(std::iter::repeat(1).take(1600));
+		synthesiser.push_response(response, Vec::new());
+
+		let audio = audio(&collect(&synthesiser, &format, 2).await);
+		assert_eq!(audio.len(), 20 + header.len() + 1600);
+		assert_eq!(&audio[..20], &header[..20]);
+		// The truncated header wasn't emitted as a header, so the next segment's header is kept.
+		assert_eq!(&audio[20..24], b"RIFF");
+		assert!(audio[20 + header.len()..].iter().all(|&byte| byte == 1));
+	}
+
+	#[tokio::test]
+	async fn error_finishes_the_sequence() {
+		let format = AudioFormat::new(8000, AudioChannels::Mono, None, AudioContainer::Raw(AudioEncoding::PcmI16Le));
+		let synthesiser = MockSpeechSynthesiser::new().with_error_at_chunk(0);
+		for _ in 0..2 {
+			synthesiser.push_response(vec![0; 1600], Vec::new());
+		}
+
+		let steps = collect(&synthesiser, &format, 2).await;
+		assert!(matches!(steps[..], [Ok(Step::Started { .. }), Err(MockError::Injected)]));
+		assert_eq!(synthesiser.remaining_responses(), 1);
+	}
+}
@@ -0,0 +1,55 @@
+//! (De)serializes byte buffers as base64 strings in human-readable formats, and as raw bytes otherwise.
+
+use core::fmt;
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use serde::{
+	Deserializer, Serializer,
+	de::{self, Visitor}
+};
+
+pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+	if serializer.is_human_readable() {
+		serializer.serialize_str(&STANDARD.encode(bytes))
+	} else {
+		serializer.serialize_bytes(bytes)
+	}
+}
+
+struct BytesVisitor;
+
+impl<'de> Visitor<'de> for BytesVisitor {
+	type Value = Box<[u8]>;
+
+	fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+		formatter.write_str("a base64 string or byte array")
+	}
+
+	fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+		STANDARD.decode(v).map(Vec::into_boxed_slice).map_err(E::custom)
+	}
+
+	fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+		Ok(v.into())
+	}
+
+	fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+		Ok(v.into_boxed_slice())
+	}
+
+	fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+		let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+		while let Some(byte) = seq.next_element()? {
+			bytes.push(byte);
+		}
+		Ok(bytes.into_boxed_slice())
+	}
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Box<[u8]>, D::Error> {
+	if deserializer.is_human_readable() {
+		deserializer.deserialize_str(BytesVisitor)
+	} else {
+		deserializer.deserialize_bytes(BytesVisitor)
+	}
+}
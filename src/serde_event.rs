@@ -0,0 +1,157 @@
+//! (De)serializes [`UtteranceEvent`]s, deserializing variants which are unknown to this version of the crate as
+//! [`UtteranceEvent::Unknown`].
+//!
+//! The derived implementations (generated as inherent functions with `#[serde(remote = "Self")]`) are used as-is,
+//! except that the deserializer is wrapped so that an unknown variant tag is replaced with `Unknown`, and the variant's
+//! content is skipped. Errors within known variants are still reported.
+
+use core::fmt;
+
+use serde::{
+	Deserialize, Deserializer, Serialize, Serializer,
+	de::{self, DeserializeSeed, EnumAccess, IgnoredAny, IntoDeserializer, VariantAccess, Visitor}
+};
+
+use crate::UtteranceEvent;
+
+const UNKNOWN: &str = "Unknown";
+
+impl Serialize for UtteranceEvent {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		UtteranceEvent::serialize(self, serializer)
+	}
+}
+
+impl<'de> Deserialize<'de> for UtteranceEvent {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		UtteranceEvent::deserialize(TolerantDeserializer(deserializer))
+	}
+}
+
+/// Forwards to the inner deserializer, wrapping the visitor passed to `deserialize_enum`.
+struct TolerantDeserializer<D>(D);
+
+impl<'de, D: Deserializer<'de>> Deserializer<'de> for TolerantDeserializer<D> {
+	type Error = D::Error;
+
+	fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+		self.0.deserialize_any(visitor)
+	}
+
+	fn deserialize_enum<V: Visitor<'de>>(self, name: &'static str, variants: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error> {
+		self.0.deserialize_enum(name, variants, TolerantVisitor { visitor, variants })
+	}
+
+	fn is_human_readable(&self) -> bool {
+		self.0.is_human_readable()
+	}
+
+	serde::forward_to_deserialize_any! {
+		bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option unit unit_struct
+		newtype_struct seq tuple tuple_struct map struct identifier ignored_any
+	}
+}
+
+struct TolerantVisitor<V> {
+	visitor: V,
+	variants: &'static [&'static str]
+}
+
+impl<'de, V: Visitor<'de>> Visitor<'de> for TolerantVisitor<V> {
+	type Value = V::Value;
+
+	fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+		self.visitor.expecting(formatter)
+	}
+
+	fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+		self.visitor.visit_enum(TolerantEnumAccess { data, variants: self.variants })
+	}
+}
+
+struct TolerantEnumAccess<A> {
+	data: A,
+	variants: &'static [&'static str]
+}
+
+/// A variant tag, which is either a variant's name or (in some binary formats) its index.
+enum Tag {
+	Name(String),
+	Index(u64)
+}
+
+impl<'de> Deserialize<'de> for Tag {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		struct TagVisitor;
+
+		impl Visitor<'_> for TagVisitor {
+			type Value = Tag;
+
+			fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+				formatter.write_str("a variant name or index")
+			}
+
+			fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+				Ok(Tag::Name(v.to_string()))
+			}
+
+			fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+				Ok(Tag::Name(String::from_utf8_lossy(v).into_owned()))
+			}
+
+			fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+				Ok(Tag::Index(v))
+			}
+		}
+
+		deserializer.deserialize_identifier(TagVisitor)
+	}
+}
+
+impl<'de, A: EnumAccess<'de>> EnumAccess<'de> for TolerantEnumAccess<A> {
+	type Error = A::Error;
+	type Variant = TolerantVariantAccess<A::Variant>;
+
+	fn variant_seed<S: DeserializeSeed<'de>>(self, seed: S) -> Result<(S::Value, Self::Variant), Self::Error> {
+		let (tag, variant) = self.data.variant::<Tag>()?;
+		let unknown = match &tag {
+			Tag::Name(name) => !self.variants.contains(&name.as_str()),
+			Tag::Index(index) => *index >= self.variants.len() as u64
+		};
+		let value = match tag {
+			_ if unknown => seed.deserialize(IntoDeserializer::<A::Error>::into_deserializer(UNKNOWN))?,
+			Tag::Name(name) => seed.deserialize(IntoDeserializer::<A::Error>::into_deserializer(name))?,
+			Tag::Index(index) => seed.deserialize(IntoDeserializer::<A::Error>::into_deserializer(index))?
+		};
+		Ok((value, TolerantVariantAccess { variant, unknown }))
+	}
+}
+
+struct TolerantVariantAccess<V> {
+	variant: V,
+	/// Whether the tag was unknown, in which case the variant's content is skipped.
+	unknown: bool
+}
+
+impl<'de, V: VariantAccess<'de>> VariantAccess<'de> for TolerantVariantAccess<V> {
+	type Error = V::Error;
+
+	fn unit_variant(self) -> Result<(), Self::Error> {
+		match self.unknown {
+			true => self.variant.newtype_variant::<IgnoredAny>().map(drop),
+			false => self.variant.unit_variant()
+		}
+	}
+
+	fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Self::Error> {
+		self.variant.newtype_variant_seed(seed)
+	}
+
+	fn tuple_variant<T: Visitor<'de>>(self, len: usize, visitor: T) -> Result<T::Value, Self::Error> {
+		self.variant.tuple_variant(len, visitor)
+	}
+
+	fn struct_variant<T: Visitor<'de>>(self, fields: &'static [&'static str], visitor: T) -> Result<T::Value, Self::Error> {
+		self.variant.struct_variant(fields, visitor)
+	}
+}
@@ -0,0 +1,643 @@
+//! Utilities for consuming & adapting [`UtteranceEventStream`]s.
+
+use core::{
+	future::{Future, poll_fn},
+	pin::{Pin, pin},
+	task::{Context, Poll}
+};
+use std::{collections::VecDeque, time::Instant};
+
+use futures_core::Stream;
+use pin_project_lite::pin_project;
+
+#[cfg(feature = "tokio")]
+use crate::SynthesisError;
+use crate::{AudioFormat, SentenceBoundary, SsmlMarkEvent, UtteranceEvent, UtteranceEventStream, WordBoundary};
+
+mod fanout;
+mod merge;
+mod pause;
+mod record;
+mod resample;
+mod sentence;
+mod silence;
+mod split;
+pub use self::{
+	fanout::{DEFAULT_FANOUT_CAPACITY, FanoutStream, OverflowPolicy, fanout, fanout_with_policy},
+	merge::merge_concurrent,
+	pause::{DEFAULT_SILENCE_THRESHOLD_MILLIS, detect_pauses},
+	record::{RecordedUtterance, record_to_vec},
+	resample::resample_visemes_to_fps,
+	sentence::{SentenceChunk, chunk_by_sentence},
+	silence::with_silence_detection,
+	split::{AudioStream, DEFAULT_SPLIT_CAPACITY, MetadataStream, split, split_with_capacity}
+};
+
+pub(crate) async fn next<S: Stream + ?Sized>(mut stream: Pin<&mut S>) -> Option<S::Item> {
+	poll_fn(|cx| stream.as_mut().poll_next(cx)).await
+}
+
+/// Writes the payloads of all [`UtteranceEvent::AudioChunk`]s in `stream` to `writer`, returning the number of bytes
+/// written.
+#[cfg(feature = "tokio")]
+pub(crate) async fn write_audio<E, W: tokio::io::AsyncWrite + Unpin>(stream: impl UtteranceEventStream<E>, mut writer: W) -> Result<u64, SynthesisError<E>> {
+	let mut stream = pin!(stream);
+	let mut written = 0;
+	while let Some(event) = next(stream.as_mut()).await {
+		let UtteranceEvent::AudioChunk(chunk) = event.map_err(SynthesisError::Synthesiser)? else {
+			continue;
+		};
+		let mut buf = &chunk[..];
+		while !buf.is_empty() {
+			let n = poll_fn(|cx| Pin::new(&mut writer).poll_write(cx, buf))
+				.await
+				.map_err(SynthesisError::Io)?;
+			if n == 0 {
+				return Err(SynthesisError::Io(std::io::ErrorKind::WriteZero.into()));
+			}
+			buf = &buf[n..];
+		}
+		written += chunk.len() as u64;
+	}
+	poll_fn(|cx| Pin::new(&mut writer).poll_flush(cx)).await.map_err(SynthesisError::Io)?;
+	Ok(written)
+}
+
+/// Extension methods for [`UtteranceEventStream`]s.
+pub trait UtteranceEventStreamExt<E>: UtteranceEventStream<E> {
+	/// Drives the stream to completion, concatenating the payloads of all [`UtteranceEvent::AudioChunk`]s in order.
+	///
+	/// All other events are discarded. Returns early if the stream yields an error.
+	fn collect_audio(self) -> impl Future<Output = Result<Vec<u8>, E>> + Send
+	where
+		Self: Sized
+	{
+		async move {
+			let mut stream = pin!(self);
+			let mut audio = Vec::new();
+			while let Some(event) = next(stream.as_mut()).await {
+				if let UtteranceEvent::AudioChunk(chunk) = event? {
+					audio.extend_from_slice(&chunk);
+				}
+			}
+			Ok(audio)
+		}
+	}
+
+	/// Drives the stream to completion, collecting all [`UtteranceEvent::WordBoundary`] events in order.
+	///
+	/// All other events are discarded. Returns early if the stream yields an error.
+	fn collect_word_boundaries(self) -> impl Future<Output = Result<Vec<WordBoundary>, E>> + Send
+	where
+		Self: Sized
+	{
+		async move {
+			let mut stream = pin!(self);
+			let mut boundaries = Vec::new();
+			while let Some(event) = next(stream.as_mut()).await {
+				if let UtteranceEvent::WordBoundary {
+					from_millis,
+					to_millis,
+					text,
+					confidence,
+					phonetic
+				} = event?
+				{
+					boundaries.push(WordBoundary {
+						from_millis,
+						to_millis,
+						text,
+						confidence,
+						phonetic
+					});
+				}
+			}
+			Ok(boundaries)
+		}
+	}
+
+	/// Drives the stream to completion, reconstructing the spoken text from its [`UtteranceEvent::WordBoundary`]
+	/// events.
+	///
+	/// Words are joined by a single space in the order they were emitted. This requires word boundary events to be
+	/// enabled with
+	/// [`UtteranceConfig::with_emit_word_boundary_events`](crate::UtteranceConfig::with_emit_word_boundary_events).
+	/// For the timing of each word, i.e. for karaoke-style highlighting, see
+	/// [`UtteranceEventStreamExt::collect_word_boundaries`].
+	fn collect_transcript(self) -> impl Future<Output = Result<String, E>> + Send
+	where
+		Self: Sized
+	{
+		async move {
+			let mut stream = pin!(self);
+			let mut transcript = String::new();
+			while let Some(event) = next(stream.as_mut()).await {
+				if let UtteranceEvent::WordBoundary { text, .. } = event? {
+					let text = text.trim();
+					if text.is_empty() {
+						continue;
+					}
+					if !transcript.is_empty() {
+						transcript.push(' ');
+					}
+					transcript.push_str(text);
+				}
+			}
+			Ok(transcript)
+		}
+	}
+
+	/// Drives the stream to completion, collecting all [`UtteranceEvent::SentenceBoundary`] events in order.
+	///
+	/// All other events are discarded. Returns early if the stream yields an error.
+	fn collect_sentence_boundaries(self) -> impl Future<Output = Result<Vec<SentenceBoundary>, E>> + Send
+	where
+		Self: Sized
+	{
+		async move {
+			let mut stream = pin!(self);
+			let mut boundaries = Vec::new();
+			while let Some(event) = next(stream.as_mut()).await {
+				if let UtteranceEvent::SentenceBoundary { from_millis, to_millis, text } = event? {
+					boundaries.push(SentenceBoundary { from_millis, to_millis, text });
+				}
+			}
+			Ok(boundaries)
+		}
+	}
+
+	/// Drives the stream to completion, collecting all [`UtteranceEvent::SsmlMark`] events, sorted by their position
+	/// in the audio.
+	///
+	/// All other events are discarded. Returns early if the stream yields an error. This requires mark events to be
+	/// enabled with [`UtteranceConfig::with_emit_ssml_marks`](crate::UtteranceConfig::with_emit_ssml_marks).
+	fn collect_ssml_marks(self) -> impl Future<Output = Result<Vec<SsmlMarkEvent>, E>> + Send
+	where
+		Self: Sized
+	{
+		async move {
+			let mut stream = pin!(self);
+			let mut marks = Vec::new();
+			while let Some(event) = next(stream.as_mut()).await {
+				if let UtteranceEvent::SsmlMark { at_millis, mark } = event? {
+					marks.push(SsmlMarkEvent { at_millis, mark });
+				}
+			}
+			marks.sort_by(|a, b| a.at_millis.total_cmp(&b.at_millis));
+			Ok(marks)
+		}
+	}
+
+	/// Drives the stream until the [`UtteranceEvent::SsmlMark`] named `name` is reached, returning its position in
+	/// milliseconds, or `None` if the stream ends without reaching it.
+	///
+	/// All events before the mark are discarded, and the rest of the stream is dropped. Returns early if the stream
+	/// yields an error.
+	fn wait_for_mark<'a>(self, name: &'a str) -> impl Future<Output = Result<Option<f32>, E>> + Send + 'a
+	where
+		Self: Sized + 'a
+	{
+		async move {
+			let mut stream = pin!(self);
+			while let Some(event) = next(stream.as_mut()).await {
+				if let UtteranceEvent::SsmlMark { at_millis, mark } = event? {
+					if *mark == *name {
+						return Ok(Some(at_millis));
+					}
+				}
+			}
+			Ok(None)
+		}
+	}
+}
+
+impl<E, T: UtteranceEventStream<E> + ?Sized> UtteranceEventStreamExt<E> for T {}
+
+pin_project! {
+	struct MapAudioChunks<S, F> {
+		#[pin]
+		inner: S,
+		f: F
+	}
+}
+
+impl<E, S: Stream<Item = Result<UtteranceEvent, E>>, F: FnMut(Box<[u8]>) -> Box<[u8]>> Stream for MapAudioChunks<S, F> {
+	type Item = Result<UtteranceEvent, E>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let this = self.project();
+		match this.inner.poll_next(cx) {
+			Poll::Ready(Some(Ok(UtteranceEvent::AudioChunk(chunk)))) => Poll::Ready(Some(Ok(UtteranceEvent::AudioChunk((this.f)(chunk))))),
+			other => other
+		}
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.inner.size_hint()
+	}
+}
+
+/// Adapts an [`UtteranceEventStream`] to pass the payload of each [`UtteranceEvent::AudioChunk`] through `f`, i.e. to
+/// apply audio effects as audio is streamed. All other events are passed through unchanged.
+///
+/// Note that chunk boundaries are determined by the synthesiser, and may not align to a whole number of samples.
+pub fn map_audio_chunks<E, F>(stream: impl UtteranceEventStream<E>, f: F) -> impl UtteranceEventStream<E>
+where
+	F: FnMut(Box<[u8]>) -> Box<[u8]> + Send
+{
+	MapAudioChunks { inner: stream, f }
+}
+
+/// Like [`map_audio_chunks`], but also passes the stream's [`AudioFormat`] to `f`.
+pub fn map_audio_chunks_with_format<E, F>(stream: impl UtteranceEventStream<E>, audio_format: AudioFormat, mut f: F) -> impl UtteranceEventStream<E>
+where
+	F: FnMut(&AudioFormat, Box<[u8]>) -> Box<[u8]> + Send
+{
+	map_audio_chunks(stream, move |chunk| f(&audio_format, chunk))
+}
+
+pin_project! {
+	struct FilterEvents<S, F> {
+		#[pin]
+		inner: S,
+		pred: F
+	}
+}
+
+impl<E, S: Stream<Item = Result<UtteranceEvent, E>>, F: FnMut(&UtteranceEvent) -> bool> Stream for FilterEvents<S, F> {
+	type Item = Result<UtteranceEvent, E>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let mut this = self.project();
+		loop {
+			match this.inner.as_mut().poll_next(cx) {
+				Poll::Ready(Some(Ok(event))) if !(this.pred)(&event) => continue,
+				other => return other
+			}
+		}
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(0, self.inner.size_hint().1)
+	}
+}
+
+/// Adapts an [`UtteranceEventStream`] to only pass through events for which `pred` returns `true`. Errors are always
+/// passed through.
+///
+/// See [`audio_only`], [`metadata_only`], and [`boundaries_only`] for common predicates.
+pub fn filter_events<E, F>(stream: impl UtteranceEventStream<E>, pred: F) -> impl UtteranceEventStream<E>
+where
+	F: FnMut(&UtteranceEvent) -> bool + Send
+{
+	FilterEvents { inner: stream, pred }
+}
+
+/// A predicate for [`filter_events`] which only passes through [`UtteranceEvent::AudioChunk`]s.
+pub fn audio_only() -> fn(&UtteranceEvent) -> bool {
+	UtteranceEvent::is_audio
+}
+
+/// A predicate for [`filter_events`] which passes through all events except [`UtteranceEvent::AudioChunk`]s.
+pub fn metadata_only() -> fn(&UtteranceEvent) -> bool {
+	|event| !event.is_audio()
+}
+
+/// A predicate for [`filter_events`] which only passes through word, sentence, and paragraph boundaries. See
+/// [`UtteranceEvent::is_boundary`].
+pub fn boundaries_only() -> fn(&UtteranceEvent) -> bool {
+	UtteranceEvent::is_boundary
+}
+
+pin_project! {
+	struct SkipUntilAudio<S, E> {
+		#[pin]
+		inner: S,
+		// Events received before the first audio chunk; `None` once it has been received.
+		preamble: Option<VecDeque<UtteranceEvent>>,
+		// Buffered events waiting to be yielded.
+		queued: VecDeque<UtteranceEvent>,
+		// An error received before the first audio chunk, yielded after the buffered events.
+		error: Option<E>,
+		// Whether the inner stream has ended, after which it must not be polled again.
+		done: bool
+	}
+}
+
+impl<E, S: Stream<Item = Result<UtteranceEvent, E>>> Stream for SkipUntilAudio<S, E> {
+	type Item = Result<UtteranceEvent, E>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let mut this = self.project();
+		if let Some(event) = this.queued.pop_front() {
+			return Poll::Ready(Some(Ok(event)));
+		}
+		if let Some(e) = this.error.take() {
+			return Poll::Ready(Some(Err(e)));
+		}
+		if *this.done {
+			return Poll::Ready(None);
+		}
+		let Some(preamble) = this.preamble.as_mut() else {
+			let item = this.inner.poll_next(cx);
+			*this.done = matches!(item, Poll::Ready(None));
+			return item;
+		};
+		loop {
+			match this.inner.as_mut().poll_next(cx) {
+				// The leading sentinel is not metadata, and is yielded immediately.
+				Poll::Ready(Some(Ok(event @ UtteranceEvent::SynthesisStarted { .. }))) if preamble.is_empty() => return Poll::Ready(Some(Ok(event))),
+				Poll::Ready(Some(Ok(event))) if !event.is_audio() => preamble.push_back(event),
+				// The stream may also end or fail before any audio, in which case the buffered events are yielded first.
+				Poll::Ready(item) => {
+					*this.done = item.is_none();
+					*this.queued = this.preamble.take().unwrap_or_default();
+					let item = match item {
+						Some(Err(e)) if !this.queued.is_empty() => {
+							*this.error = Some(e);
+							None
+						}
+						item => item
+					};
+					return match item {
+						Some(item) => Poll::Ready(Some(item)),
+						None => Poll::Ready(this.queued.pop_front().map(Ok).or_else(|| this.error.take().map(Err)))
+					};
+				}
+				Poll::Pending => return Poll::Pending
+			}
+		}
+	}
+}
+
+/// Adapts an [`UtteranceEventStream`] to yield its first [`UtteranceEvent::AudioChunk`] as soon as it is received,
+/// ahead of any metadata events emitted before it, i.e. so that latency-sensitive applications can begin buffering
+/// audio immediately with backends which emit a burst of metadata before any audio.
+///
+/// Events received before the first audio chunk are buffered, and yielded in order immediately after it. The leading
+/// [`UtteranceEvent::SynthesisStarted`] sentinel is yielded immediately, and all events after the first audio chunk are
+/// passed through unchanged. If the stream ends or yields an error before any audio, the buffered events are yielded
+/// first; no events are dropped.
+pub fn skip_until_audio<E: Send>(stream: impl UtteranceEventStream<E>) -> impl UtteranceEventStream<E> {
+	SkipUntilAudio {
+		inner: stream,
+		preamble: Some(VecDeque::new()),
+		queued: VecDeque::new(),
+		error: None,
+		done: false
+	}
+}
+
+pin_project! {
+	struct LimitAudioChunkSize<S> {
+		#[pin]
+		inner: S,
+		max: usize,
+		pending: Option<(Box<[u8]>, usize)>
+	}
+}
+
+impl<E, S: Stream<Item = Result<UtteranceEvent, E>>> Stream for LimitAudioChunkSize<S> {
+	type Item = Result<UtteranceEvent, E>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let this = self.project();
+		if let Some((chunk, offset)) = this.pending.take() {
+			return Poll::Ready(Some(Ok(UtteranceEvent::AudioChunk(take_piece(chunk, offset, *this.max, this.pending)))));
+		}
+		match this.inner.poll_next(cx) {
+			Poll::Ready(Some(Ok(UtteranceEvent::AudioChunk(chunk)))) if chunk.len() > *this.max => {
+				Poll::Ready(Some(Ok(UtteranceEvent::AudioChunk(take_piece(chunk, 0, *this.max, this.pending)))))
+			}
+			other => other
+		}
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let pending = usize::from(self.pending.is_some());
+		let (lower, _) = self.inner.size_hint();
+		(lower.saturating_add(pending), None)
+	}
+}
+
+fn take_piece(chunk: Box<[u8]>, offset: usize, max: usize, pending: &mut Option<(Box<[u8]>, usize)>) -> Box<[u8]> {
+	let end = offset.saturating_add(max).min(chunk.len());
+	let piece = chunk[offset..end].into();
+	if end < chunk.len() {
+		*pending = Some((chunk, end));
+	}
+	piece
+}
+
+/// Adapts an [`UtteranceEventStream`] to split each [`UtteranceEvent::AudioChunk`] larger than `max` bytes into
+/// consecutive chunks of at most `max` bytes. All other events are passed through unchanged.
+///
+/// A `max` of `0` is treated as `1`. See also
+/// [`UtteranceConfig::max_audio_chunk_size`](crate::UtteranceConfig::max_audio_chunk_size).
+pub fn limit_audio_chunk_size<E>(stream: impl UtteranceEventStream<E>, max: usize) -> impl UtteranceEventStream<E> {
+	LimitAudioChunkSize {
+		inner: stream,
+		max: max.max(1),
+		pending: None
+	}
+}
+
+pin_project! {
+	struct WithSynthesisEvents<S> {
+		#[pin]
+		inner: S,
+		requested_at: Instant,
+		request_id: Option<Box<str>>,
+		started: bool,
+		finished: bool,
+		total_audio_bytes: u64
+	}
+}
+
+impl<E, S: Stream<Item = Result<UtteranceEvent, E>>> Stream for WithSynthesisEvents<S> {
+	type Item = Result<UtteranceEvent, E>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let this = self.project();
+		if *this.finished {
+			return Poll::Ready(None);
+		}
+		if !*this.started {
+			*this.started = true;
+			return Poll::Ready(Some(Ok(UtteranceEvent::SynthesisStarted {
+				requested_at: *this.requested_at,
+				request_id: this.request_id.take()
+			})));
+		}
+		match this.inner.poll_next(cx) {
+			Poll::Ready(Some(Ok(event))) => {
+				if let UtteranceEvent::AudioChunk(chunk) = &event {
+					*this.total_audio_bytes += chunk.len() as u64;
+				}
+				Poll::Ready(Some(Ok(event)))
+			}
+			Poll::Ready(Some(Err(e))) => {
+				*this.finished = true;
+				Poll::Ready(Some(Err(e)))
+			}
+			Poll::Ready(None) => {
+				*this.finished = true;
+				Poll::Ready(Some(Ok(UtteranceEvent::SynthesisCompleted {
+					duration_millis: this.requested_at.elapsed().as_secs_f32() * 1000.0,
+					total_audio_bytes: *this.total_audio_bytes
+				})))
+			}
+			Poll::Pending => Poll::Pending
+		}
+	}
+}
+
+/// Adapts an [`UtteranceEventStream`] to be bracketed by the [`UtteranceEvent::SynthesisStarted`] &
+/// [`UtteranceEvent::SynthesisCompleted`] sentinel events, for synthesisers which do not emit them natively.
+///
+/// `requested_at` should be the time at which synthesis was requested, i.e. before the request was sent to a remote
+/// service. If the stream yields an error, the stream ends after the error without emitting
+/// [`UtteranceEvent::SynthesisCompleted`].
+pub fn with_synthesis_events<E>(stream: impl UtteranceEventStream<E>, requested_at: Instant) -> impl UtteranceEventStream<E> {
+	with_synthesis_events_for_request(stream, requested_at, None)
+}
+
+/// Like [`with_synthesis_events`], but also echoes `request_id` back in the [`UtteranceEvent::SynthesisStarted`] event.
+/// See [`UtteranceConfig::request_id`](crate::UtteranceConfig::request_id).
+pub fn with_synthesis_events_for_request<E>(
+	stream: impl UtteranceEventStream<E>,
+	requested_at: Instant,
+	request_id: Option<Box<str>>
+) -> impl UtteranceEventStream<E> {
+	WithSynthesisEvents {
+		inner: stream,
+		requested_at,
+		request_id,
+		started: false,
+		finished: false,
+		total_audio_bytes: 0
+	}
+}
+
+pin_project! {
+	struct WithAudioFormatTag<S> {
+		#[pin]
+		inner: S,
+		audio_format: Option<AudioFormat>
+	}
+}
+
+impl<E, S: Stream<Item = Result<UtteranceEvent, E>>> Stream for WithAudioFormatTag<S> {
+	type Item = Result<UtteranceEvent, E>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let this = self.project();
+		if let Some(audio_format) = this.audio_format.take() {
+			return Poll::Ready(Some(Ok(UtteranceEvent::AudioFormat(audio_format))));
+		}
+		this.inner.poll_next(cx)
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let extra = self.audio_format.is_some() as usize;
+		let (lower, upper) = self.inner.size_hint();
+		(lower.saturating_add(extra), upper.and_then(|upper| upper.checked_add(extra)))
+	}
+}
+
+/// Adapts an [`UtteranceEventStream`] to emit an [`UtteranceEvent::AudioFormat`] with `audio_format` before any other
+/// event, i.e. before serializing the stream to a file or socket whose reader does not otherwise know the format.
+pub fn with_audio_format_tag<E>(stream: impl UtteranceEventStream<E>, audio_format: AudioFormat) -> impl UtteranceEventStream<E> {
+	WithAudioFormatTag {
+		inner: stream,
+		audio_format: Some(audio_format)
+	}
+}
+
+pin_project! {
+	struct WithTimestampOffset<S> {
+		#[pin]
+		inner: S,
+		offset_millis: f32
+	}
+}
+
+impl<E, S: Stream<Item = Result<UtteranceEvent, E>>> Stream for WithTimestampOffset<S> {
+	type Item = Result<UtteranceEvent, E>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let this = self.project();
+		this.inner.poll_next(cx).map_ok(|mut event| {
+			event.offset_timestamps(*this.offset_millis);
+			event
+		})
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.inner.size_hint()
+	}
+}
+
+/// Adapts an [`UtteranceEventStream`] to shift the timestamps of every event by `offset_millis`, i.e. when
+/// concatenating the audio of multiple utterances. See [`UtteranceEvent::offset_timestamps`].
+pub fn with_timestamp_offset<E>(stream: impl UtteranceEventStream<E>, offset_millis: f32) -> impl UtteranceEventStream<E> {
+	WithTimestampOffset { inner: stream, offset_millis }
+}
+
+#[cfg(test)]
+mod tests {
+	use core::{
+		pin::{Pin, pin},
+		task::{Context, Poll}
+	};
+	use std::collections::VecDeque;
+
+	use futures_core::Stream;
+
+	use super::{next, skip_until_audio};
+	use crate::{UtteranceEvent, testing::MockError};
+
+	/// A stream which panics if it is polled again after ending.
+	struct Unfused(Option<VecDeque<UtteranceEvent>>);
+
+	impl Stream for Unfused {
+		type Item = Result<UtteranceEvent, MockError>;
+
+		fn poll_next(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+			let events = self.0.as_mut().expect("polled after ending");
+			match events.pop_front() {
+				Some(event) => Poll::Ready(Some(Ok(event))),
+				None => {
+					self.0 = None;
+					Poll::Ready(None)
+				}
+			}
+		}
+	}
+
+	fn mark(name: &str) -> UtteranceEvent {
+		UtteranceEvent::SsmlMark { at_millis: 0.0, mark: name.into() }
+	}
+
+	#[tokio::test]
+	async fn skip_until_audio_yields_audio_first() {
+		let events = VecDeque::from([mark("a"), mark("b"), UtteranceEvent::AudioChunk(vec![0; 4].into_boxed_slice()), mark("c")]);
+		let mut stream = pin!(skip_until_audio(Unfused(Some(events))));
+		assert!(matches!(next(stream.as_mut()).await, Some(Ok(UtteranceEvent::AudioChunk(_)))));
+		for expected in ["a", "b", "c"] {
+			assert!(matches!(next(stream.as_mut()).await, Some(Ok(UtteranceEvent::SsmlMark { mark, .. })) if &*mark == expected));
+		}
+		assert!(next(stream.as_mut()).await.is_none());
+		assert!(next(stream.as_mut()).await.is_none());
+	}
+
+	#[tokio::test]
+	async fn skip_until_audio_does_not_poll_ended_stream() {
+		let mut stream = pin!(skip_until_audio(Unfused(Some(VecDeque::from([mark("a"), mark("b")])))));
+		for expected in ["a", "b"] {
+			assert!(matches!(next(stream.as_mut()).await, Some(Ok(UtteranceEvent::SsmlMark { mark, .. })) if &*mark == expected));
+		}
+		assert!(next(stream.as_mut()).await.is_none());
+		assert!(next(stream.as_mut()).await.is_none());
+	}
+}
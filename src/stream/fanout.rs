@@ -0,0 +1,195 @@
+use core::{
+	pin::Pin,
+	task::{Context, Poll, Waker}
+};
+use std::{
+	collections::VecDeque,
+	sync::{Arc, Mutex, MutexGuard},
+	task::Wake
+};
+
+use futures_core::Stream;
+
+use crate::{UtteranceEvent, UtteranceEventStream};
+
+/// The default number of items buffered for each output of a [`fanout`] stream.
+pub const DEFAULT_FANOUT_CAPACITY: usize = 64;
+
+/// Determines what happens when an output of a [`fanout`] stream falls behind, and its buffer fills up.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum OverflowPolicy {
+	/// Stop pulling from the source stream until the slow output catches up. No events are lost, but all outputs can
+	/// only progress as fast as the slowest output.
+	#[default]
+	Block,
+	/// Discard the oldest buffered event of the slow output to make room for the new event.
+	DropOldest,
+	/// Discard the new event for the slow output, keeping the events already buffered.
+	DropNewest
+}
+
+struct FanoutWakers(Box<[Mutex<Option<Waker>>]>);
+
+impl FanoutWakers {
+	fn register(&self, index: usize, waker: &Waker) {
+		let mut slot = self.0[index].lock().unwrap_or_else(|e| e.into_inner());
+		match slot.as_ref() {
+			Some(w) if w.will_wake(waker) => {}
+			_ => *slot = Some(waker.clone())
+		}
+	}
+
+	fn wake_except(&self, index: usize) {
+		for (i, slot) in self.0.iter().enumerate() {
+			if i == index {
+				continue;
+			}
+			let waker = slot.lock().unwrap_or_else(|e| e.into_inner()).take();
+			if let Some(waker) = waker {
+				waker.wake();
+			}
+		}
+	}
+}
+
+impl Wake for FanoutWakers {
+	fn wake(self: Arc<Self>) {
+		self.wake_by_ref();
+	}
+
+	fn wake_by_ref(self: &Arc<Self>) {
+		self.wake_except(usize::MAX);
+	}
+}
+
+struct FanoutState<E> {
+	source: Option<Pin<Box<dyn UtteranceEventStream<E>>>>,
+	/// The buffered events of each output, or `None` if the output has been dropped.
+	queues: Vec<Option<VecDeque<Result<UtteranceEvent, E>>>>
+}
+
+struct FanoutShared<E> {
+	state: Mutex<FanoutState<E>>,
+	wakers: Arc<FanoutWakers>,
+	capacity: usize,
+	policy: OverflowPolicy
+}
+
+impl<E> FanoutShared<E> {
+	fn lock(&self) -> MutexGuard<'_, FanoutState<E>> {
+		self.state.lock().unwrap_or_else(|e| e.into_inner())
+	}
+
+	fn drop_output(&self, index: usize) {
+		let mut state = self.lock();
+		state.queues[index] = None;
+		if state.queues.iter().all(Option::is_none) {
+			state.source = None;
+		}
+		drop(state);
+		self.wakers.wake_except(index);
+	}
+}
+
+impl<E: Clone> FanoutShared<E> {
+	/// Pulls items from the source stream until an item is available for output `index`.
+	fn poll_output(&self, index: usize, cx: &mut Context<'_>) -> Poll<Option<Result<UtteranceEvent, E>>> {
+		let mut state = self.lock();
+		loop {
+			if let Some(item) = state.queues[index].as_mut().and_then(VecDeque::pop_front) {
+				// We've freed up space in our queue; other outputs may be waiting on us.
+				if self.policy == OverflowPolicy::Block {
+					self.wakers.wake_except(index);
+				}
+				return Poll::Ready(Some(item));
+			}
+
+			self.wakers.register(index, cx.waker());
+
+			let state = &mut *state;
+			let Some(source) = state.source.as_mut() else {
+				return Poll::Ready(None);
+			};
+			if self.policy == OverflowPolicy::Block && state.queues.iter().flatten().any(|queue| queue.len() >= self.capacity) {
+				// Another output's buffer is full; wait for it to catch up.
+				return Poll::Pending;
+			}
+
+			let waker = Waker::from(Arc::clone(&self.wakers));
+			match source.as_mut().poll_next(&mut Context::from_waker(&waker)) {
+				Poll::Ready(Some(item)) => {
+					for queue in state.queues.iter_mut().flatten() {
+						if queue.len() >= self.capacity {
+							match self.policy {
+								OverflowPolicy::DropOldest => {
+									queue.pop_front();
+								}
+								OverflowPolicy::DropNewest => continue,
+								OverflowPolicy::Block => {}
+							}
+						}
+						queue.push_back(item.clone());
+					}
+					self.wakers.wake_except(index);
+				}
+				Poll::Ready(None) => {
+					state.source = None;
+					self.wakers.wake_except(index);
+				}
+				Poll::Pending => return Poll::Pending
+			}
+		}
+	}
+}
+
+/// One output of a [`fanout`] stream.
+pub struct FanoutStream<E> {
+	shared: Arc<FanoutShared<E>>,
+	index: usize
+}
+
+impl<E: Clone> Stream for FanoutStream<E> {
+	type Item = Result<UtteranceEvent, E>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		self.shared.poll_output(self.index, cx)
+	}
+}
+
+impl<E> Drop for FanoutStream<E> {
+	fn drop(&mut self) {
+		self.shared.drop_output(self.index);
+	}
+}
+
+/// Broadcasts an [`UtteranceEventStream`] to `n` outputs, each of which receives every event (and error) yielded by
+/// the source stream, i.e. to drive both an audio player & a lip-sync animation from the same synthesis.
+///
+/// All outputs drive the source stream cooperatively; events are buffered for each output until it is polled, up to
+/// [`DEFAULT_FANOUT_CAPACITY`] events, after which polling blocks until the slowest output catches up. See
+/// [`fanout_with_policy`] to configure the buffer size & overflow behaviour. Dropping an output will discard its events
+/// without buffering.
+pub fn fanout<E: Clone + Send + 'static>(stream: impl UtteranceEventStream<E> + 'static, n: usize) -> Vec<FanoutStream<E>> {
+	fanout_with_policy(stream, n, DEFAULT_FANOUT_CAPACITY, OverflowPolicy::Block)
+}
+
+/// Broadcasts an [`UtteranceEventStream`] to `n` outputs, buffering up to `capacity` events for each output. Once an
+/// output's buffer is full, new events are handled according to `policy`. See [`fanout`].
+pub fn fanout_with_policy<E: Clone + Send + 'static>(
+	stream: impl UtteranceEventStream<E> + 'static,
+	n: usize,
+	capacity: usize,
+	policy: OverflowPolicy
+) -> Vec<FanoutStream<E>> {
+	let shared = Arc::new(FanoutShared {
+		state: Mutex::new(FanoutState {
+			source: Some(Box::pin(stream)),
+			queues: (0..n).map(|_| Some(VecDeque::new())).collect()
+		}),
+		wakers: Arc::new(FanoutWakers((0..n).map(|_| Mutex::new(None)).collect())),
+		capacity: capacity.max(1),
+		policy
+	});
+	(0..n).map(|index| FanoutStream { shared: Arc::clone(&shared), index }).collect()
+}
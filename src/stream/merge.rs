@@ -0,0 +1,142 @@
+use core::{
+	cmp::{Ordering, Reverse},
+	pin::Pin,
+	task::{Context, Poll}
+};
+use std::{collections::BinaryHeap, time::Instant};
+
+use futures_core::Stream;
+
+use crate::{UtteranceEvent, UtteranceEventStream};
+
+/// The next event of one of the merged streams, ordered by timestamp and then by the index of its stream.
+struct Head {
+	at_millis: f32,
+	index: usize,
+	event: UtteranceEvent
+}
+
+impl PartialEq for Head {
+	fn eq(&self, other: &Self) -> bool {
+		self.cmp(other) == Ordering::Equal
+	}
+}
+
+impl Eq for Head {}
+
+impl PartialOrd for Head {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for Head {
+	fn cmp(&self, other: &Self) -> Ordering {
+		self.at_millis.total_cmp(&other.at_millis).then_with(|| self.index.cmp(&other.index))
+	}
+}
+
+struct Input<S> {
+	stream: Pin<Box<S>>,
+	offset_millis: f32,
+	/// The timestamp of the last event received from this stream, used to order events without a timestamp of their
+	/// own (i.e. audio chunks).
+	last_millis: f32,
+	/// Whether this stream's next event is in the heap.
+	queued: bool,
+	finished: bool
+}
+
+struct MergeConcurrent<S> {
+	inputs: Vec<Input<S>>,
+	heads: BinaryHeap<Reverse<Head>>,
+	finished: bool
+}
+
+impl<E, S: Stream<Item = Result<UtteranceEvent, E>>> Stream for MergeConcurrent<S> {
+	type Item = Result<UtteranceEvent, E>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let this = self.get_mut();
+		if this.finished {
+			return Poll::Ready(None);
+		}
+
+		for (index, input) in this.inputs.iter_mut().enumerate() {
+			while !input.queued && !input.finished {
+				match input.stream.as_mut().poll_next(cx) {
+					// Each stream's sentinels are replaced by a single pair bracketing the merged stream.
+					Poll::Ready(Some(Ok(UtteranceEvent::SynthesisStarted { .. } | UtteranceEvent::SynthesisCompleted { .. }))) => {}
+					Poll::Ready(Some(Ok(mut event))) => {
+						event.offset_timestamps(input.offset_millis);
+						if let Some(at_millis) = event.timestamp_millis() {
+							input.last_millis = input.last_millis.max(at_millis);
+						}
+						input.queued = true;
+						this.heads.push(Reverse(Head {
+							at_millis: input.last_millis,
+							index,
+							event
+						}));
+					}
+					Poll::Ready(Some(Err(e))) => {
+						this.finished = true;
+						return Poll::Ready(Some(Err(e)));
+					}
+					Poll::Ready(None) => input.finished = true,
+					Poll::Pending => break
+				}
+			}
+		}
+
+		// An event can only be emitted once every stream which may still produce an earlier event has been heard from.
+		if this.inputs.iter().any(|input| !input.queued && !input.finished) {
+			return Poll::Pending;
+		}
+		match this.heads.pop() {
+			Some(Reverse(head)) => {
+				this.inputs[head.index].queued = false;
+				Poll::Ready(Some(Ok(head.event)))
+			}
+			None => {
+				this.finished = true;
+				Poll::Ready(None)
+			}
+		}
+	}
+}
+
+/// Merges multiple [`UtteranceEventStream`]s (i.e. those of multiple speakers synthesised in parallel) into a single
+/// stream, in timestamp order. Each stream is paired with the time in milliseconds at which it starts, relative to the
+/// beginning of the merged stream; the timestamps of its events are offset accordingly.
+///
+/// All streams are polled concurrently, and an event is only emitted once every stream which has not yet ended has an
+/// event ready, so that events are emitted in order. Events without a timestamp of their own (such as
+/// [`UtteranceEvent::AudioChunk`]s) are ordered by the last timestamp seen from their stream, or its start time if
+/// there is none; events with equal timestamps are emitted in the order their streams were given.
+///
+/// Each stream's [`UtteranceEvent::SynthesisStarted`] & [`UtteranceEvent::SynthesisCompleted`] sentinels are replaced
+/// by a single pair bracketing the merged stream. The first error from any stream is yielded immediately, and ends the
+/// merged stream.
+///
+/// Note that the audio of each stream is not mixed; audio chunks from different streams are simply interleaved.
+pub fn merge_concurrent<E, S: UtteranceEventStream<E>>(streams: Vec<(S, f32)>) -> impl UtteranceEventStream<E> {
+	let inputs = streams
+		.into_iter()
+		.map(|(stream, offset_millis)| Input {
+			stream: Box::pin(stream),
+			offset_millis,
+			last_millis: offset_millis,
+			queued: false,
+			finished: false
+		})
+		.collect();
+	super::with_synthesis_events(
+		MergeConcurrent {
+			inputs,
+			heads: BinaryHeap::new(),
+			finished: false
+		},
+		Instant::now()
+	)
+}
@@ -0,0 +1,283 @@
+use core::{
+	pin::Pin,
+	task::{Context, Poll}
+};
+use std::collections::VecDeque;
+
+use futures_core::Stream;
+use pin_project_lite::pin_project;
+
+use crate::{AudioContainer, AudioEncoding, AudioFormat, Endianness, UtteranceEvent, UtteranceEventStream};
+
+/// The default minimum length in milliseconds of silence reported as a pause by [`detect_pauses`], used when
+/// [`UtteranceConfig::silence_threshold_millis`](crate::UtteranceConfig::silence_threshold_millis) is not set.
+pub const DEFAULT_SILENCE_THRESHOLD_MILLIS: f32 = 250.0;
+
+/// Samples quieter than this (about -60 dBFS) are considered silent.
+const SILENCE_LEVEL: f32 = 0.001;
+
+/// Returns the absolute amplitude of a single sample, from `0.0` to `1.0`.
+#[allow(deprecated)]
+fn amplitude(encoding: AudioEncoding, sample: &[u8]) -> f32 {
+	let big_endian = encoding.endianness() == Some(Endianness::Big);
+	let mut bytes = [0; 8];
+	let bytes = &mut bytes[..sample.len()];
+	bytes.copy_from_slice(sample);
+	if !big_endian {
+		bytes.reverse();
+	}
+	// `bytes` is now big-endian.
+	match encoding.normalize() {
+		AudioEncoding::PcmI16Le | AudioEncoding::PcmI16Be => (i16::from_be_bytes([bytes[0], bytes[1]]) as f32 / 32768.0).abs(),
+		AudioEncoding::PcmI24Le | AudioEncoding::PcmI24Be => (i32::from_be_bytes([bytes[0], bytes[1], bytes[2], 0]) as f32 / 2147483648.0).abs(),
+		AudioEncoding::PcmI32Le | AudioEncoding::PcmI32Be => (i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f32 / 2147483648.0).abs(),
+		AudioEncoding::PcmF32Le | AudioEncoding::PcmF32Be => f32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]).abs(),
+		AudioEncoding::PcmF64Le | AudioEncoding::PcmF64Be => {
+			f64::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7]]).abs() as f32
+		}
+		AudioEncoding::MuLaw => {
+			let u = !bytes[0];
+			let (exponent, mantissa) = ((u >> 4) & 0x07, (u & 0x0f) as i32);
+			((((mantissa << 3) + 0x84) << exponent) - 0x84) as f32 / 32768.0
+		}
+		AudioEncoding::ALaw => {
+			let a = bytes[0] ^ 0x55;
+			let (exponent, mantissa) = ((a >> 4) & 0x07, (a & 0x0f) as i32);
+			let magnitude = match exponent {
+				0 => (mantissa << 4) + 8,
+				_ => ((mantissa << 4) + 0x108) << (exponent - 1)
+			};
+			magnitude as f32 / 32768.0
+		}
+		AudioEncoding::PcmI16 | AudioEncoding::PcmI24 | AudioEncoding::PcmI32 | AudioEncoding::PcmF32 | AudioEncoding::PcmF64 => unreachable!("normalized")
+	}
+}
+
+/// How a block of audio is determined to be silent by a [`SilenceScanner`].
+pub(super) enum Measure {
+	/// Each frame is silent if none of its samples' amplitudes exceed the level.
+	Peak(f32),
+	/// Each window of `window_millis` is silent if the RMS amplitude of its samples is below `level`.
+	Rms { level: f32, window_millis: u64 }
+}
+
+/// Tracks runs of silence across the audio chunks of a stream, as shared by [`detect_pauses`] &
+/// [`with_silence_detection`](super::with_silence_detection).
+pub(super) struct SilenceScanner {
+	encoding: AudioEncoding,
+	channels: usize,
+	sample_rate: u32,
+	measure: Measure,
+	/// The number of frames in each block measured for silence.
+	block_frames: u64,
+	/// The minimum length in frames of a reported run of silence.
+	min_frames: u64,
+	/// Bytes of an incomplete frame at the end of the previous chunk.
+	carry: Vec<u8>,
+	/// The index of the next frame to be read.
+	position: u64,
+	/// The peak amplitude, sum of squared amplitudes, & number of samples in the current block.
+	block: (f32, f32, u32),
+	/// The index of the frame at which the current run of silence began, if the audio is currently silent.
+	silence_start: Option<u64>,
+	/// Whether any audible blocks have been read yet.
+	heard: bool
+}
+
+impl SilenceScanner {
+	/// Returns `None` if silence cannot be detected in the format, i.e. it is not [`AudioContainer::Raw`].
+	pub(super) fn new(audio_format: &AudioFormat, measure: Measure, min_duration_millis: f32) -> Option<Self> {
+		let AudioContainer::Raw(encoding) = audio_format.container() else {
+			return None;
+		};
+		let sample_rate = audio_format.sample_rate().max(1);
+		let block_frames = match measure {
+			Measure::Peak(_) => 1,
+			Measure::Rms { window_millis, .. } => (sample_rate as u64 * window_millis / 1000).max(1)
+		};
+		Some(SilenceScanner {
+			encoding,
+			channels: audio_format.channels().count() as usize,
+			sample_rate,
+			measure,
+			block_frames,
+			min_frames: (min_duration_millis.max(0.0) * sample_rate as f32 / 1000.0).ceil() as u64,
+			carry: Vec::new(),
+			position: 0,
+			block: (0.0, 0.0, 0),
+			silence_start: None,
+			heard: false
+		})
+	}
+
+	fn frames_to_millis(&self, frames: u64) -> f32 {
+		frames as f32 * 1000.0 / self.sample_rate as f32
+	}
+
+	/// Scans a chunk of audio, calling `on_silence` with the position & duration in milliseconds of each run of silence
+	/// at least the minimum duration long which ends with audible audio.
+	///
+	/// Silence before the first audible block is leading silence, and is not reported.
+	pub(super) fn scan(&mut self, chunk: &[u8], mut on_silence: impl FnMut(f32, f32)) {
+		let sample_width = self.encoding.byte_width() as usize;
+		let frame_width = sample_width * self.channels;
+		let mut data = core::mem::take(&mut self.carry);
+		data.extend_from_slice(chunk);
+
+		let mut frames = data.chunks_exact(frame_width);
+		for frame in frames.by_ref() {
+			for sample in frame.chunks_exact(sample_width) {
+				let x = amplitude(self.encoding, sample);
+				self.block.0 = self.block.0.max(x);
+				self.block.1 += x * x;
+				self.block.2 += 1;
+			}
+			self.position += 1;
+			if self.position % self.block_frames == 0 {
+				self.end_block(&mut on_silence);
+			}
+		}
+		self.carry = frames.remainder().to_vec();
+	}
+
+	fn end_block(&mut self, on_silence: &mut impl FnMut(f32, f32)) {
+		let (peak, sum, count) = core::mem::take(&mut self.block);
+		let silent = match self.measure {
+			Measure::Peak(level) => peak <= level,
+			Measure::Rms { level, .. } => (sum / count.max(1) as f32).sqrt() < level
+		};
+		let block_start = self.position - self.block_frames;
+		match (silent, self.silence_start) {
+			(true, None) => self.silence_start = Some(block_start),
+			(false, Some(start)) => {
+				self.silence_start = None;
+				let length = block_start - start;
+				if self.heard && length >= self.min_frames {
+					on_silence(self.frames_to_millis(start), self.frames_to_millis(length));
+				}
+			}
+			_ => {}
+		}
+		self.heard |= !silent;
+	}
+}
+
+/// Queues an [`UtteranceEvent::PauseDetected`] for each run of silence found by its scanner.
+struct Detector {
+	scanner: SilenceScanner,
+	queued: VecDeque<UtteranceEvent>
+}
+
+impl Detector {
+	fn scan(&mut self, chunk: &[u8]) {
+		let queued = &mut self.queued;
+		self.scanner
+			.scan(chunk, |at_millis, duration_millis| queued.push_back(UtteranceEvent::PauseDetected { at_millis, duration_millis }));
+	}
+}
+
+pin_project! {
+	struct DetectPauses<S> {
+		#[pin]
+		inner: S,
+		// `None` if pauses cannot be detected in the stream's format, or the stream reports pauses itself.
+		detector: Option<Detector>
+	}
+}
+
+impl<E, S: Stream<Item = Result<UtteranceEvent, E>>> Stream for DetectPauses<S> {
+	type Item = Result<UtteranceEvent, E>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let this = self.project();
+		if let Some(event) = this.detector.as_mut().and_then(|detector| detector.queued.pop_front()) {
+			return Poll::Ready(Some(Ok(event)));
+		}
+		let item = match this.inner.poll_next(cx) {
+			Poll::Ready(item) => item,
+			Poll::Pending => return Poll::Pending
+		};
+		match (&item, this.detector.as_mut()) {
+			// Pauses reported by the synthesiser itself take precedence over detected pauses.
+			(Some(Ok(UtteranceEvent::PauseDetected { .. })), Some(_)) => *this.detector = None,
+			(Some(Ok(UtteranceEvent::AudioChunk(chunk))), Some(detector)) => detector.scan(chunk),
+			_ => {}
+		}
+		Poll::Ready(item)
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(self.inner.size_hint().0, None)
+	}
+}
+
+/// Adapts an [`UtteranceEventStream`] to emit an [`UtteranceEvent::PauseDetected`] for each run of silence in the
+/// audio at least `threshold_millis` long, for synthesisers which do not report pauses themselves. See
+/// [`UtteranceConfig::with_emit_pause_events`](crate::UtteranceConfig::with_emit_pause_events).
+///
+/// Each event is emitted after the [`UtteranceEvent::AudioChunk`] in which the pause ends. Only silence between speech
+/// is reported; leading & trailing silence is not. Pauses can only be detected in [`AudioContainer::Raw`] audio; other
+/// formats are passed through unchanged. Detection is also disabled once the stream emits a `PauseDetected` event of
+/// its own.
+pub fn detect_pauses<E>(stream: impl UtteranceEventStream<E>, audio_format: &AudioFormat, threshold_millis: f32) -> impl UtteranceEventStream<E> {
+	let detector =
+		SilenceScanner::new(audio_format, Measure::Peak(SILENCE_LEVEL), threshold_millis).map(|scanner| Detector { scanner, queued: VecDeque::new() });
+	DetectPauses { inner: stream, detector }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{Measure, SILENCE_LEVEL, SilenceScanner};
+	use crate::{AudioChannels, AudioContainer, AudioEncoding, AudioFormat};
+
+	/// 1 kHz mono PCM audio which is audible for `audible` ms, silent for `silent` ms, then audible for `audible` ms.
+	fn gap(audible: usize, silent: usize) -> Vec<u8> {
+		let loud = i16::MAX.to_le_bytes();
+		let mut audio = loud.repeat(audible);
+		audio.extend(vec![0; 2 * silent]);
+		audio.extend(loud.repeat(audible));
+		audio
+	}
+
+	fn scan(measure: Measure, min_duration_millis: f32, chunks: &[&[u8]]) -> Vec<(f32, f32)> {
+		let format = AudioFormat::new(1000, AudioChannels::Mono, None, AudioContainer::Raw(AudioEncoding::PcmI16Le));
+		let mut scanner = SilenceScanner::new(&format, measure, min_duration_millis).unwrap();
+		let mut runs = Vec::new();
+		for chunk in chunks {
+			scanner.scan(chunk, |at_millis, duration_millis| runs.push((at_millis, duration_millis)));
+		}
+		runs
+	}
+
+	#[test]
+	fn peak_reports_runs_of_silent_frames() {
+		let audio = gap(50, 300);
+		assert_eq!(scan(Measure::Peak(SILENCE_LEVEL), 250.0, &[&audio]), [(50.0, 300.0)]);
+		assert_eq!(scan(Measure::Peak(SILENCE_LEVEL), 400.0, &[&audio]), []);
+		// Frames split across chunks are carried over.
+		let (first, rest) = audio.split_at(301);
+		assert_eq!(scan(Measure::Peak(SILENCE_LEVEL), 250.0, &[first, rest]), [(50.0, 300.0)]);
+	}
+
+	#[test]
+	fn rms_reports_runs_of_silent_windows() {
+		let measure = || Measure::Rms { level: 0.01, window_millis: 10 };
+		assert_eq!(scan(measure(), 250.0, &[&gap(50, 300)]), [(50.0, 300.0)]);
+		// A window which is only partly silent is audible.
+		assert_eq!(scan(measure(), 250.0, &[&gap(55, 300)]), [(60.0, 290.0)]);
+	}
+
+	#[test]
+	fn leading_and_trailing_silence_is_not_reported() {
+		let mut audio = vec![0; 600];
+		audio.extend(i16::MAX.to_le_bytes().repeat(50));
+		audio.extend(vec![0; 600]);
+		assert_eq!(scan(Measure::Peak(SILENCE_LEVEL), 250.0, &[&audio]), []);
+	}
+
+	#[test]
+	fn non_raw_formats_are_not_scanned() {
+		let format = AudioFormat::new(1000, AudioChannels::Mono, None, AudioContainer::Riff(AudioEncoding::PcmI16Le));
+		assert!(SilenceScanner::new(&format, Measure::Peak(SILENCE_LEVEL), 250.0).is_none());
+	}
+}
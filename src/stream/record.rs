@@ -0,0 +1,69 @@
+use core::{
+	convert::Infallible,
+	pin::{Pin, pin},
+	task::{Context, Poll}
+};
+
+use futures_core::Stream;
+
+use super::next;
+use crate::{AudioFormat, UtteranceEvent, UtteranceEventStream};
+
+/// Every event of an utterance, captured by [`record_to_vec`] so that it can be [replayed](RecordedUtterance::replay),
+/// i.e. by a synthesis cache, or to provide canned responses in tests.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RecordedUtterance {
+	/// The recorded events, in the order they were received.
+	pub events: Vec<UtteranceEvent>,
+	/// The format of the audio in the recorded [`UtteranceEvent::AudioChunk`]s.
+	pub format: AudioFormat
+}
+
+impl RecordedUtterance {
+	pub fn new(events: Vec<UtteranceEvent>, format: AudioFormat) -> Self {
+		RecordedUtterance { events, format }
+	}
+
+	/// Returns a stream which yields a copy of every recorded event in order, without waiting.
+	pub fn replay(&self) -> impl UtteranceEventStream<Infallible> + 'static {
+		Replay {
+			events: self.events.clone().into_iter()
+		}
+	}
+
+	/// Like [`RecordedUtterance::replay`], but consumes the recording to avoid copying its events.
+	pub fn into_replay(self) -> impl UtteranceEventStream<Infallible> + 'static {
+		Replay { events: self.events.into_iter() }
+	}
+}
+
+struct Replay {
+	events: std::vec::IntoIter<UtteranceEvent>
+}
+
+impl Stream for Replay {
+	type Item = Result<UtteranceEvent, Infallible>;
+
+	fn poll_next(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		Poll::Ready(self.events.next().map(Ok))
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.events.size_hint()
+	}
+}
+
+/// Drives `stream` to completion, recording all of its events into a [`RecordedUtterance`]. `audio_format` should be
+/// the format the stream was synthesised in.
+///
+/// Returns early if the stream yields an error, discarding the partial recording.
+pub async fn record_to_vec<E>(stream: impl UtteranceEventStream<E>, audio_format: AudioFormat) -> Result<RecordedUtterance, E> {
+	let mut stream = pin!(stream);
+	let mut events = Vec::new();
+	while let Some(event) = next(stream.as_mut()).await {
+		events.push(event?);
+	}
+	Ok(RecordedUtterance::new(events, audio_format))
+}
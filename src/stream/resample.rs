@@ -0,0 +1,143 @@
+use core::{
+	pin::Pin,
+	task::{Context, Poll}
+};
+
+use futures_core::Stream;
+use pin_project_lite::pin_project;
+
+use crate::{BasicVisemeFrame, BlendShapeVisemeFrame, UtteranceEvent, UtteranceEventStream};
+
+trait Frame: Clone {
+	fn offset(&self) -> f32;
+
+	/// Samples the frame at `at`, where `a.offset() <= at <= b.offset()`.
+	fn sample(a: &Self, b: &Self, at: f32) -> Self;
+}
+
+impl Frame for BasicVisemeFrame {
+	fn offset(&self) -> f32 {
+		self.frame_offset
+	}
+
+	/// Basic visemes are categorical, so the nearest frame is used.
+	fn sample(a: &Self, b: &Self, at: f32) -> Self {
+		let viseme = if at - a.frame_offset < b.frame_offset - at { a.viseme } else { b.viseme };
+		BasicVisemeFrame { viseme, frame_offset: at }
+	}
+}
+
+impl Frame for BlendShapeVisemeFrame {
+	fn offset(&self) -> f32 {
+		self.frame_offset
+	}
+
+	fn sample(a: &Self, b: &Self, at: f32) -> Self {
+		let span = b.frame_offset - a.frame_offset;
+		let t = if span == 0.0 { 1.0 } else { (at - a.frame_offset) / span };
+		// Avoid interpolating at either end, so that frames already at the target rate are reproduced exactly.
+		let mut frame = if t >= 1.0 {
+			b.clone()
+		} else if t <= 0.0 {
+			a.clone()
+		} else {
+			a.lerp(b, t)
+		};
+		frame.frame_offset = at;
+		frame
+	}
+}
+
+struct Resampler<F> {
+	previous: Option<F>,
+	/// The index of the next output frame.
+	index: u64
+}
+
+impl<F: Frame> Resampler<F> {
+	fn new() -> Self {
+		Resampler { previous: None, index: 0 }
+	}
+
+	fn resample(&mut self, frames: &[F], fps: f32) -> Vec<F> {
+		let mut out = Vec::new();
+		for frame in frames {
+			loop {
+				let at = (self.index as f64 * 1000.0 / fps as f64) as f32;
+				if at > frame.offset() {
+					break;
+				}
+				// Output frames before the first source frame can only be sampled from the first frame.
+				let previous = self.previous.as_ref().unwrap_or(frame);
+				out.push(F::sample(previous, frame, at.max(previous.offset())));
+				self.index += 1;
+			}
+			self.previous = Some(frame.clone());
+		}
+		out
+	}
+}
+
+pin_project! {
+	struct ResampleVisemes<S> {
+		#[pin]
+		inner: S,
+		fps: f32,
+		basic: Resampler<BasicVisemeFrame>,
+		blend_shapes: Resampler<BlendShapeVisemeFrame>
+	}
+}
+
+impl<E, S: Stream<Item = Result<UtteranceEvent, E>>> Stream for ResampleVisemes<S> {
+	type Item = Result<UtteranceEvent, E>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let mut this = self.project();
+		if !(this.fps.is_finite() && *this.fps > 0.0) {
+			return this.inner.poll_next(cx);
+		}
+		loop {
+			let event = match this.inner.as_mut().poll_next(cx) {
+				Poll::Ready(Some(Ok(UtteranceEvent::VisemesChunk(frames)))) => {
+					let frames = this.basic.resample(&frames, *this.fps);
+					if frames.is_empty() {
+						continue;
+					}
+					UtteranceEvent::VisemesChunk(frames.into_boxed_slice())
+				}
+				Poll::Ready(Some(Ok(UtteranceEvent::BlendShapeVisemesChunk(frames)))) => {
+					let frames = this.blend_shapes.resample(&frames, *this.fps);
+					if frames.is_empty() {
+						continue;
+					}
+					UtteranceEvent::BlendShapeVisemesChunk(frames.into_boxed_slice())
+				}
+				other => return other
+			};
+			return Poll::Ready(Some(Ok(event)));
+		}
+	}
+}
+
+/// Adapts an [`UtteranceEventStream`] to resample its [`UtteranceEvent::VisemesChunk`] &
+/// [`UtteranceEvent::BlendShapeVisemesChunk`] events to a fixed frame rate, i.e. to drive an animation running at
+/// `fps` frames per second.
+///
+/// Output frames are placed at exact multiples of `1000 / fps` milliseconds from the beginning of the audio stream.
+/// Blend shape frames are linearly interpolated between the adjacent source frames with
+/// [`BlendShapeVisemeFrame::lerp`]; basic visemes are categorical, so the nearest source frame is used instead. Source
+/// frames which already lie on the target frame rate are reproduced exactly. Frames are emitted as soon as the source
+/// frame following them is received, so an output chunk may not correspond to any one source chunk, and chunks which
+/// would be empty are skipped.
+///
+/// Like [`interpolate_frames`](crate::interpolate_frames), this panics if adjacent blend shape frames do not contain
+/// the same blend shapes. If `fps` is not a positive number, the stream is passed through unchanged. All other
+/// events are passed through unaffected.
+pub fn resample_visemes_to_fps<E>(stream: impl UtteranceEventStream<E>, fps: f32) -> impl UtteranceEventStream<E> {
+	ResampleVisemes {
+		inner: stream,
+		fps,
+		basic: Resampler::new(),
+		blend_shapes: Resampler::new()
+	}
+}
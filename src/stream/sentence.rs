@@ -0,0 +1,138 @@
+use core::{
+	pin::Pin,
+	task::{Context, Poll}
+};
+
+use futures_core::Stream;
+use pin_project_lite::pin_project;
+
+use crate::{UtteranceEvent, UtteranceEventStream, WordBoundary};
+
+/// A single sentence of an utterance, along with all of its audio & word boundaries, as yielded by
+/// [`chunk_by_sentence`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct SentenceChunk {
+	/// The text of the sentence.
+	pub text: Box<str>,
+	/// The position in milliseconds the sentence begun, relative to the beginning of the audio stream.
+	pub from_millis: f32,
+	/// The position in milliseconds the sentence ended, relative to the beginning of the audio stream.
+	pub to_millis: f32,
+	/// The audio of the sentence, concatenated from all [`UtteranceEvent::AudioChunk`]s received while the sentence
+	/// was current.
+	pub audio_bytes: Vec<u8>,
+	/// The boundaries of each word in the sentence.
+	pub word_boundaries: Vec<WordBoundary>
+}
+
+impl SentenceChunk {
+	fn new(text: Box<str>, from_millis: f32, to_millis: f32) -> Self {
+		SentenceChunk {
+			text,
+			from_millis,
+			to_millis,
+			audio_bytes: Vec::new(),
+			word_boundaries: Vec::new()
+		}
+	}
+
+	fn is_empty(&self) -> bool {
+		self.text.is_empty() && self.audio_bytes.is_empty() && self.word_boundaries.is_empty()
+	}
+}
+
+pin_project! {
+	struct ChunkBySentence<S> {
+		#[pin]
+		inner: Option<S>,
+		current: SentenceChunk,
+		// Whether a `SentenceBoundary` has been received yet; events before the first boundary are part of the first
+		// sentence.
+		started: bool
+	}
+}
+
+impl<E, S: Stream<Item = Result<UtteranceEvent, E>>> Stream for ChunkBySentence<S> {
+	type Item = Result<SentenceChunk, E>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let mut this = self.project();
+		loop {
+			let Some(inner) = this.inner.as_mut().as_pin_mut() else {
+				return Poll::Ready(None);
+			};
+			match inner.poll_next(cx) {
+				Poll::Ready(Some(Ok(UtteranceEvent::SentenceBoundary { from_millis, to_millis, text }))) => {
+					if !*this.started {
+						*this.started = true;
+						this.current.text = text;
+						this.current.from_millis = from_millis;
+						this.current.to_millis = to_millis;
+						continue;
+					}
+					let chunk = core::mem::replace(this.current, SentenceChunk::new(text, from_millis, to_millis));
+					return Poll::Ready(Some(Ok(chunk)));
+				}
+				Poll::Ready(Some(Ok(UtteranceEvent::AudioChunk(audio)))) => this.current.audio_bytes.extend_from_slice(&audio),
+				Poll::Ready(Some(Ok(UtteranceEvent::WordBoundary {
+					from_millis,
+					to_millis,
+					text,
+					confidence,
+					phonetic
+				}))) => {
+					if !*this.started {
+						// Without a sentence boundary, the sentence's span can only be inferred from its words.
+						if this.current.word_boundaries.is_empty() {
+							this.current.from_millis = from_millis;
+						}
+						this.current.to_millis = to_millis;
+					}
+					this.current.word_boundaries.push(WordBoundary {
+						from_millis,
+						to_millis,
+						text,
+						confidence,
+						phonetic
+					});
+				}
+				Poll::Ready(Some(Ok(_))) => {}
+				Poll::Ready(Some(Err(e))) => {
+					this.inner.set(None);
+					return Poll::Ready(Some(Err(e)));
+				}
+				Poll::Ready(None) => {
+					this.inner.set(None);
+					let chunk = core::mem::replace(this.current, SentenceChunk::new(Box::from(""), 0.0, 0.0));
+					if chunk.is_empty() {
+						return Poll::Ready(None);
+					}
+					return Poll::Ready(Some(Ok(chunk)));
+				}
+				Poll::Pending => return Poll::Pending
+			}
+		}
+	}
+}
+
+/// Adapts an [`UtteranceEventStream`] into a stream of [`SentenceChunk`]s, grouping the audio & word boundaries
+/// received between consecutive [`UtteranceEvent::SentenceBoundary`] events, i.e. to display text sentence-by-sentence
+/// as its audio is played.
+///
+/// Each sentence is yielded once the next sentence's boundary is received (so that all of its audio is known), and the
+/// final sentence is yielded once the stream ends. Events received before the first sentence boundary are considered
+/// part of the first sentence. All events besides audio, word boundaries & sentence boundaries are discarded.
+///
+/// The stream must be configured to emit sentence boundaries (see
+/// [`UtteranceConfig::emit_sentence_boundary_events`](crate::UtteranceConfig::emit_sentence_boundary_events));
+/// otherwise, the entire utterance is yielded as a single chunk with empty text.
+///
+/// If the stream yields an error, the error is yielded in place of the current sentence, and the stream then ends.
+pub fn chunk_by_sentence<E>(stream: impl UtteranceEventStream<E>) -> impl Stream<Item = Result<SentenceChunk, E>> + Send {
+	ChunkBySentence {
+		inner: Some(stream),
+		current: SentenceChunk::new(Box::from(""), 0.0, 0.0),
+		started: false
+	}
+}
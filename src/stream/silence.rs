@@ -0,0 +1,147 @@
+use core::{
+	pin::Pin,
+	task::{Context, Poll}
+};
+use std::collections::VecDeque;
+
+use futures_core::Stream;
+use pin_project_lite::pin_project;
+
+use super::pause::{Measure, SilenceScanner};
+use crate::{AudioFormat, UtteranceEvent, UtteranceEventStream};
+
+/// The length in milliseconds of each window over which the RMS amplitude is calculated.
+const WINDOW_MILLIS: u64 = 10;
+
+/// Tracks runs of silence across the audio chunks of a stream, and the positions at which silence is expected.
+struct Monitor {
+	scanner: SilenceScanner,
+	/// Runs of silence which have not yet been checked against `expected`, as `(at_millis, duration_millis)`.
+	candidates: Vec<(f32, f32)>,
+	/// Spans during which silence is expected, from pauses & sentence boundaries, as `(from_millis, to_millis)`.
+	expected: Vec<(f32, f32)>,
+	queued: VecDeque<UtteranceEvent>
+}
+
+impl Monitor {
+	fn observe(&mut self, event: &UtteranceEvent) {
+		match *event {
+			UtteranceEvent::PauseDetected { at_millis, duration_millis } => self.expected.push((at_millis, at_millis + duration_millis)),
+			UtteranceEvent::SentenceBoundary { from_millis, to_millis, .. } | UtteranceEvent::ParagraphBoundary { from_millis, to_millis, .. } => {
+				self.expected.push((from_millis, from_millis));
+				self.expected.push((to_millis, to_millis));
+			}
+			_ => {}
+		}
+	}
+
+	/// Queues an [`UtteranceEvent::UnexpectedSilence`] for each candidate run of silence which does not overlap a span
+	/// of expected silence.
+	fn flush(&mut self) {
+		for (at_millis, duration_millis) in self.candidates.drain(..) {
+			let end_millis = at_millis + duration_millis;
+			if !self.expected.iter().any(|&(from, to)| from <= end_millis && to >= at_millis) {
+				self.queued.push_back(UtteranceEvent::UnexpectedSilence { at_millis, duration_millis });
+			}
+		}
+	}
+
+	fn scan(&mut self, chunk: &[u8]) {
+		let candidates = &mut self.candidates;
+		self.scanner
+			.scan(chunk, |at_millis, duration_millis| candidates.push((at_millis, duration_millis)));
+	}
+}
+
+pin_project! {
+	struct WithSilenceDetection<S> {
+		#[pin]
+		inner: S,
+		// `None` if silence cannot be detected in the stream's format.
+		monitor: Option<Monitor>,
+		finished: bool
+	}
+}
+
+impl<E, S: Stream<Item = Result<UtteranceEvent, E>>> Stream for WithSilenceDetection<S> {
+	type Item = Result<UtteranceEvent, E>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let this = self.project();
+		if let Some(event) = this.monitor.as_mut().and_then(|monitor| monitor.queued.pop_front()) {
+			return Poll::Ready(Some(Ok(event)));
+		}
+		if *this.finished {
+			return Poll::Ready(None);
+		}
+		let item = match this.inner.poll_next(cx) {
+			Poll::Ready(item) => item,
+			Poll::Pending => return Poll::Pending
+		};
+		let Some(monitor) = this.monitor.as_mut() else {
+			return Poll::Ready(item);
+		};
+		match item {
+			// Pause & boundary events for a run of silence may be emitted after the chunk in which it ends, so
+			// candidates are only checked once the next chunk (or the end of the stream) is reached.
+			Some(Ok(UtteranceEvent::AudioChunk(ref chunk))) => {
+				monitor.flush();
+				monitor.scan(chunk);
+				Poll::Ready(item)
+			}
+			// `SynthesisCompleted` must remain the last event of the stream, so remaining candidates are emitted first.
+			Some(Ok(completed @ UtteranceEvent::SynthesisCompleted { .. })) => {
+				monitor.flush();
+				monitor.queued.push_back(completed);
+				Poll::Ready(monitor.queued.pop_front().map(Ok))
+			}
+			Some(Ok(ref event)) => {
+				monitor.observe(event);
+				Poll::Ready(item)
+			}
+			Some(Err(_)) => Poll::Ready(item),
+			None => {
+				*this.finished = true;
+				monitor.flush();
+				Poll::Ready(monitor.queued.pop_front().map(Ok))
+			}
+		}
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(self.inner.size_hint().0, None)
+	}
+}
+
+/// Adapts an [`UtteranceEventStream`] to emit an [`UtteranceEvent::UnexpectedSilence`] for each run of silence in the
+/// audio at least `min_duration_ms` long which does not coincide with an [`UtteranceEvent::PauseDetected`],
+/// [`UtteranceEvent::SentenceBoundary`], or [`UtteranceEvent::ParagraphBoundary`], i.e. to detect dropped words or
+/// backend errors in production.
+///
+/// Audio is considered silent where its RMS amplitude, measured over 10 ms windows, is below `threshold_db` dBFS (i.e.
+/// `-50.0`). Leading & trailing silence is not reported. Each event is emitted after the
+/// [`UtteranceEvent::AudioChunk`] following the one in which the silence ends, so that pause & boundary events emitted
+/// in between are taken into account. Like [`detect_pauses`](super::detect_pauses), silence can only be detected in
+/// [`AudioContainer::Raw`](crate::AudioContainer::Raw) audio; other formats are passed through unchanged.
+pub fn with_silence_detection<E>(
+	stream: impl UtteranceEventStream<E>,
+	audio_format: &AudioFormat,
+	threshold_db: f32,
+	min_duration_ms: f32
+) -> impl UtteranceEventStream<E> {
+	let measure = Measure::Rms {
+		level: 10f32.powf(threshold_db / 20.0),
+		window_millis: WINDOW_MILLIS
+	};
+	let monitor = SilenceScanner::new(audio_format, measure, min_duration_ms).map(|scanner| Monitor {
+		scanner,
+		candidates: Vec::new(),
+		expected: Vec::new(),
+		queued: VecDeque::new()
+	});
+	WithSilenceDetection {
+		inner: stream,
+		monitor,
+		finished: false
+	}
+}
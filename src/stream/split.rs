@@ -0,0 +1,275 @@
+use core::{
+	pin::Pin,
+	task::{Context, Poll, Waker}
+};
+use std::{
+	collections::VecDeque,
+	io,
+	sync::{Arc, Mutex, MutexGuard},
+	task::Wake
+};
+
+use futures_core::Stream;
+
+use crate::{UtteranceEvent, UtteranceEventStream};
+
+/// The default number of items buffered for one half of a [`split`] stream while the other half is being polled.
+pub const DEFAULT_SPLIT_CAPACITY: usize = 64;
+
+const AUDIO: usize = 0;
+const METADATA: usize = 1;
+
+#[derive(Default)]
+struct SplitWakers([Mutex<Option<Waker>>; 2]);
+
+impl SplitWakers {
+	fn register(&self, side: usize, waker: &Waker) {
+		let mut slot = self.0[side].lock().unwrap_or_else(|e| e.into_inner());
+		match slot.as_ref() {
+			Some(w) if w.will_wake(waker) => {}
+			_ => *slot = Some(waker.clone())
+		}
+	}
+
+	fn wake_side(&self, side: usize) {
+		let waker = self.0[side].lock().unwrap_or_else(|e| e.into_inner()).take();
+		if let Some(waker) = waker {
+			waker.wake();
+		}
+	}
+}
+
+impl Wake for SplitWakers {
+	fn wake(self: Arc<Self>) {
+		self.wake_by_ref();
+	}
+
+	fn wake_by_ref(self: &Arc<Self>) {
+		self.wake_side(AUDIO);
+		self.wake_side(METADATA);
+	}
+}
+
+struct SplitState<E> {
+	source: Option<Pin<Box<dyn UtteranceEventStream<E>>>>,
+	audio: VecDeque<io::Result<Box<[u8]>>>,
+	metadata: VecDeque<Result<UtteranceEvent, E>>,
+	dropped: [bool; 2]
+}
+
+impl<E> SplitState<E> {
+	fn queue_len(&self, side: usize) -> usize {
+		match side {
+			AUDIO => self.audio.len(),
+			_ => self.metadata.len()
+		}
+	}
+}
+
+struct SplitShared<E> {
+	state: Mutex<SplitState<E>>,
+	wakers: Arc<SplitWakers>,
+	capacity: usize
+}
+
+impl<E> SplitShared<E> {
+	fn lock(&self) -> MutexGuard<'_, SplitState<E>> {
+		self.state.lock().unwrap_or_else(|e| e.into_inner())
+	}
+
+	/// Pulls items from the source stream until an item is available for `side`.
+	fn poll_side<T>(&self, side: usize, cx: &mut Context<'_>, mut pop: impl FnMut(&mut SplitState<E>) -> Option<T>) -> Poll<Option<T>> {
+		let other = 1 - side;
+		let mut state = self.lock();
+		loop {
+			if let Some(item) = pop(&mut state) {
+				// We've freed up space in our queue; the other side may be waiting on us.
+				self.wakers.wake_side(other);
+				return Poll::Ready(Some(item));
+			}
+
+			self.wakers.register(side, cx.waker());
+
+			let state = &mut *state;
+			if state.source.is_some() && !state.dropped[other] && state.queue_len(other) >= self.capacity {
+				// The other side's buffer is full; wait for it to catch up.
+				return Poll::Pending;
+			}
+			let Some(source) = state.source.as_mut() else {
+				return Poll::Ready(None);
+			};
+
+			let waker = Waker::from(Arc::clone(&self.wakers));
+			match source.as_mut().poll_next(&mut Context::from_waker(&waker)) {
+				Poll::Ready(Some(Ok(UtteranceEvent::AudioChunk(chunk)))) => {
+					if !state.dropped[AUDIO] {
+						state.audio.push_back(Ok(chunk));
+						if side != AUDIO {
+							self.wakers.wake_side(AUDIO);
+						}
+					}
+				}
+				Poll::Ready(Some(Ok(event))) => {
+					if !state.dropped[METADATA] {
+						state.metadata.push_back(Ok(event));
+						if side != METADATA {
+							self.wakers.wake_side(METADATA);
+						}
+					}
+				}
+				Poll::Ready(Some(Err(e))) => {
+					if !state.dropped[AUDIO] {
+						state.audio.push_back(Err(io::Error::other("synthesis stream yielded an error")));
+					}
+					if !state.dropped[METADATA] {
+						state.metadata.push_back(Err(e));
+					}
+					self.wakers.wake_side(other);
+				}
+				Poll::Ready(None) => {
+					state.source = None;
+					self.wakers.wake_side(other);
+				}
+				Poll::Pending => return Poll::Pending
+			}
+		}
+	}
+
+	fn drop_side(&self, side: usize) {
+		let mut state = self.lock();
+		state.dropped[side] = true;
+		match side {
+			AUDIO => state.audio.clear(),
+			_ => state.metadata.clear()
+		}
+		if state.dropped[AUDIO] && state.dropped[METADATA] {
+			state.source = None;
+		}
+		drop(state);
+		self.wakers.wake_side(1 - side);
+	}
+}
+
+/// Type-erased access to the audio half of a [`SplitShared`], so [`AudioStream`] need not be generic over the error
+/// type.
+trait AudioSource: Send + Sync {
+	fn poll_audio(&self, cx: &mut Context<'_>) -> Poll<Option<io::Result<Box<[u8]>>>>;
+
+	fn drop_audio(&self);
+}
+
+impl<E: Send> AudioSource for SplitShared<E> {
+	fn poll_audio(&self, cx: &mut Context<'_>) -> Poll<Option<io::Result<Box<[u8]>>>> {
+		self.poll_side(AUDIO, cx, |state| state.audio.pop_front())
+	}
+
+	fn drop_audio(&self) {
+		self.drop_side(AUDIO);
+	}
+}
+
+/// The audio half of a [`split`] stream, yielding the payloads of [`UtteranceEvent::AudioChunk`]s.
+///
+/// If the source stream yields an error, the audio stream yields an [`io::Error`]; the actual error is delivered to
+/// the [`MetadataStream`].
+///
+/// With the `tokio` feature enabled, this also implements [`tokio::io::AsyncRead`].
+pub struct AudioStream {
+	shared: Arc<dyn AudioSource>,
+	/// A partially consumed chunk, and the offset of its unread bytes.
+	partial: Option<(Box<[u8]>, usize)>
+}
+
+impl Stream for AudioStream {
+	type Item = io::Result<Box<[u8]>>;
+
+	fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		if let Some((chunk, offset)) = self.partial.take() {
+			return Poll::Ready(Some(Ok(chunk[offset..].into())));
+		}
+		self.shared.poll_audio(cx)
+	}
+}
+
+#[cfg(feature = "tokio")]
+impl tokio::io::AsyncRead for AudioStream {
+	fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut tokio::io::ReadBuf<'_>) -> Poll<io::Result<()>> {
+		loop {
+			if let Some((chunk, offset)) = self.partial.as_mut() {
+				let n = buf.remaining().min(chunk.len() - *offset);
+				buf.put_slice(&chunk[*offset..*offset + n]);
+				*offset += n;
+				if *offset == chunk.len() {
+					self.partial = None;
+				}
+				return Poll::Ready(Ok(()));
+			}
+
+			match self.shared.poll_audio(cx) {
+				Poll::Ready(Some(Ok(chunk))) => self.partial = Some((chunk, 0)),
+				Poll::Ready(Some(Err(e))) => return Poll::Ready(Err(e)),
+				Poll::Ready(None) => return Poll::Ready(Ok(())),
+				Poll::Pending => return Poll::Pending
+			}
+		}
+	}
+}
+
+impl Drop for AudioStream {
+	fn drop(&mut self) {
+		self.shared.drop_audio();
+	}
+}
+
+/// The metadata half of a [`split`] stream, yielding all events besides [`UtteranceEvent::AudioChunk`]s, and any
+/// errors yielded by the source stream.
+pub struct MetadataStream<E> {
+	shared: Arc<SplitShared<E>>
+}
+
+impl<E> Stream for MetadataStream<E> {
+	type Item = Result<UtteranceEvent, E>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		self.shared.poll_side(METADATA, cx, |state| state.metadata.pop_front())
+	}
+}
+
+impl<E> Drop for MetadataStream<E> {
+	fn drop(&mut self) {
+		self.shared.drop_side(METADATA);
+	}
+}
+
+/// Splits an [`UtteranceEventStream`] into a stream of audio, and a stream of all other events.
+///
+/// Both halves drive the underlying stream cooperatively; events destined for the other half are buffered until they
+/// are polled, up to [`DEFAULT_SPLIT_CAPACITY`] events. See [`split_with_capacity`] to configure the buffer size.
+/// Dropping one half will discard its events without buffering.
+pub fn split<E: Send + 'static>(stream: impl UtteranceEventStream<E> + 'static) -> (AudioStream, MetadataStream<E>) {
+	split_with_capacity(stream, DEFAULT_SPLIT_CAPACITY)
+}
+
+/// Splits an [`UtteranceEventStream`] into a stream of audio, and a stream of all other events.
+///
+/// Up to `capacity` events are buffered for one half while the other half is being polled. Once the buffer is full,
+/// the polling half will wait until the other half is polled. See [`split`].
+pub fn split_with_capacity<E: Send + 'static>(stream: impl UtteranceEventStream<E> + 'static, capacity: usize) -> (AudioStream, MetadataStream<E>) {
+	let shared = Arc::new(SplitShared {
+		state: Mutex::new(SplitState {
+			source: Some(Box::pin(stream)),
+			audio: VecDeque::new(),
+			metadata: VecDeque::new(),
+			dropped: [false; 2]
+		}),
+		wakers: Arc::default(),
+		capacity: capacity.max(1)
+	});
+	(
+		AudioStream {
+			shared: Arc::clone(&shared) as Arc<dyn AudioSource>,
+			partial: None
+		},
+		MetadataStream { shared }
+	)
+}
@@ -0,0 +1,105 @@
+use core::{future::Future, pin::Pin, time::Duration};
+use std::borrow::Cow;
+
+use crate::{
+	AudioContainer, AudioFormat, AudioFormatPreference, LanguageInfo, SpeechSynthesiser, SsmlValidationError, SynthesiserCapabilities, UtteranceConfig,
+	UtteranceEventStream, VoiceInfo, text::text_to_ssml
+};
+
+/// Wraps a [`SpeechSynthesiser`] to honour [`UtteranceConfig::voice_style`], [`UtteranceConfig::voice_style_degree`]
+/// & [`UtteranceConfig::voice_role`] for text input.
+///
+/// When a style or role is configured, text input is wrapped in an `<mstts:express-as>` element with the configured
+/// style, degree & role, and the resulting document is synthesised via [`SpeechSynthesiser::synthesise_ssml_stream`].
+/// SSML input is passed through as-is, since styles can already be specified with [`ssml::mstts::Express`] elements.
+///
+/// If the synthesiser's [capabilities](SynthesiserCapabilities::supports_voice_roles) report that it does not support
+/// voice roles, the configured role is ignored (with a warning under the `tracing` feature).
+///
+/// Note that `<mstts:express-as>` is a Microsoft extension to SSML, and will likely be rejected or ignored by
+/// synthesisers which do not support it.
+#[derive(Debug, Clone)]
+pub struct VoiceStyleSpeechSynthesiser<S>(S);
+
+impl<S: SpeechSynthesiser> VoiceStyleSpeechSynthesiser<S> {
+	pub fn new(synthesiser: S) -> Self {
+		VoiceStyleSpeechSynthesiser(synthesiser)
+	}
+
+	pub fn inner(&self) -> &S {
+		&self.0
+	}
+
+	pub fn into_inner(self) -> S {
+		self.0
+	}
+}
+
+impl<S: SpeechSynthesiser + Sync> SpeechSynthesiser for VoiceStyleSpeechSynthesiser<S> {
+	type Error = S::Error;
+
+	fn negotiate_audio_format(&self, pref: &AudioFormatPreference) -> Option<AudioFormat> {
+		self.0.negotiate_audio_format(pref)
+	}
+
+	fn capabilities(&self) -> SynthesiserCapabilities {
+		self.0.capabilities()
+	}
+
+	fn validate_ssml(&self, input: &ssml::Speak<'_>) -> Result<(), Vec<SsmlValidationError>> {
+		self.0.validate_ssml(input)
+	}
+
+	fn estimate_duration(&self, text: &str, config: &UtteranceConfig) -> Option<Duration> {
+		self.0.estimate_duration(text, config)
+	}
+
+	fn supported_sample_rates(&self) -> &[u32] {
+		self.0.supported_sample_rates()
+	}
+
+	fn supported_containers(&self) -> &[AudioContainer] {
+		self.0.supported_containers()
+	}
+
+	fn synthesise_ssml_stream(
+		&self,
+		input: &ssml::Speak<'_>,
+		audio_format: &AudioFormat,
+		config: &UtteranceConfig
+	) -> impl Future<Output = Result<impl UtteranceEventStream<Self::Error> + 'static, Self::Error>> + Send {
+		self.0.synthesise_ssml_stream(input, audio_format, config)
+	}
+
+	async fn synthesise_text_stream(
+		&self,
+		input: &str,
+		audio_format: &AudioFormat,
+		config: &UtteranceConfig
+	) -> Result<impl UtteranceEventStream<Self::Error> + 'static, Self::Error> {
+		let mut config = Cow::Borrowed(config);
+		if config.voice_role.is_some() && !self.0.capabilities().supports_voice_roles {
+			#[cfg(feature = "tracing")]
+			tracing::warn!(role = ?config.voice_role, "synthesiser does not support voice roles; ignoring configured role");
+			config.to_mut().voice_role = None;
+		}
+		let config = &*config;
+		let stream: Pin<Box<dyn UtteranceEventStream<Self::Error>>> = match (&config.voice_style, config.voice_role) {
+			(None, None) => Box::pin(self.0.synthesise_text_stream(input, audio_format, config).await?),
+			_ => Box::pin(self.0.synthesise_ssml_stream(&text_to_ssml(input, config), audio_format, config).await?)
+		};
+		Ok(stream)
+	}
+
+	fn list_voices(&self) -> impl Future<Output = Result<Vec<VoiceInfo>, Self::Error>> + Send {
+		self.0.list_voices()
+	}
+
+	fn supported_languages(&self) -> impl Future<Output = Result<Vec<LanguageInfo>, Self::Error>> + Send {
+		self.0.supported_languages()
+	}
+
+	fn health_check(&self) -> impl Future<Output = Result<(), Self::Error>> + Send {
+		self.0.health_check()
+	}
+}
@@ -0,0 +1,99 @@
+//! Generate WebVTT & SRT subtitles from the boundary events of an [`UtteranceEventStream`].
+//!
+//! Subtitles require boundary events to be emitted by the synthesiser; see
+//! [`UtteranceConfig::with_emit_sentence_boundary_events`](crate::UtteranceConfig::with_emit_sentence_boundary_events)
+//! and [`UtteranceConfig::with_emit_word_boundary_events`](crate::UtteranceConfig::with_emit_word_boundary_events).
+
+use core::{fmt::Write, pin::pin};
+
+use crate::{UtteranceEvent, UtteranceEventStream, stream::next};
+
+struct Cue {
+	from_millis: f32,
+	to_millis: f32,
+	text: String
+}
+
+/// Drives the stream to completion and collects subtitle cues.
+///
+/// Each cue corresponds to one sentence boundary, or one word boundary if the stream emitted no sentence boundaries.
+/// Cues with empty text are skipped, exact duplicates are removed, and overlapping cues are merged.
+async fn collect_cues<E>(stream: impl UtteranceEventStream<E>) -> Result<Vec<Cue>, E> {
+	let mut stream = pin!(stream);
+	let mut sentences = Vec::new();
+	let mut words = Vec::new();
+	while let Some(event) = next(stream.as_mut()).await {
+		match event? {
+			UtteranceEvent::SentenceBoundary { from_millis, to_millis, text } => sentences.push((from_millis, to_millis, text)),
+			UtteranceEvent::WordBoundary { from_millis, to_millis, text, .. } => words.push((from_millis, to_millis, text)),
+			_ => {}
+		}
+	}
+
+	let mut boundaries = if sentences.is_empty() { words } else { sentences };
+	boundaries.retain(|(_, _, text)| !text.trim().is_empty());
+	boundaries.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+	let mut cues: Vec<Cue> = Vec::with_capacity(boundaries.len());
+	for (from_millis, to_millis, text) in boundaries {
+		let text = text.trim();
+		if let Some(last) = cues.last_mut() {
+			if last.from_millis == from_millis && last.to_millis == to_millis && last.text == text {
+				continue;
+			}
+			if from_millis < last.to_millis {
+				last.to_millis = last.to_millis.max(to_millis);
+				last.text.push(' ');
+				last.text.push_str(text);
+				continue;
+			}
+		}
+		cues.push(Cue {
+			from_millis,
+			to_millis,
+			text: text.to_string()
+		});
+	}
+	Ok(cues)
+}
+
+fn write_timestamp(out: &mut String, millis: f32, fraction_separator: char) {
+	let millis = millis.max(0.0).round() as u64;
+	let (hours, minutes, seconds, millis) = (millis / 3_600_000, (millis / 60_000) % 60, (millis / 1000) % 60, millis % 1000);
+	let _ = write!(out, "{hours:02}:{minutes:02}:{seconds:02}{fraction_separator}{millis:03}");
+}
+
+fn write_cues(out: &mut String, cues: &[Cue], fraction_separator: char) {
+	for (i, cue) in cues.iter().enumerate() {
+		let _ = writeln!(out, "{}", i + 1);
+		write_timestamp(out, cue.from_millis, fraction_separator);
+		out.push_str(" --> ");
+		write_timestamp(out, cue.to_millis, fraction_separator);
+		out.push('\n');
+		out.push_str(&cue.text);
+		out.push_str("\n\n");
+	}
+}
+
+/// Drives the stream to completion, generating a [WebVTT](https://www.w3.org/TR/webvtt1/) subtitle document from its
+/// boundary events.
+///
+/// Each cue corresponds to one [`UtteranceEvent::SentenceBoundary`], falling back to
+/// [`UtteranceEvent::WordBoundary`] events if the stream did not emit any sentence boundaries. Boundaries with empty
+/// text are skipped, duplicate boundaries are removed, and overlapping boundaries are merged into a single cue.
+pub async fn stream_to_webvtt<E>(stream: impl UtteranceEventStream<E>) -> Result<String, E> {
+	let cues = collect_cues(stream).await?;
+	let mut out = String::from("WEBVTT\n\n");
+	write_cues(&mut out, &cues, '.');
+	Ok(out)
+}
+
+/// Drives the stream to completion, generating an SRT (SubRip) subtitle document from its boundary events.
+///
+/// Cues are generated in the same manner as [`stream_to_webvtt`].
+pub async fn stream_to_srt<E>(stream: impl UtteranceEventStream<E>) -> Result<String, E> {
+	let cues = collect_cues(stream).await?;
+	let mut out = String::new();
+	write_cues(&mut out, &cues, ',');
+	Ok(out)
+}
@@ -0,0 +1,249 @@
+//! Utilities for testing code which uses a [`SpeechSynthesiser`].
+
+use core::{
+	fmt,
+	future::Future,
+	pin::Pin,
+	task::{Context, Poll},
+	time::Duration
+};
+use std::{
+	collections::VecDeque,
+	sync::{Mutex, MutexGuard}
+};
+
+use futures_core::Stream;
+use futures_timer::Delay;
+
+use crate::{AudioFormat, AudioFormatPreference, SpeechSynthesiser, SynthesiserCapabilities, UtteranceConfig, UtteranceEvent, UtteranceEventStream};
+
+/// An error returned by a [`MockSpeechSynthesiser`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MockError {
+	/// Synthesis was requested, but no responses were queued with [`MockSpeechSynthesiser::push_response`].
+	NoResponse,
+	/// An error injected via [`MockSpeechSynthesiser::with_error_at_chunk`].
+	Injected
+}
+
+impl fmt::Display for MockError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			MockError::NoResponse => f.write_str("no mock responses are queued"),
+			MockError::Injected => f.write_str("injected mock error")
+		}
+	}
+}
+
+impl std::error::Error for MockError {}
+
+/// A [`SpeechSynthesiser`] which returns pre-programmed responses, for use in tests.
+///
+/// Each call to `synthesise_*_stream` takes the next response queued with [`MockSpeechSynthesiser::push_response`],
+/// regardless of the input, and returns a stream which yields the response's events in order, followed by its audio
+/// as a single [`UtteranceEvent::AudioChunk`]. [`UtteranceConfig::custom_endpoint`] is ignored.
+#[derive(Debug, Default)]
+pub struct MockSpeechSynthesiser {
+	responses: Mutex<VecDeque<Vec<UtteranceEvent>>>,
+	supported_format: Mutex<Option<AudioFormat>>,
+	capabilities: SynthesiserCapabilities,
+	simulate_latency: Duration,
+	error_at_chunk: Option<usize>
+}
+
+impl MockSpeechSynthesiser {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Configures the capabilities to be returned by [`SpeechSynthesiser::capabilities`].
+	pub fn with_capabilities(mut self, capabilities: SynthesiserCapabilities) -> Self {
+		self.capabilities = capabilities;
+		self
+	}
+
+	/// Configures a delay to wait before each item is yielded from a stream.
+	pub fn with_simulated_latency(mut self, latency: Duration) -> Self {
+		self.simulate_latency = latency;
+		self
+	}
+
+	/// Configures streams to yield [`MockError::Injected`] in place of the [`UtteranceEvent::AudioChunk`] at index
+	/// `index`, ending the stream afterwards. Only audio chunks are counted; any events before the failing chunk are
+	/// yielded as usual.
+	pub fn with_error_at_chunk(mut self, index: usize) -> Self {
+		self.error_at_chunk = Some(index);
+		self
+	}
+
+	/// Queues a response to be returned by the next synthesis request.
+	pub fn push_response(&self, audio: Vec<u8>, events: Vec<UtteranceEvent>) {
+		let mut events = events;
+		if !audio.is_empty() {
+			events.push(UtteranceEvent::AudioChunk(audio.into_boxed_slice()));
+		}
+		lock(&self.responses).push_back(events);
+	}
+
+	/// Sets the format to be returned by [`SpeechSynthesiser::negotiate_audio_format`], regardless of preference.
+	pub fn set_supported_format(&self, format: AudioFormat) {
+		*lock(&self.supported_format) = Some(format);
+	}
+
+	/// Returns the number of queued responses which have not yet been returned.
+	pub fn remaining_responses(&self) -> usize {
+		lock(&self.responses).len()
+	}
+
+	fn next_stream(&self) -> Result<MockStream, MockError> {
+		let events = lock(&self.responses).pop_front().ok_or(MockError::NoResponse)?;
+		Ok(MockStream {
+			events: events.into(),
+			chunk_index: 0,
+			error_at_chunk: self.error_at_chunk,
+			latency: self.simulate_latency,
+			delay: None
+		})
+	}
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+	mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+impl SpeechSynthesiser for MockSpeechSynthesiser {
+	type Error = MockError;
+
+	fn negotiate_audio_format(&self, _: &AudioFormatPreference) -> Option<AudioFormat> {
+		lock(&self.supported_format).clone()
+	}
+
+	fn capabilities(&self) -> SynthesiserCapabilities {
+		self.capabilities.clone()
+	}
+
+	fn synthesise_ssml_stream(
+		&self,
+		_: &ssml::Speak<'_>,
+		_: &AudioFormat,
+		_: &UtteranceConfig
+	) -> impl Future<Output = Result<impl UtteranceEventStream<Self::Error> + 'static, Self::Error>> + Send {
+		let stream = self.next_stream();
+		async move { stream }
+	}
+
+	fn synthesise_text_stream(
+		&self,
+		_: &str,
+		_: &AudioFormat,
+		_: &UtteranceConfig
+	) -> impl Future<Output = Result<impl UtteranceEventStream<Self::Error> + 'static, Self::Error>> + Send {
+		let stream = self.next_stream();
+		async move { stream }
+	}
+}
+
+struct MockStream {
+	events: VecDeque<UtteranceEvent>,
+	/// The number of audio chunks yielded so far.
+	chunk_index: usize,
+	error_at_chunk: Option<usize>,
+	latency: Duration,
+	delay: Option<Delay>
+}
+
+impl Stream for MockStream {
+	type Item = Result<UtteranceEvent, MockError>;
+
+	fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		if self.events.is_empty() {
+			return Poll::Ready(None);
+		}
+
+		if !self.latency.is_zero() {
+			let latency = self.latency;
+			let delay = self.delay.get_or_insert_with(|| Delay::new(latency));
+			if Pin::new(delay).poll(cx).is_pending() {
+				return Poll::Pending;
+			}
+			self.delay = None;
+		}
+
+		if let Some(UtteranceEvent::AudioChunk(_)) = self.events.front() {
+			let index = self.chunk_index;
+			self.chunk_index += 1;
+			if self.error_at_chunk == Some(index) {
+				self.events.clear();
+				return Poll::Ready(Some(Err(MockError::Injected)));
+			}
+		}
+		Poll::Ready(self.events.pop_front().map(Ok))
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(self.events.len(), Some(self.events.len()))
+	}
+}
+
+/// Asserts that `synthesiser` negotiates exactly `format` when it is preferred, as with
+/// [`AudioFormatPreference::from_audio_format`], i.e. to verify that a synthesiser's
+/// [`negotiate_audio_format`](SpeechSynthesiser::negotiate_audio_format) honours a format it supports.
+///
+/// # Panics
+/// Panics if negotiation fails or returns a different format.
+#[track_caller]
+pub fn assert_format_roundtrip(synthesiser: &impl SpeechSynthesiser, format: AudioFormat) {
+	match synthesiser.negotiate_audio_format(&AudioFormatPreference::from_audio_format(&format)) {
+		Some(negotiated) => assert_eq!(negotiated, format, "synthesiser negotiated a different format than the one preferred"),
+		None => panic!("synthesiser failed to negotiate {format:?}")
+	}
+}
+
+/// Asserts that `synthesiser` fails negotiation when `format`'s sample rate, channels, and container are required, i.e.
+/// to verify that a synthesiser's [`negotiate_audio_format`](SpeechSynthesiser::negotiate_audio_format) does not
+/// return a format other than the one required.
+///
+/// # Panics
+/// Panics if negotiation succeeds.
+#[track_caller]
+pub fn assert_negotiation_rejects(synthesiser: &impl SpeechSynthesiser, format: AudioFormat) {
+	let pref = AudioFormatPreference::from_audio_format(&format)
+		.require_sample_rate(format.sample_rate())
+		.require_channels(format.channels())
+		.require_container(format.container());
+	if let Some(negotiated) = synthesiser.negotiate_audio_format(&pref) {
+		panic!("synthesiser negotiated {negotiated:?} despite being required to use {format:?}");
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use core::pin::pin;
+
+	use super::{MockError, MockSpeechSynthesiser};
+	use crate::{AudioChannels, AudioContainer, AudioEncoding, AudioFormat, SpeechSynthesiser, UtteranceConfig, UtteranceEvent, stream};
+
+	#[tokio::test]
+	async fn error_at_chunk_counts_only_audio_chunks() {
+		let synthesiser = MockSpeechSynthesiser::new().with_error_at_chunk(1);
+		let events = vec![
+			UtteranceEvent::SsmlMark { at_millis: 0.0, mark: "a".into() },
+			UtteranceEvent::AudioChunk(vec![0; 16].into_boxed_slice()),
+			UtteranceEvent::SsmlMark { at_millis: 10.0, mark: "b".into() },
+		];
+		synthesiser.push_response(vec![0; 16], events);
+
+		let format = AudioFormat::new(16000, AudioChannels::Mono, None, AudioContainer::Raw(AudioEncoding::PcmI16Le));
+		let stream = synthesiser
+			.synthesise_text_stream("", &format, &UtteranceConfig::default())
+			.await
+			.unwrap();
+		let mut stream = pin!(stream);
+		assert!(matches!(stream::next(stream.as_mut()).await, Some(Ok(UtteranceEvent::SsmlMark { .. }))));
+		assert!(matches!(stream::next(stream.as_mut()).await, Some(Ok(UtteranceEvent::AudioChunk(_)))));
+		assert!(matches!(stream::next(stream.as_mut()).await, Some(Ok(UtteranceEvent::SsmlMark { .. }))));
+		assert_eq!(stream::next(stream.as_mut()).await.map(|item| item.err()), Some(Some(MockError::Injected)));
+		assert!(stream::next(stream.as_mut()).await.is_none());
+	}
+}
@@ -0,0 +1,224 @@
+use ssml::{CustomElement, Element, Speak, TimeDesignation, VoiceConfig, mstts::express::Expression};
+use unicode_segmentation::UnicodeSegmentation;
+
+use self::pattern::Pattern;
+use crate::UtteranceConfig;
+
+mod pattern;
+
+/// How raw text input is split into sentences before synthesis, i.e. when inserting
+/// [pauses between sentences](UtteranceConfig::inter_sentence_pause) or splitting long text across multiple requests.
+///
+/// See [`UtteranceConfig::with_sentence_detection`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SentenceDetectionMode {
+	/// Split sentences according to the [Unicode text segmentation rules](https://www.unicode.org/reports/tr29/).
+	///
+	/// This works well for most languages, but is less accurate for languages which do not separate sentences with
+	/// spaces, such as Japanese and Chinese.
+	#[default]
+	Unicode,
+	/// End a sentence after each match of a regular expression, i.e. `[。！？]+` for Japanese text.
+	///
+	/// A small subset of regular expression syntax is supported: literals, `.`, character classes (including `\d`,
+	/// `\w`, and `\s`), groups, alternation, the anchors `^` & `$`, and greedy quantifiers. Patterns which are invalid
+	/// or use unsupported syntax are reported by [`UtteranceConfig::validate`]; sentences are otherwise split as with
+	/// [`SentenceDetectionMode::Unicode`].
+	Regex(Box<str>),
+	/// Treat the entire text as a single sentence.
+	None,
+	/// Skip all sentence detection, and send raw text to the synthesiser as-is, leaving sentence detection to the
+	/// backend.
+	///
+	/// Adapters which must split text to honour an option, such as [`SentencePauseSpeechSynthesiser`], instead leave
+	/// that option to the backend too. Long text may still be split between words to honour
+	/// [`UtteranceConfig::max_chars_per_request`].
+	///
+	/// [`SentencePauseSpeechSynthesiser`]: crate::SentencePauseSpeechSynthesiser
+	Backend
+}
+
+impl SentenceDetectionMode {
+	/// Returns `true` if this is a [`SentenceDetectionMode::Regex`] with a valid pattern, or any other mode.
+	pub(crate) fn is_valid(&self) -> bool {
+		match self {
+			SentenceDetectionMode::Regex(pattern) => Pattern::new(pattern).is_some(),
+			_ => true
+		}
+	}
+}
+
+/// Splits `input` into sentences according to `mode`, returning each sentence along with its byte offset in `input`.
+/// Sentences include any surrounding whitespace, so that the sentences of the text concatenate back to `input`.
+pub(crate) fn sentence_bounds<'s>(input: &'s str, mode: &SentenceDetectionMode) -> Vec<(usize, &'s str)> {
+	if input.is_empty() {
+		return Vec::new();
+	}
+	let pattern = match mode {
+		SentenceDetectionMode::Regex(pattern) => Pattern::new(pattern),
+		SentenceDetectionMode::None | SentenceDetectionMode::Backend => return vec![(0, input)],
+		SentenceDetectionMode::Unicode => None
+	};
+	let Some(pattern) = pattern else {
+		return input.split_sentence_bound_indices().collect();
+	};
+
+	let mut sentences = Vec::new();
+	let (mut start, mut from) = (0, 0);
+	while let Some((match_start, match_end)) = pattern.find_at(input, from) {
+		if match_end == match_start {
+			// Empty matches never end a sentence.
+			match input[match_start..].chars().next() {
+				Some(c) => from = match_start + c.len_utf8(),
+				None => break
+			}
+			continue;
+		}
+		sentences.push((start, &input[start..match_end]));
+		(start, from) = (match_end, match_end);
+	}
+	if start < input.len() {
+		sentences.push((start, &input[start..]));
+	}
+	sentences
+}
+
+/// Converts raw text input into an SSML document honouring the text-only options of `config`, for adapters which
+/// implement text synthesis via
+/// [`SpeechSynthesiser::synthesise_ssml_stream`](crate::SpeechSynthesiser::synthesise_ssml_stream).
+///
+/// All such options are applied at once, so that adapters can be nested in any order.
+pub(crate) fn text_to_ssml<'s>(input: &'s str, config: &'s UtteranceConfig) -> Speak<'s> {
+	let elements = match (config.inter_sentence_pause, &config.sentence_detection) {
+		(Some(_), mode) if *mode != SentenceDetectionMode::Backend => sentence_elements(input, &[], config),
+		_ => vec![input.into()]
+	};
+	wrap_elements(elements, config)
+}
+
+/// Converts raw text input into an SSML document like [`text_to_ssml`], with a `<mark>` inserted at each of the given
+/// character offsets. The text is always split into `<s>` elements, unless sentence detection is left to the
+/// [backend](SentenceDetectionMode::Backend).
+///
+/// Marks are inserted in order of their offsets; marks with the same offset keep their relative order. Offsets past
+/// the end of the text are placed at the end of the last sentence.
+pub(crate) fn marked_text_to_ssml<'s>(input: &'s str, marks: &[(&'s str, usize)], config: &'s UtteranceConfig) -> Speak<'s> {
+	wrap_elements(sentence_elements(input, marks, config), config)
+}
+
+fn sentence_elements<'s>(input: &'s str, marks: &[(&'s str, usize)], config: &UtteranceConfig) -> Vec<Element<'s>> {
+	let mut marks = marks.to_vec();
+	marks.sort_by_key(|(_, offset)| *offset);
+	let mut marks = marks.into_iter().peekable();
+
+	let mut elements = Vec::new();
+	let mut char_start = 0;
+	for (byte_start, raw) in sentence_bounds(input, &config.sentence_detection) {
+		let is_last = byte_start + raw.len() == input.len();
+		let char_end = char_start + raw.chars().count();
+		let lead = raw.len() - raw.trim_start().len();
+		let sentence = raw.trim();
+
+		let mut children: Vec<Element<'s>> = Vec::new();
+		let mut cursor = 0;
+		while let Some((name, offset)) = marks.next_if(|(_, offset)| *offset < char_end || is_last) {
+			let pos = raw.char_indices().nth(offset.saturating_sub(char_start)).map_or(raw.len(), |(i, _)| i);
+			let pos = pos.saturating_sub(lead).min(sentence.len()).max(cursor);
+			if pos > cursor {
+				children.push(sentence[cursor..pos].into());
+			}
+			children.push(ssml::mark(name).into());
+			cursor = pos;
+		}
+		if cursor < sentence.len() {
+			children.push(sentence[cursor..].into());
+		}
+		char_start = char_end;
+		if children.is_empty() {
+			continue;
+		}
+
+		if let (Some(pause), false) = (config.inter_sentence_pause, elements.is_empty()) {
+			elements.push(ssml::breaks(TimeDesignation::from_millis(pause.as_secs_f32() * 1000.0)).into());
+		}
+		match config.sentence_detection {
+			SentenceDetectionMode::Backend => elements.extend(children),
+			_ => elements.push(CustomElement::new("s").with_children(children).into())
+		}
+	}
+	// Only reachable for empty input, which has no sentences to hold the marks.
+	elements.extend(marks.map(|(name, _)| Element::from(ssml::mark(name))));
+	elements
+}
+
+fn wrap_elements<'s>(mut elements: Vec<Element<'s>>, config: &'s UtteranceConfig) -> Speak<'s> {
+	if let Some(role) = config.voice_role {
+		// `ssml::mstts::Express` has no `role` attribute, so the element is constructed manually.
+		let mut express = CustomElement::new("mstts:express-as");
+		if let Some(style) = config.voice_style.as_deref() {
+			express = express
+				.with_attr("style", style.to_string())
+				.with_attr("styledegree", config.voice_style_degree.unwrap_or(1.0).to_string());
+		}
+		elements = vec![express.with_attr("role", role.as_str()).with_children(elements).into()];
+	} else if let Some(style) = config.voice_style.as_deref() {
+		let expression = Expression::new(style.to_string()).with_degree(config.voice_style_degree.unwrap_or(1.0));
+		elements = vec![ssml::mstts::express(expression, elements).into()];
+	}
+	// SSML synthesis ignores the configured voice, so it must be specified in the document instead.
+	if let Some(voice) = config.voice.as_deref() {
+		elements = vec![ssml::voice(VoiceConfig::named(voice), elements).into()];
+	}
+	Speak::new(config.language.as_deref(), elements)
+}
+
+/// Splits raw text input into chunks of at most `max_chars` characters each, for adapters which synthesise long text
+/// across multiple requests.
+///
+/// Chunks are split at sentence boundaries where possible. Sentences longer than `max_chars` are split at word
+/// boundaries, and words longer than `max_chars` are split between characters. Sentences are detected according to
+/// `mode`.
+pub(crate) fn split_text(input: &str, max_chars: usize, mode: &SentenceDetectionMode) -> Vec<String> {
+	let max_chars = max_chars.max(1);
+	let mut chunks = Vec::new();
+	let mut current = String::new();
+	let mut current_chars = 0;
+	let mut push = |piece: &str, piece_chars: usize, split: bool| {
+		if split || current_chars + piece_chars > max_chars {
+			chunks.push(core::mem::take(&mut current));
+			current_chars = 0;
+		}
+		current.push_str(piece);
+		current_chars += piece_chars;
+	};
+	for (_, sentence) in sentence_bounds(input, mode) {
+		let sentence_chars = sentence.chars().count();
+		if sentence_chars <= max_chars {
+			push(sentence, sentence_chars, false);
+			continue;
+		}
+		// Overly long sentences begin a new chunk, so that only the sentence itself is split mid-way.
+		let mut split = true;
+		for word in sentence.split_word_bounds() {
+			let word_chars = word.chars().count();
+			if word_chars <= max_chars {
+				push(word, word_chars, core::mem::take(&mut split));
+				continue;
+			}
+			let mut rest = word;
+			while !rest.is_empty() {
+				let len = rest.char_indices().nth(max_chars).map_or(rest.len(), |(i, _)| i);
+				let (piece, tail) = rest.split_at(len);
+				push(piece, piece.chars().count(), core::mem::take(&mut split));
+				rest = tail;
+			}
+		}
+	}
+	chunks.push(current);
+	chunks
+		.into_iter()
+		.map(|chunk| chunk.trim().to_string())
+		.filter(|chunk| !chunk.is_empty())
+		.collect()
+}
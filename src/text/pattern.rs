@@ -0,0 +1,271 @@
+//! A minimal backtracking regular expression matcher, used to split text into sentences with
+//! [`SentenceDetectionMode::Regex`](crate::SentenceDetectionMode::Regex).
+//!
+//! Supports literals, `.`, character classes (`[...]`, `[^...]`, `\d`, `\w`, `\s` and their negations), groups
+//! (`(...)` & `(?:...)`), alternation, the anchors `^` & `$`, and the greedy quantifiers `*`, `+`, `?`, `{n}`, `{n,}`
+//! & `{n,m}`.
+
+use core::{iter::Peekable, str::Chars};
+
+#[derive(Debug, Clone, Copy)]
+enum Perl {
+	Digit,
+	Word,
+	Space
+}
+
+impl Perl {
+	fn matches(self, c: char) -> bool {
+		match self {
+			Perl::Digit => c.is_ascii_digit(),
+			Perl::Word => c.is_alphanumeric() || c == '_',
+			Perl::Space => c.is_whitespace()
+		}
+	}
+}
+
+#[derive(Debug)]
+enum ClassItem {
+	Range(char, char),
+	Perl(Perl, bool)
+}
+
+#[derive(Debug)]
+enum Node {
+	Char(char),
+	Any,
+	Class { items: Vec<ClassItem>, negated: bool },
+	Group(Vec<Vec<Node>>),
+	Repeat { node: Box<Node>, min: u32, max: Option<u32> },
+	Start,
+	End
+}
+
+impl Node {
+	fn matches_char(&self, c: char) -> bool {
+		match self {
+			Node::Char(x) => *x == c,
+			Node::Any => c != '\n',
+			Node::Class { items, negated } => {
+				let matched = items.iter().any(|item| match item {
+					ClassItem::Range(start, end) => (*start..=*end).contains(&c),
+					ClassItem::Perl(perl, negated) => perl.matches(c) != *negated
+				});
+				matched != *negated
+			}
+			_ => false
+		}
+	}
+}
+
+/// A compiled pattern.
+#[derive(Debug)]
+pub(crate) struct Pattern {
+	root: Node
+}
+
+impl Pattern {
+	/// Compiles `pattern`, returning `None` if it is invalid or uses unsupported syntax.
+	pub(crate) fn new(pattern: &str) -> Option<Self> {
+		let mut chars = pattern.chars().peekable();
+		let alternatives = parse_alternatives(&mut chars)?;
+		// An unmatched `)` ends parsing early.
+		if chars.next().is_some() {
+			return None;
+		}
+		Some(Pattern { root: Node::Group(alternatives) })
+	}
+
+	/// Returns the byte range of the leftmost match in `text` starting at or after the byte offset `from`.
+	pub(crate) fn find_at(&self, text: &str, from: usize) -> Option<(usize, usize)> {
+		let starts = text[from..].char_indices().map(|(i, _)| from + i).chain(core::iter::once(text.len()));
+		for start in starts {
+			let mut end = None;
+			if match_nodes(core::slice::from_ref(&self.root), text, start, &mut |p| {
+				end = Some(p);
+				true
+			}) {
+				return end.map(|end| (start, end));
+			}
+		}
+		None
+	}
+}
+
+fn parse_alternatives(chars: &mut Peekable<Chars<'_>>) -> Option<Vec<Vec<Node>>> {
+	let mut alternatives = vec![Vec::new()];
+	while let Some(&c) = chars.peek() {
+		match c {
+			')' => break,
+			'|' => {
+				chars.next();
+				alternatives.push(Vec::new());
+			}
+			_ => {
+				let atom = parse_atom(chars)?;
+				let atom = parse_quantifier(chars, atom)?;
+				alternatives.last_mut()?.push(atom);
+			}
+		}
+	}
+	Some(alternatives)
+}
+
+fn parse_atom(chars: &mut Peekable<Chars<'_>>) -> Option<Node> {
+	Some(match chars.next()? {
+		'.' => Node::Any,
+		'^' => Node::Start,
+		'$' => Node::End,
+		'(' => {
+			if chars.next_if_eq(&'?').is_some() && chars.next() != Some(':') {
+				return None;
+			}
+			let alternatives = parse_alternatives(chars)?;
+			if chars.next() != Some(')') {
+				return None;
+			}
+			Node::Group(alternatives)
+		}
+		'[' => parse_class(chars)?,
+		'\\' => match parse_escape(chars)? {
+			ClassItem::Range(c, _) => Node::Char(c),
+			item => Node::Class { items: vec![item], negated: false }
+		},
+		'*' | '+' | '?' | '{' | ')' => return None,
+		c => Node::Char(c)
+	})
+}
+
+fn parse_escape(chars: &mut Peekable<Chars<'_>>) -> Option<ClassItem> {
+	Some(match chars.next()? {
+		'd' => ClassItem::Perl(Perl::Digit, false),
+		'D' => ClassItem::Perl(Perl::Digit, true),
+		'w' => ClassItem::Perl(Perl::Word, false),
+		'W' => ClassItem::Perl(Perl::Word, true),
+		's' => ClassItem::Perl(Perl::Space, false),
+		'S' => ClassItem::Perl(Perl::Space, true),
+		'n' => ClassItem::Range('\n', '\n'),
+		'r' => ClassItem::Range('\r', '\r'),
+		't' => ClassItem::Range('\t', '\t'),
+		c if c.is_alphanumeric() => return None,
+		c => ClassItem::Range(c, c)
+	})
+}
+
+fn parse_class(chars: &mut Peekable<Chars<'_>>) -> Option<Node> {
+	let negated = chars.next_if_eq(&'^').is_some();
+	let mut items = Vec::new();
+	// A `]` at the start of the class is a literal.
+	let mut first = true;
+	loop {
+		let start = match chars.next()? {
+			']' if !first => break,
+			'\\' => match parse_escape(chars)? {
+				ClassItem::Range(c, _) => c,
+				item => {
+					items.push(item);
+					first = false;
+					continue;
+				}
+			},
+			c => c
+		};
+		first = false;
+		let mut end = start;
+		if chars.peek() == Some(&'-') {
+			let mut lookahead = chars.clone();
+			lookahead.next();
+			if let Some(&c) = lookahead.peek().filter(|&&c| c != ']') {
+				chars.next();
+				chars.next();
+				end = match c {
+					'\\' => match parse_escape(chars)? {
+						ClassItem::Range(c, _) => c,
+						ClassItem::Perl(..) => return None
+					},
+					c => c
+				};
+				if end < start {
+					return None;
+				}
+			}
+		}
+		items.push(ClassItem::Range(start, end));
+	}
+	Some(Node::Class { items, negated })
+}
+
+fn parse_quantifier(chars: &mut Peekable<Chars<'_>>, node: Node) -> Option<Node> {
+	let (min, max) = match chars.peek() {
+		Some('*') => (0, None),
+		Some('+') => (1, None),
+		Some('?') => (0, Some(1)),
+		Some('{') => {
+			chars.next();
+			let mut bounds = String::new();
+			loop {
+				match chars.next()? {
+					'}' => break,
+					c => bounds.push(c)
+				}
+			}
+			let (min, max) = match bounds.split_once(',') {
+				Some((min, "")) => (min.parse().ok()?, None),
+				Some((min, max)) => (min.parse().ok()?, Some(max.parse().ok()?)),
+				None => {
+					let n = bounds.parse().ok()?;
+					(n, Some(n))
+				}
+			};
+			if max.is_some_and(|max| max < min) {
+				return None;
+			}
+			return parse_quantifier_suffix(chars, Node::Repeat { node: Box::new(node), min, max });
+		}
+		_ => return Some(node)
+	};
+	chars.next();
+	parse_quantifier_suffix(chars, Node::Repeat { node: Box::new(node), min, max })
+}
+
+fn parse_quantifier_suffix(chars: &mut Peekable<Chars<'_>>, node: Node) -> Option<Node> {
+	// Quantifiers may not be stacked, and lazy quantifiers are not supported.
+	match chars.peek() {
+		Some('*' | '+' | '?' | '{') => None,
+		_ => Some(node)
+	}
+}
+
+/// Matches `nodes` against `text` at the byte offset `pos`, calling `k` with the end of each match (longest first)
+/// until it returns `true`.
+fn match_nodes(nodes: &[Node], text: &str, pos: usize, k: &mut dyn FnMut(usize) -> bool) -> bool {
+	let Some((node, rest)) = nodes.split_first() else {
+		return k(pos);
+	};
+	match node {
+		Node::Start => pos == 0 && match_nodes(rest, text, pos, k),
+		Node::End => pos == text.len() && match_nodes(rest, text, pos, k),
+		Node::Group(alternatives) => alternatives
+			.iter()
+			.any(|alternative| match_nodes(alternative, text, pos, &mut |p| match_nodes(rest, text, p, k))),
+		Node::Repeat { node, min, max } => match_repeat(node, *min, *max, 0, rest, text, pos, k),
+		node => match text[pos..].chars().next() {
+			Some(c) if node.matches_char(c) => match_nodes(rest, text, pos + c.len_utf8(), k),
+			_ => false
+		}
+	}
+}
+
+#[allow(clippy::too_many_arguments)]
+fn match_repeat(node: &Node, min: u32, max: Option<u32>, count: u32, rest: &[Node], text: &str, pos: usize, k: &mut dyn FnMut(usize) -> bool) -> bool {
+	if max.map_or(true, |max| count < max) {
+		let matched = match_nodes(core::slice::from_ref(node), text, pos, &mut |p| {
+			// An empty iteration can never make progress, so it only counts towards the minimum.
+			(p != pos || count < min) && match_repeat(node, min, max, count + 1, rest, text, p, k)
+		});
+		if matched {
+			return true;
+		}
+	}
+	count >= min && match_nodes(rest, text, pos, k)
+}
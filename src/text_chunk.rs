@@ -0,0 +1,218 @@
+use core::{
+	future::Future,
+	pin::Pin,
+	task::{Context, Poll},
+	time::Duration
+};
+use std::{collections::VecDeque, sync::Arc, time::Instant};
+
+use futures_core::Stream;
+
+use crate::{
+	AudioContainer, AudioFormat, AudioFormatPreference, LanguageInfo, SpeechSynthesiser, SsmlValidationError, SynthesiserCapabilities, UtteranceConfig,
+	UtteranceEvent, UtteranceEventStream, VoiceInfo,
+	stream::{with_synthesis_events_for_request, with_timestamp_offset},
+	text::split_text
+};
+
+type ChunkStream<E> = Pin<Box<dyn UtteranceEventStream<E>>>;
+type StartFuture<E> = Pin<Box<dyn Future<Output = Result<ChunkStream<E>, E>> + Send>>;
+
+/// Wraps a [`SpeechSynthesiser`] to honour [`UtteranceConfig::max_chars_per_request`].
+///
+/// Text input longer than the configured limit is split into chunks according to the Unicode sentence boundary rules
+/// (falling back to word boundaries for overly long sentences), and each chunk is synthesised with a separate call to
+/// [`SpeechSynthesiser::synthesise_text_stream`]. The next chunk is only requested once the previous chunk's stream has
+/// ended. If the byte rate of the audio format is known (see [`AudioFormat::bytes_per_second`]), event timestamps are
+/// offset by the duration of the preceding chunks, so the combined stream appears as a single utterance.
+///
+/// SSML input is passed through as-is, since it cannot be split without altering its meaning.
+///
+/// Since the returned stream must outlive the synthesiser reference, the synthesiser is cloned into the stream when
+/// text is split; synthesisers which are expensive to clone should share their state via an [`Arc`].
+///
+/// Note that for containers with headers (e.g. [`AudioContainer::Riff`](crate::AudioContainer::Riff)), each chunk's
+/// audio will include its own header.
+#[derive(Debug, Clone)]
+pub struct TextChunkingSpeechSynthesiser<S>(S);
+
+impl<S: SpeechSynthesiser> TextChunkingSpeechSynthesiser<S> {
+	pub fn new(synthesiser: S) -> Self {
+		TextChunkingSpeechSynthesiser(synthesiser)
+	}
+
+	pub fn inner(&self) -> &S {
+		&self.0
+	}
+
+	pub fn into_inner(self) -> S {
+		self.0
+	}
+}
+
+fn start_chunk<S: SpeechSynthesiser + Send + Sync + 'static>(
+	synthesiser: Arc<S>,
+	chunk: String,
+	audio_format: AudioFormat,
+	config: UtteranceConfig,
+	offset_millis: f32
+) -> StartFuture<S::Error> {
+	Box::pin(async move {
+		let stream = synthesiser.synthesise_text_stream(&chunk, &audio_format, &config).await?;
+		Ok(Box::pin(with_timestamp_offset(stream, offset_millis)) as ChunkStream<S::Error>)
+	})
+}
+
+struct TextChunkStream<S: SpeechSynthesiser> {
+	synthesiser: Arc<S>,
+	audio_format: AudioFormat,
+	config: UtteranceConfig,
+	chunks: VecDeque<String>,
+	starting: Option<StartFuture<S::Error>>,
+	current: Option<ChunkStream<S::Error>>,
+	/// The number of audio bytes emitted by all chunks so far.
+	audio_bytes: u64,
+	finished: bool
+}
+
+impl<S: SpeechSynthesiser + Send + Sync + 'static> Stream for TextChunkStream<S> {
+	type Item = Result<UtteranceEvent, S::Error>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let this = self.get_mut();
+		loop {
+			if this.finished {
+				return Poll::Ready(None);
+			}
+
+			if let Some(current) = this.current.as_mut() {
+				match current.as_mut().poll_next(cx) {
+					// Each chunk's sentinels are replaced by a single pair bracketing the combined stream.
+					Poll::Ready(Some(Ok(UtteranceEvent::SynthesisStarted { .. } | UtteranceEvent::SynthesisCompleted { .. }))) => continue,
+					Poll::Ready(Some(Ok(event))) => {
+						if let UtteranceEvent::AudioChunk(chunk) = &event {
+							this.audio_bytes += chunk.len() as u64;
+						}
+						return Poll::Ready(Some(Ok(event)));
+					}
+					Poll::Ready(Some(Err(e))) => {
+						this.finished = true;
+						return Poll::Ready(Some(Err(e)));
+					}
+					Poll::Ready(None) => {
+						this.current = None;
+						continue;
+					}
+					Poll::Pending => return Poll::Pending
+				}
+			}
+
+			if let Some(starting) = this.starting.as_mut() {
+				match starting.as_mut().poll(cx) {
+					Poll::Ready(Ok(stream)) => {
+						this.starting = None;
+						this.current = Some(stream);
+						continue;
+					}
+					Poll::Ready(Err(e)) => {
+						this.finished = true;
+						return Poll::Ready(Some(Err(e)));
+					}
+					Poll::Pending => return Poll::Pending
+				}
+			}
+
+			let Some(chunk) = this.chunks.pop_front() else {
+				this.finished = true;
+				return Poll::Ready(None);
+			};
+			let offset_millis = this
+				.audio_format
+				.duration_from_byte_count(this.audio_bytes)
+				.map_or(0.0, |offset| offset.as_secs_f32() * 1000.0);
+			this.starting = Some(start_chunk(Arc::clone(&this.synthesiser), chunk, this.audio_format.clone(), this.config.clone(), offset_millis));
+		}
+	}
+}
+
+impl<S: SpeechSynthesiser + Clone + Send + Sync + 'static> SpeechSynthesiser for TextChunkingSpeechSynthesiser<S> {
+	type Error = S::Error;
+
+	fn negotiate_audio_format(&self, pref: &AudioFormatPreference) -> Option<AudioFormat> {
+		self.0.negotiate_audio_format(pref)
+	}
+
+	fn capabilities(&self) -> SynthesiserCapabilities {
+		self.0.capabilities()
+	}
+
+	fn validate_ssml(&self, input: &ssml::Speak<'_>) -> Result<(), Vec<SsmlValidationError>> {
+		self.0.validate_ssml(input)
+	}
+
+	fn estimate_duration(&self, text: &str, config: &UtteranceConfig) -> Option<Duration> {
+		self.0.estimate_duration(text, config)
+	}
+
+	fn supported_sample_rates(&self) -> &[u32] {
+		self.0.supported_sample_rates()
+	}
+
+	fn supported_containers(&self) -> &[AudioContainer] {
+		self.0.supported_containers()
+	}
+
+	fn synthesise_ssml_stream(
+		&self,
+		input: &ssml::Speak<'_>,
+		audio_format: &AudioFormat,
+		config: &UtteranceConfig
+	) -> impl Future<Output = Result<impl UtteranceEventStream<Self::Error> + 'static, Self::Error>> + Send {
+		self.0.synthesise_ssml_stream(input, audio_format, config)
+	}
+
+	async fn synthesise_text_stream(
+		&self,
+		input: &str,
+		audio_format: &AudioFormat,
+		config: &UtteranceConfig
+	) -> Result<impl UtteranceEventStream<Self::Error> + 'static, Self::Error> {
+		let chunks = match config.max_chars_per_request {
+			Some(max_chars) if input.chars().count() > max_chars => split_text(input, max_chars, &config.sentence_detection),
+			_ => return Ok(Box::pin(self.0.synthesise_text_stream(input, audio_format, config).await?) as ChunkStream<Self::Error>)
+		};
+
+		let requested_at = Instant::now();
+		let synthesiser = Arc::new(self.0.clone());
+		let mut chunks = VecDeque::from(chunks);
+		// The first chunk is requested immediately, so that a failure to begin synthesis is reported by this future, like
+		// any other synthesiser.
+		let first = match chunks.pop_front() {
+			Some(chunk) => Some(start_chunk(Arc::clone(&synthesiser), chunk, audio_format.clone(), config.clone(), 0.0).await?),
+			None => None
+		};
+		let stream = TextChunkStream {
+			synthesiser,
+			audio_format: audio_format.clone(),
+			config: config.clone(),
+			chunks,
+			starting: None,
+			current: first,
+			audio_bytes: 0,
+			finished: false
+		};
+		Ok(Box::pin(with_synthesis_events_for_request(stream, requested_at, config.request_id_or_generate())) as ChunkStream<Self::Error>)
+	}
+
+	fn list_voices(&self) -> impl Future<Output = Result<Vec<VoiceInfo>, Self::Error>> + Send {
+		self.0.list_voices()
+	}
+
+	fn supported_languages(&self) -> impl Future<Output = Result<Vec<LanguageInfo>, Self::Error>> + Send {
+		self.0.supported_languages()
+	}
+
+	fn health_check(&self) -> impl Future<Output = Result<(), Self::Error>> + Send {
+		self.0.health_check()
+	}
+}
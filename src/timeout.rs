@@ -0,0 +1,283 @@
+use core::{
+	future::{Future, poll_fn},
+	pin::{Pin, pin},
+	task::{Context, Poll},
+	time::Duration
+};
+
+use futures_core::Stream;
+use futures_timer::Delay;
+use pin_project_lite::pin_project;
+
+use crate::{
+	AudioContainer, AudioFormat, AudioFormatPreference, LanguageInfo, SpeechSynthesiser, SsmlValidationError, SynthesisError, SynthesiserCapabilities,
+	UtteranceConfig, UtteranceEvent, UtteranceEventStream, VoiceInfo
+};
+
+/// Wraps a [`SpeechSynthesiser`] to honour [`UtteranceConfig::synthesis_timeout`] & [`UtteranceConfig::chunk_timeout`].
+///
+/// If the synthesiser does not return a stream within the synthesis timeout, the synthesis future resolves to
+/// [`SynthesisError::Timeout`]. If the stream does not yield an item within the chunk timeout of the previous item (or
+/// of the stream being returned, for the first item), the stream yields a single [`SynthesisError::Timeout`], and then
+/// ends.
+#[derive(Debug, Clone)]
+pub struct TimedSpeechSynthesiser<S>(S);
+
+impl<S: SpeechSynthesiser> TimedSpeechSynthesiser<S> {
+	pub fn new(synthesiser: S) -> Self {
+		TimedSpeechSynthesiser(synthesiser)
+	}
+
+	pub fn inner(&self) -> &S {
+		&self.0
+	}
+
+	pub fn into_inner(self) -> S {
+		self.0
+	}
+}
+
+async fn race_timeout<T, E>(fut: impl Future<Output = Result<T, E>>, timeout: Option<Duration>) -> Result<T, SynthesisError<E>> {
+	let mut fut = pin!(fut);
+	let mut delay = timeout.map(Delay::new);
+	poll_fn(|cx| {
+		if let Poll::Ready(res) = fut.as_mut().poll(cx) {
+			return Poll::Ready(res.map_err(SynthesisError::Synthesiser));
+		}
+		match delay.as_mut() {
+			Some(delay) => Pin::new(delay).poll(cx).map(|()| Err(SynthesisError::Timeout)),
+			None => Poll::Pending
+		}
+	})
+	.await
+}
+
+pin_project! {
+	struct TimedStream<S> {
+		#[pin]
+		inner: Option<S>,
+		timeout: Option<Duration>,
+		delay: Option<Delay>
+	}
+}
+
+fn timed<E>(stream: impl UtteranceEventStream<E>, timeout: Option<Duration>) -> impl UtteranceEventStream<SynthesisError<E>> {
+	TimedStream {
+		inner: Some(stream),
+		timeout,
+		delay: timeout.map(Delay::new)
+	}
+}
+
+impl<E, S: Stream<Item = Result<UtteranceEvent, E>>> Stream for TimedStream<S> {
+	type Item = Result<UtteranceEvent, SynthesisError<E>>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let mut this = self.project();
+		let Some(inner) = this.inner.as_mut().as_pin_mut() else {
+			return Poll::Ready(None);
+		};
+
+		match inner.poll_next(cx) {
+			Poll::Ready(None) => {
+				this.inner.set(None);
+				Poll::Ready(None)
+			}
+			Poll::Ready(Some(item)) => {
+				if let (Some(delay), Some(timeout)) = (this.delay.as_mut(), *this.timeout) {
+					delay.reset(timeout);
+				}
+				Poll::Ready(Some(item.map_err(SynthesisError::Synthesiser)))
+			}
+			Poll::Pending => {
+				if this.delay.as_mut().is_some_and(|delay| Pin::new(delay).poll(cx).is_ready()) {
+					this.inner.set(None);
+					return Poll::Ready(Some(Err(SynthesisError::Timeout)));
+				}
+				Poll::Pending
+			}
+		}
+	}
+}
+
+impl<S: SpeechSynthesiser + Sync> SpeechSynthesiser for TimedSpeechSynthesiser<S> {
+	type Error = SynthesisError<S::Error>;
+
+	fn negotiate_audio_format(&self, pref: &AudioFormatPreference) -> Option<AudioFormat> {
+		self.0.negotiate_audio_format(pref)
+	}
+
+	fn capabilities(&self) -> SynthesiserCapabilities {
+		self.0.capabilities()
+	}
+
+	fn validate_ssml(&self, input: &ssml::Speak<'_>) -> Result<(), Vec<SsmlValidationError>> {
+		self.0.validate_ssml(input)
+	}
+
+	fn estimate_duration(&self, text: &str, config: &UtteranceConfig) -> Option<Duration> {
+		self.0.estimate_duration(text, config)
+	}
+
+	fn supported_sample_rates(&self) -> &[u32] {
+		self.0.supported_sample_rates()
+	}
+
+	fn supported_containers(&self) -> &[AudioContainer] {
+		self.0.supported_containers()
+	}
+
+	fn synthesise_ssml_stream(
+		&self,
+		input: &ssml::Speak<'_>,
+		audio_format: &AudioFormat,
+		config: &UtteranceConfig
+	) -> impl Future<Output = Result<impl UtteranceEventStream<Self::Error> + 'static, Self::Error>> + Send {
+		let (synthesis_timeout, chunk_timeout) = (config.synthesis_timeout, config.chunk_timeout);
+		let fut = self.0.synthesise_ssml_stream(input, audio_format, config);
+		async move { Ok(timed(race_timeout(fut, synthesis_timeout).await?, chunk_timeout)) }
+	}
+
+	fn synthesise_text_stream(
+		&self,
+		input: &str,
+		audio_format: &AudioFormat,
+		config: &UtteranceConfig
+	) -> impl Future<Output = Result<impl UtteranceEventStream<Self::Error> + 'static, Self::Error>> + Send {
+		let (synthesis_timeout, chunk_timeout) = (config.synthesis_timeout, config.chunk_timeout);
+		let fut = self.0.synthesise_text_stream(input, audio_format, config);
+		async move { Ok(timed(race_timeout(fut, synthesis_timeout).await?, chunk_timeout)) }
+	}
+
+	fn list_voices(&self) -> impl Future<Output = Result<Vec<VoiceInfo>, Self::Error>> + Send {
+		let fut = self.0.list_voices();
+		async move { fut.await.map_err(SynthesisError::Synthesiser) }
+	}
+
+	fn supported_languages(&self) -> impl Future<Output = Result<Vec<LanguageInfo>, Self::Error>> + Send {
+		let fut = self.0.supported_languages();
+		async move { fut.await.map_err(SynthesisError::Synthesiser) }
+	}
+
+	fn health_check(&self) -> impl Future<Output = Result<(), Self::Error>> + Send {
+		let fut = self.0.health_check();
+		async move { fut.await.map_err(SynthesisError::Synthesiser) }
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use core::{future::Future, pin::pin, time::Duration};
+
+	use futures_timer::Delay;
+
+	use super::TimedSpeechSynthesiser;
+	use crate::{
+		AudioChannels, AudioContainer, AudioEncoding, AudioFormat, AudioFormatPreference, SpeechSynthesiser, SynthesisError, UtteranceConfig, UtteranceEvent,
+		UtteranceEventStream, stream,
+		testing::{MockError, MockSpeechSynthesiser}
+	};
+
+	/// A backend which waits for the given duration before returning a stream.
+	struct SlowStart(MockSpeechSynthesiser, Duration);
+
+	impl SpeechSynthesiser for SlowStart {
+		type Error = MockError;
+
+		fn negotiate_audio_format(&self, pref: &AudioFormatPreference) -> Option<AudioFormat> {
+			self.0.negotiate_audio_format(pref)
+		}
+
+		fn synthesise_ssml_stream(
+			&self,
+			input: &ssml::Speak<'_>,
+			audio_format: &AudioFormat,
+			config: &UtteranceConfig
+		) -> impl Future<Output = Result<impl UtteranceEventStream<Self::Error> + 'static, Self::Error>> + Send {
+			let fut = self.0.synthesise_ssml_stream(input, audio_format, config);
+			let delay = Delay::new(self.1);
+			async move {
+				delay.await;
+				fut.await
+			}
+		}
+
+		fn synthesise_text_stream(
+			&self,
+			input: &str,
+			audio_format: &AudioFormat,
+			config: &UtteranceConfig
+		) -> impl Future<Output = Result<impl UtteranceEventStream<Self::Error> + 'static, Self::Error>> + Send {
+			let fut = self.0.synthesise_text_stream(input, audio_format, config);
+			let delay = Delay::new(self.1);
+			async move {
+				delay.await;
+				fut.await
+			}
+		}
+	}
+
+	fn format() -> AudioFormat {
+		AudioFormat::new(16000, AudioChannels::Mono, None, AudioContainer::Raw(AudioEncoding::PcmI16Le))
+	}
+
+	#[tokio::test]
+	async fn synthesis_timeout() {
+		let mock = MockSpeechSynthesiser::new();
+		mock.push_response(vec![0; 16], Vec::new());
+		let synthesiser = TimedSpeechSynthesiser::new(SlowStart(mock, Duration::from_millis(200)));
+
+		let config = UtteranceConfig::default().with_timeout(Duration::from_millis(20));
+		let result = synthesiser.synthesise_text_stream("", &format(), &config).await;
+		assert!(matches!(result, Err(SynthesisError::Timeout)));
+	}
+
+	#[tokio::test]
+	async fn slow_start_within_timeout() {
+		let mock = MockSpeechSynthesiser::new();
+		mock.push_response(vec![0; 16], Vec::new());
+		let synthesiser = TimedSpeechSynthesiser::new(SlowStart(mock, Duration::from_millis(10)));
+
+		let config = UtteranceConfig::default().with_timeout(Duration::from_millis(500));
+		let stream = synthesiser.synthesise_text_stream("", &format(), &config).await.unwrap();
+		let mut stream = pin!(stream);
+		assert!(matches!(stream::next(stream.as_mut()).await, Some(Ok(UtteranceEvent::AudioChunk(_)))));
+		assert!(stream::next(stream.as_mut()).await.is_none());
+	}
+
+	#[tokio::test]
+	async fn chunk_timeout() {
+		let mock = MockSpeechSynthesiser::new().with_simulated_latency(Duration::from_millis(200));
+		mock.push_response(vec![0; 16], Vec::new());
+		let synthesiser = TimedSpeechSynthesiser::new(mock);
+
+		let config = UtteranceConfig::default()
+			.with_timeout(Duration::from_millis(20))
+			.with_chunk_timeout(Duration::from_millis(20));
+		let stream = synthesiser.synthesise_text_stream("", &format(), &config).await.unwrap();
+		let mut stream = pin!(stream);
+		assert!(matches!(stream::next(stream.as_mut()).await, Some(Err(SynthesisError::Timeout))));
+		assert!(stream::next(stream.as_mut()).await.is_none());
+	}
+
+	#[tokio::test]
+	async fn chunk_timeout_resets_after_each_item() {
+		let mock = MockSpeechSynthesiser::new().with_simulated_latency(Duration::from_millis(30));
+		mock.push_response(
+			vec![0; 16],
+			vec![UtteranceEvent::SentenceBoundary {
+				from_millis: 0.0,
+				to_millis: 10.0,
+				text: "a".into()
+			}]
+		);
+		let synthesiser = TimedSpeechSynthesiser::new(mock);
+
+		let config = UtteranceConfig::default().with_chunk_timeout(Duration::from_millis(200));
+		let stream = synthesiser.synthesise_text_stream("", &format(), &config).await.unwrap();
+		let mut stream = pin!(stream);
+		assert!(matches!(stream::next(stream.as_mut()).await, Some(Ok(UtteranceEvent::SentenceBoundary { .. }))));
+		assert!(matches!(stream::next(stream.as_mut()).await, Some(Ok(UtteranceEvent::AudioChunk(_)))));
+		assert!(stream::next(stream.as_mut()).await.is_none());
+	}
+}
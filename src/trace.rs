@@ -0,0 +1,218 @@
+use core::{
+	future::Future,
+	pin::Pin,
+	task::{Context, Poll},
+	time::Duration
+};
+use std::{borrow::Cow, time::Instant};
+
+use futures_core::Stream;
+use pin_project_lite::pin_project;
+use tracing::{Instrument, Span, field};
+
+use crate::{
+	AudioContainer, AudioFormat, AudioFormatPreference, LanguageInfo, SpeechSynthesiser, SsmlValidationError, SynthesiserCapabilities, UtteranceConfig,
+	UtteranceEvent, UtteranceEventStream, VoiceInfo
+};
+
+/// Wraps a [`SpeechSynthesiser`] to report each synthesis as a [`tracing`] span.
+///
+/// Each call to [`SpeechSynthesiser::synthesise_ssml_stream`] or [`SpeechSynthesiser::synthesise_text_stream`] opens
+/// an info-level `synthesis` span with the following fields:
+/// - `voice`: the configured [voice](UtteranceConfig::voice), if any.
+/// - `chars`: the length of the input in bytes, for text synthesis only.
+/// - `trace_id`: the configured [trace ID](UtteranceConfig::trace_id), if any.
+/// - `request_id`: the configured [request ID](UtteranceConfig::request_id), if any. With the `uuid` feature, one is
+///   generated if none is configured, and passed on to the inner synthesiser.
+/// - `synthesis.first_chunk_latency_ms`: the time from the request until the first [`UtteranceEvent::AudioChunk`] was
+///   received.
+/// - `synthesis.total_audio_bytes` & `synthesis.event_count`: recorded once the stream ends.
+/// - `synthesis.error`: the error which ended synthesis, if any.
+///
+/// The span is entered whenever the synthesis future or stream is polled, so events logged by the inner synthesiser
+/// are attributed to it.
+#[derive(Debug, Clone)]
+pub struct TracedSpeechSynthesiser<S>(S);
+
+impl<S: SpeechSynthesiser> TracedSpeechSynthesiser<S> {
+	pub fn new(synthesiser: S) -> Self {
+		TracedSpeechSynthesiser(synthesiser)
+	}
+
+	pub fn inner(&self) -> &S {
+		&self.0
+	}
+
+	pub fn into_inner(self) -> S {
+		self.0
+	}
+}
+
+/// Fills in a generated [request ID](UtteranceConfig::request_id_or_generate) if none is configured, so that the span
+/// and the inner synthesiser report the same ID.
+fn with_request_id(config: &UtteranceConfig) -> Cow<'_, UtteranceConfig> {
+	match (&config.request_id, config.request_id_or_generate()) {
+		(None, Some(request_id)) => Cow::Owned(config.clone().with_request_id(request_id)),
+		_ => Cow::Borrowed(config)
+	}
+}
+
+fn synthesis_span(config: &UtteranceConfig, chars: Option<usize>) -> Span {
+	tracing::info_span!(
+		"synthesis",
+		voice = config.voice.as_deref(),
+		chars,
+		trace_id = config.trace_id.as_deref(),
+		request_id = config.request_id.as_deref(),
+		synthesis.first_chunk_latency_ms = field::Empty,
+		synthesis.total_audio_bytes = field::Empty,
+		synthesis.event_count = field::Empty,
+		synthesis.error = field::Empty
+	)
+}
+
+pin_project! {
+	struct TracedStream<S> {
+		#[pin]
+		inner: S,
+		span: Span,
+		requested_at: Instant,
+		received_audio: bool,
+		total_audio_bytes: u64,
+		event_count: u64
+	}
+}
+
+impl<E: core::fmt::Display, S: Stream<Item = Result<UtteranceEvent, E>>> Stream for TracedStream<S> {
+	type Item = Result<UtteranceEvent, E>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let this = self.project();
+		let _entered = this.span.enter();
+		let item = this.inner.poll_next(cx);
+		match &item {
+			Poll::Ready(Some(Ok(event))) => {
+				*this.event_count += 1;
+				if let UtteranceEvent::AudioChunk(chunk) = event {
+					if !*this.received_audio {
+						*this.received_audio = true;
+						this.span
+							.record("synthesis.first_chunk_latency_ms", this.requested_at.elapsed().as_secs_f64() * 1000.0);
+					}
+					*this.total_audio_bytes += chunk.len() as u64;
+				}
+			}
+			Poll::Ready(Some(Err(e))) => {
+				this.span.record("synthesis.error", field::display(e));
+			}
+			Poll::Ready(None) => {
+				this.span.record("synthesis.total_audio_bytes", *this.total_audio_bytes);
+				this.span.record("synthesis.event_count", *this.event_count);
+			}
+			Poll::Pending => {}
+		}
+		item
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.inner.size_hint()
+	}
+}
+
+fn traced<E>(stream: impl UtteranceEventStream<E>, span: Span, requested_at: Instant) -> impl UtteranceEventStream<E>
+where
+	E: core::fmt::Display
+{
+	TracedStream {
+		inner: stream,
+		span,
+		requested_at,
+		received_audio: false,
+		total_audio_bytes: 0,
+		event_count: 0
+	}
+}
+
+fn record_error<T, E: core::fmt::Display>(span: &Span, result: Result<T, E>) -> Result<T, E> {
+	if let Err(e) = &result {
+		span.record("synthesis.error", field::display(e));
+	}
+	result
+}
+
+impl<S: SpeechSynthesiser + Sync> SpeechSynthesiser for TracedSpeechSynthesiser<S> {
+	type Error = S::Error;
+
+	fn negotiate_audio_format(&self, pref: &AudioFormatPreference) -> Option<AudioFormat> {
+		self.0.negotiate_audio_format(pref)
+	}
+
+	fn capabilities(&self) -> SynthesiserCapabilities {
+		self.0.capabilities()
+	}
+
+	fn validate_ssml(&self, input: &ssml::Speak<'_>) -> Result<(), Vec<SsmlValidationError>> {
+		self.0.validate_ssml(input)
+	}
+
+	fn estimate_duration(&self, text: &str, config: &UtteranceConfig) -> Option<Duration> {
+		self.0.estimate_duration(text, config)
+	}
+
+	fn supported_sample_rates(&self) -> &[u32] {
+		self.0.supported_sample_rates()
+	}
+
+	fn supported_containers(&self) -> &[AudioContainer] {
+		self.0.supported_containers()
+	}
+
+	fn synthesise_ssml_stream(
+		&self,
+		input: &ssml::Speak<'_>,
+		audio_format: &AudioFormat,
+		config: &UtteranceConfig
+	) -> impl Future<Output = Result<impl UtteranceEventStream<Self::Error> + 'static, Self::Error>> + Send {
+		let config = with_request_id(config);
+		async move {
+			let span = synthesis_span(&config, None);
+			let requested_at = Instant::now();
+			let fut = span
+				.in_scope(|| self.0.synthesise_ssml_stream(input, audio_format, &config))
+				.instrument(span.clone());
+			// Boxed since the inner stream's type would otherwise borrow `config`, which may be owned by this future.
+			let stream: Pin<Box<dyn UtteranceEventStream<S::Error>>> = Box::pin(record_error(&span, fut.await)?);
+			Ok(traced(stream, span, requested_at))
+		}
+	}
+
+	fn synthesise_text_stream(
+		&self,
+		input: &str,
+		audio_format: &AudioFormat,
+		config: &UtteranceConfig
+	) -> impl Future<Output = Result<impl UtteranceEventStream<Self::Error> + 'static, Self::Error>> + Send {
+		let config = with_request_id(config);
+		async move {
+			let span = synthesis_span(&config, Some(input.len()));
+			let requested_at = Instant::now();
+			let fut = span
+				.in_scope(|| self.0.synthesise_text_stream(input, audio_format, &config))
+				.instrument(span.clone());
+			let stream: Pin<Box<dyn UtteranceEventStream<S::Error>>> = Box::pin(record_error(&span, fut.await)?);
+			Ok(traced(stream, span, requested_at))
+		}
+	}
+
+	fn list_voices(&self) -> impl Future<Output = Result<Vec<VoiceInfo>, Self::Error>> + Send {
+		self.0.list_voices()
+	}
+
+	fn supported_languages(&self) -> impl Future<Output = Result<Vec<LanguageInfo>, Self::Error>> + Send {
+		self.0.supported_languages()
+	}
+
+	fn health_check(&self) -> impl Future<Output = Result<(), Self::Error>> + Send {
+		self.0.health_check()
+	}
+}
@@ -0,0 +1,203 @@
+use core::fmt;
+
+use ssml::{Element, ProsodyPitch, ProsodyRate, ProsodyVolume, Speak};
+
+/// A problem found in an SSML document by
+/// [`SpeechSynthesiser::validate_ssml`](crate::SpeechSynthesiser::validate_ssml).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SsmlValidationError {
+	/// The path to the offending element, i.e. `/speak/voice[1]/prosody[2]`. Indices are 1-based and count siblings of
+	/// the same element type.
+	pub path: Box<str>,
+	/// A human-readable description of the problem.
+	pub message: Box<str>
+}
+
+impl SsmlValidationError {
+	pub fn new(path: impl Into<Box<str>>, message: impl Into<Box<str>>) -> Self {
+		SsmlValidationError {
+			path: path.into(),
+			message: message.into()
+		}
+	}
+}
+
+impl fmt::Display for SsmlValidationError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}: {}", self.path, self.message)
+	}
+}
+
+impl std::error::Error for SsmlValidationError {}
+
+/// Performs generic structural checks on an SSML document, per the SSML 1.1 specification.
+///
+/// This is the default implementation of [`SpeechSynthesiser::validate_ssml`](crate::SpeechSynthesiser::validate_ssml);
+/// synthesisers overriding it to add their own rules should call this to include the generic checks.
+///
+/// Checks include out-of-range prosody values, negative break & clip times, and missing required attributes.
+pub fn validate_ssml_structure(input: &Speak<'_>) -> Result<(), Vec<SsmlValidationError>> {
+	let mut validator = Validator {
+		path: String::from("/speak"),
+		errors: Vec::new()
+	};
+	validator.visit_children(input.children());
+	if validator.errors.is_empty() { Ok(()) } else { Err(validator.errors) }
+}
+
+struct Validator {
+	path: String,
+	errors: Vec<SsmlValidationError>
+}
+
+fn tag_name(element: &Element<'_>) -> Option<&'static str> {
+	Some(match element {
+		Element::Audio(_) => "audio",
+		Element::Meta(_) => "meta",
+		Element::Voice(_) => "voice",
+		Element::Break(_) => "break",
+		Element::Emphasis(_) => "emphasis",
+		Element::Mark(_) => "mark",
+		Element::SayAs(_) => "say-as",
+		Element::Lang(_) => "lang",
+		Element::Prosody(_) => "prosody",
+		Element::FlavorMSTTS(ssml::mstts::Element::Express(_)) => "mstts:express-as",
+		Element::Custom(_) => "*",
+		_ => return None
+	})
+}
+
+impl Validator {
+	fn error(&mut self, message: impl Into<Box<str>>) {
+		self.errors.push(SsmlValidationError::new(self.path.as_str(), message));
+	}
+
+	fn visit_children(&mut self, children: &[Element<'_>]) {
+		let mut counts: Vec<(&'static str, usize)> = Vec::new();
+		self.visit_siblings(children, &mut counts);
+	}
+
+	fn visit_siblings(&mut self, children: &[Element<'_>], counts: &mut Vec<(&'static str, usize)>) {
+		for child in children {
+			// Groups are transparent; their children are treated as siblings of the group.
+			if let Element::Group(group) = child {
+				self.visit_siblings(group.children(), counts);
+				continue;
+			}
+
+			let Some(tag) = tag_name(child) else {
+				continue;
+			};
+			let index = match counts.iter_mut().find(|(t, _)| *t == tag) {
+				Some((_, count)) => {
+					*count += 1;
+					*count
+				}
+				None => {
+					counts.push((tag, 1));
+					1
+				}
+			};
+
+			let len = self.path.len();
+			self.path.push('/');
+			self.path.push_str(tag);
+			self.path.push('[');
+			self.path.push_str(&index.to_string());
+			self.path.push(']');
+			self.visit_element(child);
+			self.path.truncate(len);
+		}
+	}
+
+	fn visit_element(&mut self, element: &Element<'_>) {
+		match element {
+			Element::Audio(audio) => {
+				if audio.src().trim().is_empty() {
+					self.error("audio element is missing a `src`");
+				}
+				if let Some(speed) = audio.speed() {
+					if !(speed.is_finite() && speed > 0.0) {
+						self.error(format!("audio speed must be a positive number, got {speed}"));
+					}
+				}
+				if let Some(db) = audio.sound_level() {
+					if !db.value().is_finite() {
+						self.error("audio sound level must be a finite number of decibels");
+					}
+				}
+				for time in [audio.clip_begin(), audio.clip_end()].into_iter().flatten() {
+					self.check_time("audio clip", time.to_millis());
+				}
+				if let (Some(begin), Some(end)) = (audio.clip_begin(), audio.clip_end()) {
+					if begin.to_millis() > end.to_millis() {
+						self.error("audio clip must begin before it ends");
+					}
+				}
+				self.visit_children(audio.alternate());
+			}
+			Element::Voice(voice) => {
+				let config = voice.config();
+				if config.gender.is_none() && config.age.is_none() && config.names.is_none() && config.variant.is_none() && config.languages.is_none() {
+					self.error("voice element must specify at least one of `name`, `gender`, `age`, `variant`, or `languages`");
+				}
+				self.visit_children(voice.children());
+			}
+			Element::Break(ssml::Break::Time(time)) => self.check_time("break time", time.to_millis()),
+			Element::Mark(mark) if mark.name().is_empty() => self.error("mark element is missing a `name`"),
+			Element::Emphasis(emphasis) => self.visit_children(emphasis.children()),
+			Element::Lang(lang) => self.visit_children(lang.children()),
+			Element::Prosody(prosody) => {
+				let control = prosody.control();
+				if let Some(ProsodyRate::Rate(rate)) = control.rate {
+					if !(rate.is_finite() && rate > 0.0) {
+						self.error(format!("prosody rate must be a positive number, got {rate}"));
+					}
+				}
+				for pitch in [control.pitch, control.range].into_iter().flatten() {
+					self.check_pitch(pitch);
+				}
+				if let Some(contour) = &control.contour {
+					for (position, pitch) in contour.points() {
+						if !(0.0..=100.0).contains(position) {
+							self.error(format!("prosody contour positions must be between 0% and 100%, got {position}%"));
+						}
+						self.check_pitch(*pitch);
+					}
+				}
+				if let Some(duration) = &control.duration {
+					self.check_time("prosody duration", duration.to_millis());
+				}
+				if let Some(ProsodyVolume::Db(db)) = &control.volume {
+					if !db.value().is_finite() {
+						self.error("prosody volume must be a finite number of decibels");
+					}
+				}
+				self.visit_children(prosody.children());
+			}
+			Element::FlavorMSTTS(ssml::mstts::Element::Express(express)) => {
+				let degree = express.expression().degree();
+				if !(0.01..=2.0).contains(&degree) {
+					self.error(format!("style degree must be between 0.01 and 2.0, got {degree}"));
+				}
+				self.visit_children(express.children());
+			}
+			_ => {}
+		}
+	}
+
+	fn check_time(&mut self, what: &str, millis: f32) {
+		if !(millis.is_finite() && millis >= 0.0) {
+			self.error(format!("{what} must be a non-negative duration, got {millis}ms"));
+		}
+	}
+
+	fn check_pitch(&mut self, pitch: ProsodyPitch) {
+		if let ProsodyPitch::Semitone(x) | ProsodyPitch::Hz(x) = pitch {
+			if !x.is_finite() {
+				self.error(format!("prosody pitch must be a finite number, got {x}"));
+			}
+		}
+	}
+}
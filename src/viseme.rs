@@ -0,0 +1,106 @@
+use crate::{BasicViseme, backends::AmazonViseme};
+
+/// A well-known viseme group, allowing viseme data from different synthesisers to be used interchangeably.
+///
+/// The groups follow the Microsoft SAPI viseme set, and are named after the phonemes (in
+/// [Arpabet](https://en.wikipedia.org/wiki/ARPABET) notation, as used by the CMU Pronouncing Dictionary) which share the
+/// same mouth shape. The discriminant of each variant is its SAPI viseme ID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+#[non_exhaustive]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StandardViseme {
+	/// Silence.
+	Silence = 0,
+	/// `ae`, `ax`, `ah`
+	AeAxAh = 1,
+	/// `aa`
+	Aa = 2,
+	/// `ao`
+	Ao = 3,
+	/// `ey`, `eh`, `uh`
+	EyEhUh = 4,
+	/// `er`
+	Er = 5,
+	/// `y`, `iy`, `ih`, `ix`
+	YIyIhIx = 6,
+	/// `w`, `uw`
+	WUw = 7,
+	/// `ow`
+	Ow = 8,
+	/// `aw`
+	Aw = 9,
+	/// `oy`
+	Oy = 10,
+	/// `ay`
+	Ay = 11,
+	/// `h`
+	H = 12,
+	/// `r`
+	R = 13,
+	/// `l`
+	L = 14,
+	/// `s`, `z`
+	Sz = 15,
+	/// `sh`, `ch`, `jh`, `zh`
+	ShChJhZh = 16,
+	/// `th`, `dh`
+	ThDh = 17,
+	/// `f`, `v`
+	Fv = 18,
+	/// `d`, `t`, `n`
+	Dtn = 19,
+	/// `k`, `g`, `ng`
+	KGNg = 20,
+	/// `p`, `b`, `m`
+	Pbm = 21
+}
+
+impl StandardViseme {
+	/// Returns the viseme with the given SAPI viseme ID, or `None` if the ID is out of range.
+	pub fn from_id(id: u8) -> Option<StandardViseme> {
+		Some(match id {
+			0 => StandardViseme::Silence,
+			1 => StandardViseme::AeAxAh,
+			2 => StandardViseme::Aa,
+			3 => StandardViseme::Ao,
+			4 => StandardViseme::EyEhUh,
+			5 => StandardViseme::Er,
+			6 => StandardViseme::YIyIhIx,
+			7 => StandardViseme::WUw,
+			8 => StandardViseme::Ow,
+			9 => StandardViseme::Aw,
+			10 => StandardViseme::Oy,
+			11 => StandardViseme::Ay,
+			12 => StandardViseme::H,
+			13 => StandardViseme::R,
+			14 => StandardViseme::L,
+			15 => StandardViseme::Sz,
+			16 => StandardViseme::ShChJhZh,
+			17 => StandardViseme::ThDh,
+			18 => StandardViseme::Fv,
+			19 => StandardViseme::Dtn,
+			20 => StandardViseme::KGNg,
+			21 => StandardViseme::Pbm,
+			_ => return None
+		})
+	}
+
+	/// Returns the SAPI viseme ID of this viseme.
+	pub fn id(self) -> u8 {
+		self as u8
+	}
+}
+
+impl BasicViseme {
+	/// Maps this viseme to a [`StandardViseme`], or `None` if the viseme is not recognized.
+	///
+	/// Basic visemes are interpreted as Amazon Polly viseme symbols (see [`AmazonViseme`]), with whitespace
+	/// representing silence.
+	pub fn to_standard(&self) -> Option<StandardViseme> {
+		if self.0.is_whitespace() {
+			return Some(StandardViseme::Silence);
+		}
+		u8::try_from(self.0).ok().and_then(|c| AmazonViseme(c).to_standard())
+	}
+}
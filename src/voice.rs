@@ -0,0 +1,278 @@
+/// The gender of a synthesiser's voice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VoiceGender {
+	Male,
+	Female,
+	Neutral
+}
+
+/// The apparent age of a synthesiser's voice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VoiceAge {
+	Young,
+	MiddleAged,
+	Senior
+}
+
+/// A character role which a voice can play, i.e. for voicing multiple characters in a conversation with a single
+/// voice. See [`UtteranceConfig::with_role`](crate::UtteranceConfig::with_role).
+///
+/// Roles are currently only supported by some Azure Cognitive Speech Services voices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VoiceRole {
+	Girl,
+	Boy,
+	YoungAdultFemale,
+	YoungAdultMale,
+	OlderAdultFemale,
+	OlderAdultMale,
+	SeniorFemale,
+	SeniorMale
+}
+
+impl VoiceRole {
+	/// Returns the name of this role as used in the `role` attribute of Azure's `<mstts:express-as>` element, i.e.
+	/// `YoungAdultFemale`.
+	pub fn as_str(self) -> &'static str {
+		match self {
+			VoiceRole::Girl => "Girl",
+			VoiceRole::Boy => "Boy",
+			VoiceRole::YoungAdultFemale => "YoungAdultFemale",
+			VoiceRole::YoungAdultMale => "YoungAdultMale",
+			VoiceRole::OlderAdultFemale => "OlderAdultFemale",
+			VoiceRole::OlderAdultMale => "OlderAdultMale",
+			VoiceRole::SeniorFemale => "SeniorFemale",
+			VoiceRole::SeniorMale => "SeniorMale"
+		}
+	}
+}
+
+/// Metadata describing a single voice supported by a speech synthesiser, as returned by
+/// [`SpeechSynthesiser::list_voices`](crate::SpeechSynthesiser::list_voices).
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VoiceInfo {
+	/// The name of the voice. This is the same name accepted by
+	/// [`UtteranceConfig::with_voice`](crate::UtteranceConfig::with_voice).
+	pub name: Box<str>,
+	/// The BCP-47 locale of the voice, e.g. `en-US`.
+	pub locale: Box<str>,
+	/// The gender of the voice, if known.
+	pub gender: Option<VoiceGender>,
+	/// The apparent age of the voice, if known.
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub age: Option<VoiceAge>,
+	/// Whether this is a neural voice, as opposed to a standard (concatenative or parametric) voice.
+	pub neural: bool,
+	/// Speaking styles supported by this voice, e.g. `cheerful` or `sad`, lowercased & deduplicated. Private so that
+	/// styles can only be added via [`VoiceInfo::with_styles`], which normalises them.
+	#[cfg_attr(feature = "serde", serde(deserialize_with = "deserialize_styles"))]
+	styles: Vec<Box<str>>,
+	/// The name of the voice's neutral speaking style, if it has one, e.g. `general` for Azure voices.
+	pub default_style: Option<Box<str>>,
+	/// Character roles this voice can play. See [`VoiceRole`].
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub roles: Vec<VoiceRole>
+}
+
+impl VoiceInfo {
+	pub fn new(name: impl Into<Box<str>>, locale: impl Into<Box<str>>) -> Self {
+		VoiceInfo {
+			name: name.into(),
+			locale: locale.into(),
+			gender: None,
+			age: None,
+			neural: false,
+			styles: Vec::new(),
+			default_style: None,
+			roles: Vec::new()
+		}
+	}
+
+	pub fn with_gender(mut self, gender: VoiceGender) -> Self {
+		self.gender = Some(gender);
+		self
+	}
+
+	pub fn with_age(mut self, age: VoiceAge) -> Self {
+		self.age = Some(age);
+		self
+	}
+
+	pub fn with_neural(mut self, neural: bool) -> Self {
+		self.neural = neural;
+		self
+	}
+
+	/// Adds speaking styles supported by this voice. Styles are lowercased, and styles which have already been added
+	/// are ignored.
+	pub fn with_styles<S: Into<Box<str>>>(mut self, styles: impl IntoIterator<Item = S>) -> Self {
+		add_styles(&mut self.styles, styles);
+		self
+	}
+
+	pub fn with_default_style(mut self, style: impl Into<Box<str>>) -> Self {
+		self.default_style = Some(style.into());
+		self
+	}
+
+	pub fn with_roles(mut self, roles: impl IntoIterator<Item = VoiceRole>) -> Self {
+		for role in roles {
+			if !self.roles.contains(&role) {
+				self.roles.push(role);
+			}
+		}
+		self
+	}
+
+	/// Returns the character roles this voice can play, i.e. to check that a role is supported before configuring it
+	/// with [`UtteranceConfig::with_role`](crate::UtteranceConfig::with_role).
+	pub fn supported_roles(&self) -> &[VoiceRole] {
+		&self.roles
+	}
+
+	/// Returns the speaking styles supported by this voice, e.g. `cheerful` or `sad`, lowercased & deduplicated.
+	pub fn list_styles(&self) -> &[Box<str>] {
+		&self.styles
+	}
+
+	/// Returns `true` if this voice supports the given speaking style, i.e. for use with
+	/// [`UtteranceConfig::with_voice_style`](crate::UtteranceConfig::with_voice_style). Matching is case-insensitive.
+	///
+	/// The voice's [default style](VoiceInfo::default_style) is always considered supported.
+	pub fn supports_style(&self, style: &str) -> bool {
+		self.styles
+			.iter()
+			.chain(self.default_style.as_ref())
+			.any(|s| s.eq_ignore_ascii_case(style))
+	}
+
+	/// Returns the name of the voice's neutral speaking style, if it has one.
+	pub fn default_style(&self) -> Option<&str> {
+		self.default_style.as_deref()
+	}
+
+	/// Returns `true` if this voice's locale matches the given BCP-47 tag.
+	///
+	/// Matching is case-insensitive. A tag containing only a language subtag (i.e. `en`) will match any voice of that
+	/// language (`en-US`, `en-GB`, ...).
+	pub fn matches_locale(&self, locale: &str) -> bool {
+		locale_matches(&self.locale, locale)
+	}
+}
+
+fn add_styles<S: Into<Box<str>>>(styles: &mut Vec<Box<str>>, new: impl IntoIterator<Item = S>) {
+	for style in new {
+		let style: Box<str> = style.into().to_ascii_lowercase().into_boxed_str();
+		if !styles.contains(&style) {
+			styles.push(style);
+		}
+	}
+}
+
+/// Deserializes a voice's styles, normalising them as in [`VoiceInfo::with_styles`].
+#[cfg(feature = "serde")]
+fn deserialize_styles<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<Box<str>>, D::Error> {
+	let mut styles = Vec::new();
+	add_styles(&mut styles, <Vec<Box<str>> as serde::Deserialize>::deserialize(deserializer)?);
+	Ok(styles)
+}
+
+fn locale_matches(tag: &str, locale: &str) -> bool {
+	if tag.eq_ignore_ascii_case(locale) {
+		return true;
+	}
+	match tag.split_once('-') {
+		Some((language, _)) => !locale.contains('-') && language.eq_ignore_ascii_case(locale),
+		None => false
+	}
+}
+
+/// Metadata describing a single language supported by a speech synthesiser, as returned by
+/// [`SpeechSynthesiser::supported_languages`](crate::SpeechSynthesiser::supported_languages).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LanguageInfo {
+	/// The BCP-47 tag of the language, e.g. `en-US`.
+	pub bcp47: Box<str>,
+	/// The human-readable name of the language, e.g. `English (United States)`.
+	pub display_name: Box<str>,
+	/// The number of voices supporting this language.
+	pub voice_count: u32,
+	/// The number of [neural](VoiceInfo::neural) voices supporting this language.
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub neural_voice_count: u32
+}
+
+impl LanguageInfo {
+	pub fn new(bcp47: impl Into<Box<str>>, display_name: impl Into<Box<str>>) -> Self {
+		LanguageInfo {
+			bcp47: bcp47.into(),
+			display_name: display_name.into(),
+			voice_count: 0,
+			neural_voice_count: 0
+		}
+	}
+
+	pub fn with_voice_count(mut self, count: u32) -> Self {
+		self.voice_count = count;
+		self
+	}
+
+	pub fn with_neural_voice_count(mut self, count: u32) -> Self {
+		self.neural_voice_count = count;
+		self
+	}
+
+	/// Returns `true` if any neural voices support this language.
+	pub fn is_neural_supported(&self) -> bool {
+		self.neural_voice_count > 0
+	}
+
+	/// Returns `true` if this language matches the given BCP-47 tag. See [`VoiceInfo::matches_locale`].
+	pub fn matches_locale(&self, locale: &str) -> bool {
+		locale_matches(&self.bcp47, locale)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::VoiceInfo;
+
+	#[test]
+	fn styles_are_deduplicated_and_lowercased() {
+		let voice = VoiceInfo::new("en-US-JennyNeural", "en-US")
+			.with_styles(["Cheerful", "sad", "CHEERFUL"])
+			.with_styles(["Sad", "whispering"]);
+		assert_eq!(voice.list_styles(), ["cheerful".into(), "sad".into(), "whispering".into()]);
+	}
+
+	#[test]
+	fn supports_style_is_case_insensitive() {
+		let voice = VoiceInfo::new("en-US-JennyNeural", "en-US")
+			.with_styles(["cheerful"])
+			.with_default_style("general");
+		assert!(voice.supports_style("Cheerful"));
+		assert!(voice.supports_style("GENERAL"));
+		assert!(!voice.supports_style("sad"));
+		assert_eq!(voice.default_style(), Some("general"));
+	}
+
+	#[cfg(feature = "serde")]
+	#[test]
+	fn deserialized_styles_are_normalised() {
+		let voice: VoiceInfo = serde_json::from_str(
+			r#"{"name":"en-US-JennyNeural","locale":"en-US","gender":null,"neural":true,"styles":["Cheerful","cheerful","Sad"],"default_style":null}"#
+		)
+		.unwrap();
+		assert_eq!(voice.list_styles(), ["cheerful".into(), "sad".into()]);
+	}
+}